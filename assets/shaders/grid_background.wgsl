@@ -5,6 +5,8 @@
 @group(2) @binding(0) var<uniform> color: vec4<f32>;
 @group(2) @binding(1) var<uniform> size: GridSize;
 @group(2) @binding(2) var<storage> grid: array<u32>;
+// Opacity of empty (x) and occupied (y) cells.
+@group(2) @binding(3) var<uniform> opacity: vec2<f32>;
 
 @fragment
 fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
@@ -14,9 +16,11 @@ fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
 
     var output_color = color;
     output_color *= f32((col + row) % u32(2)) * (CHECKERBOARD_LIGHT - CHECKERBOARD_DARK) + CHECKERBOARD_DARK;
-    let highlight = f32(grid[grid_index(size, row, col)]) * HIGHLIGHT_LEVEL;
+    let occupied = f32(grid[grid_index(size, row, col)]);
+    let highlight = occupied * HIGHLIGHT_LEVEL;
     output_color.r += highlight;
     output_color.g += highlight;
     output_color.b += highlight;
+    output_color.a *= mix(opacity.x, opacity.y, occupied);
     return output_color / 1.5;
 }
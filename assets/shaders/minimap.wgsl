@@ -8,6 +8,15 @@
 @group(2) @binding(3) var<uniform> viewport_size: vec2<f32>;
 @group(2) @binding(4) var<storage> grid: array<u32>;
 @group(2) @binding(5) var<storage> visibility_grid: array<f32>;
+@group(2) @binding(6) var<uniform> team_colors: array<vec4<f32>, 3>;
+
+// Grid values are 0 for empty cells, otherwise the team index plus one.
+fn team_color(code: u32) -> vec4<f32> {
+    if code == 0u {
+        return vec4<f32>(0.);
+    }
+    return team_colors[code - 1u];
+}
 
 @fragment
 fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
@@ -43,19 +52,21 @@ fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
     fog *= 0.3;
 
     var highlight = 0.;
-    highlight += f32(grid[grid_index(size, row, col)]);
-    highlight += f32(grid[grid_index(size, row + 1u, col + 0u)]);
-    highlight += f32(grid[grid_index(size, row + 0u, col + 1u)]);
-    highlight += f32(grid[grid_index(size, row - 1u, col + 0u)]);
-    highlight += f32(grid[grid_index(size, row + 0u, col - 1u)]);
-    highlight += f32(grid[grid_index(size, row + 1u, col + 1u)]);
-    highlight += f32(grid[grid_index(size, row - 1u, col - 1u)]);
-    highlight += f32(grid[grid_index(size, row - 1u, col + 1u)]);
-    highlight += f32(grid[grid_index(size, row + 1u, col - 1u)]);
+    var highlight_color = vec4<f32>(0.);
+    for (var dr = -1; dr <= 1; dr++) {
+        for (var dc = -1; dc <= 1; dc++) {
+            let code = grid[grid_index(size, u32(i32(row) + dr), u32(i32(col) + dc))];
+            highlight += f32(min(code, 1u));
+            highlight_color += team_color(code);
+        }
+    }
+    if highlight > 0. {
+        highlight_color /= highlight;
+    }
     highlight = min(highlight, HIGHLIGHT_LEVEL) * (1. - visible);
 
 
-    output_color += 30. * color * highlight;
+    output_color += 30. * highlight_color * highlight;
 
 
     // fog += f32(visibility_grid[grid_index(size, row, col)]);
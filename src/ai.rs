@@ -0,0 +1,244 @@
+use bevy::prelude::*;
+
+use crate::prelude::*;
+
+/// Plugin for computer-controlled opponents.
+/// Each team other than the player's gets an `AiController` that periodically gives orders.
+pub struct AiPlugin;
+impl Plugin for AiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (AiController::sync, AiController::update)
+                .chain()
+                .in_set(SystemStage::Compute),
+        );
+    }
+}
+
+/// What an AI controller is currently trying to do.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AiState {
+    /// Send idle workers after the nearest enemy.
+    #[default]
+    AttackNearest,
+}
+
+/// Gives orders to the units of one team.
+#[derive(Component, Debug)]
+pub struct AiController {
+    pub team: Team,
+    pub state: AiState,
+    pub timer: Timer,
+}
+impl AiController {
+    pub fn new(team: Team, interval: f32) -> Self {
+        Self {
+            team,
+            state: AiState::default(),
+            timer: Timer::from_seconds(interval, TimerMode::Repeating),
+        }
+    }
+
+    /// Keep one controller per non-player team, matching the configured decision interval.
+    pub fn sync(
+        mut controllers: Query<(Entity, &mut Self)>,
+        configs: Res<Configs>,
+        mut commands: Commands,
+    ) {
+        let enabled = configs.ai_decision_interval > 0.;
+        let mut teams: Vec<Team> = Team::ALL
+            .into_iter()
+            .filter(|&team| enabled && team != Team::None && team != configs.player_team)
+            .collect();
+        for (entity, mut controller) in &mut controllers {
+            if let Some(index) = teams.iter().position(|&team| team == controller.team) {
+                teams.swap_remove(index);
+                let interval = configs.ai_decision_interval;
+                if controller.timer.duration().as_secs_f32() != interval {
+                    controller.timer = Timer::from_seconds(interval, TimerMode::Repeating);
+                }
+            } else {
+                commands.entity(entity).despawn();
+            }
+        }
+        for team in teams {
+            commands.spawn((
+                Name::new(format!("{:?} AI", team)),
+                Self::new(team, configs.ai_decision_interval),
+            ));
+        }
+    }
+
+    /// Make a decision for each team whenever its timer finishes.
+    pub fn update(
+        mut controllers: Query<&mut Self>,
+        mut units: Query<(&Team, &Object, &Transform, &mut Objectives)>,
+        targets: Query<(&Team, &Object)>,
+        grid: EntityGrids,
+        time: Res<Time>,
+    ) {
+        let spec = grid.spec();
+        let search_radius = spec.world2d_bounds().size().length();
+        for mut controller in &mut controllers {
+            controller.timer.tick(time.delta());
+            if !controller.timer.just_finished() {
+                continue;
+            }
+            let ai_team = controller.team;
+            match controller.state {
+                AiState::AttackNearest => {
+                    let is_enemy = |entity| {
+                        targets
+                            .get(entity)
+                            .is_ok_and(|(&team, object)| Self::is_enemy(ai_team, team, *object))
+                    };
+                    for (&team, object, transform, mut objectives) in &mut units {
+                        if team != ai_team
+                            || *object != Object::Worker
+                            || !Self::is_idle(team, &objectives, &targets)
+                        {
+                            continue;
+                        }
+                        let position = transform.translation.xy();
+                        if let Some((enemy, _)) =
+                            grid.get_nearest(position, search_radius, is_enemy)
+                        {
+                            objectives.push(Objective::FollowEntity(enemy));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns true if a unit has no orders beyond following its own team's head,
+    /// which is how workers spawned by a head start out.
+    pub fn is_idle(team: Team, objectives: &Objectives, targets: &Query<(&Team, &Object)>) -> bool {
+        match objectives.last() {
+            Objective::None => true,
+            Objective::FollowEntity(entity) => {
+                !objectives.has_queued()
+                    && targets.get(*entity).is_ok_and(|(&other_team, object)| {
+                        other_team == team && *object == Object::Head
+                    })
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns true if `team` should attack an `object` on `other_team`.
+    pub fn is_enemy(team: Team, other_team: Team, object: Object) -> bool {
+        other_team != team
+            && other_team != Team::None
+            && matches!(object, Object::Worker | Object::Head)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::prelude::*;
+
+    use crate::prelude::*;
+
+    use super::AiController;
+
+    #[test]
+    fn test_attack_nearest() {
+        let spec = GridSpec {
+            rows: 10,
+            cols: 10,
+            width: 10.0,
+            ..default()
+        };
+        let mut world = World::new();
+        world.insert_resource(Configs {
+            player_team: Team::Blue,
+            ai_decision_interval: 1.,
+            ..default()
+        });
+        world.init_resource::<Time>();
+        let mut grid = Grid2::<EntitySet>::default();
+        grid.resize_with(spec.clone());
+        world.insert_resource(spec);
+
+        let mut spawn = |team: Team, object: Object, position: Vec2, objectives: Objectives| {
+            let entity = world
+                .spawn((
+                    team,
+                    object,
+                    Transform::from_translation(position.extend(0.)),
+                    objectives,
+                ))
+                .id();
+            grid.update_entity(entity, None, position);
+            entity
+        };
+        let ai_head = spawn(
+            Team::Red,
+            Object::Head,
+            Vec2::new(-40., -40.),
+            Objectives::default(),
+        );
+        // Workers start out following the head that spawned them, like in `ZooidHead::spawn_zooids`.
+        let ai_worker = spawn(
+            Team::Red,
+            Object::Worker,
+            Vec2::new(-30., -30.),
+            Objectives::new(Objective::FollowEntity(ai_head)),
+        );
+        let player_worker = spawn(
+            Team::Blue,
+            Object::Worker,
+            Vec2::new(30., 30.),
+            Objectives::default(),
+        );
+        let _near_food = spawn(
+            Team::None,
+            Object::Food,
+            Vec2::new(-20., -30.),
+            Objectives::default(),
+        );
+        let _far_head = spawn(
+            Team::Blue,
+            Object::Head,
+            Vec2::new(40., 40.),
+            Objectives::default(),
+        );
+        world.insert_resource(grid);
+        let mut schedule = Schedule::default();
+        schedule.add_systems((AiController::sync, AiController::update).chain());
+
+        // Only non-player teams get a controller.
+        schedule.run(&mut world);
+        let mut controllers = world.query::<&AiController>();
+        let teams: Vec<Team> = controllers.iter(&world).map(|c| c.team).collect();
+        assert_eq!(teams, vec![Team::Red]);
+        assert_eq!(
+            world.get::<Objectives>(ai_worker).unwrap().last(),
+            &Objective::FollowEntity(ai_head)
+        );
+
+        // Once the decision interval passes, idle AI workers go after the nearest enemy.
+        world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(1.));
+        schedule.run(&mut world);
+        assert_eq!(
+            world.get::<Objectives>(ai_worker).unwrap().last(),
+            &Objective::FollowEntity(player_worker)
+        );
+        assert_eq!(
+            world.get::<Objectives>(player_worker).unwrap().last(),
+            &Objective::None
+        );
+
+        // Switching the player's team hands control of the other team to the AI.
+        world.resource_mut::<Configs>().player_team = Team::Red;
+        schedule.run(&mut world);
+        let teams: Vec<Team> = controllers.iter(&world).map(|c| c.team).collect();
+        assert_eq!(teams, vec![Team::Blue]);
+    }
+}
@@ -0,0 +1,133 @@
+use std::time::Duration;
+
+use bevy::{audio::Volume, prelude::*};
+
+use crate::{objects::GameOverEvent, prelude::*};
+
+/// Plugin for sound effects.
+/// Sounds are generated tones, so no audio files are needed.
+pub struct AudioPlugin;
+impl Plugin for AudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SoundAssets>().add_systems(
+            FixedUpdate,
+            SoundAssets::update
+                .in_set(SystemStage::Despawn)
+                .after(GameOverEvent::update),
+        );
+    }
+}
+
+/// Handles to the sounds played for game events.
+#[derive(Resource)]
+pub struct SoundAssets {
+    pub hit: Handle<Pitch>,
+    pub spawn: Handle<Pitch>,
+    pub game_over: Handle<Pitch>,
+}
+impl FromWorld for SoundAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mut pitches = world.get_resource_mut::<Assets<Pitch>>().unwrap();
+        Self {
+            hit: pitches.add(Pitch::new(220., Duration::from_millis(40))),
+            spawn: pitches.add(Pitch::new(660., Duration::from_millis(60))),
+            game_over: pitches.add(Pitch::new(330., Duration::from_millis(600))),
+        }
+    }
+}
+impl SoundAssets {
+    /// Maximum number of copies of the same sound started per frame.
+    pub const MAX_PER_FRAME: usize = 3;
+
+    /// Controls that play the spawn sound.
+    pub const SPAWN_ACTIONS: [ControlAction; 4] = [
+        ControlAction::SpawnHead,
+        ControlAction::SpawnZooid,
+        ControlAction::SpawnPlankton,
+        ControlAction::SpawnFood,
+    ];
+
+    /// Play sounds for this frame's events, capping repeats of each sound.
+    pub fn update(
+        mut damage_events: EventReader<DamageEvent>,
+        mut control_events: EventReader<ControlEvent>,
+        mut game_over_events: EventReader<GameOverEvent>,
+        assets: Res<Self>,
+        configs: Res<Configs>,
+        mut commands: Commands,
+    ) {
+        let hits = damage_events.read().count();
+        let spawns = control_events
+            .read()
+            .filter(|event| {
+                Self::SPAWN_ACTIONS
+                    .iter()
+                    .any(|&action| event.is_pressed(action))
+            })
+            .count();
+        let game_overs = game_over_events.read().count();
+
+        for (sound, count) in [
+            (&assets.hit, hits),
+            (&assets.spawn, spawns),
+            (&assets.game_over, game_overs),
+        ] {
+            for _ in 0..count.min(Self::MAX_PER_FRAME) {
+                commands.spawn((
+                    Name::new("sound"),
+                    PitchBundle {
+                        source: sound.clone(),
+                        settings: PlaybackSettings::DESPAWN
+                            .with_volume(Volume::new(configs.sfx_volume)),
+                    },
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{ecs::system::RunSystemOnce, prelude::*};
+
+    use crate::{objects::GameOverEvent, prelude::*};
+
+    use super::SoundAssets;
+
+    #[test]
+    fn test_sound_debounce() {
+        let mut world = World::new();
+        world.init_resource::<Assets<Pitch>>();
+        world.init_resource::<SoundAssets>();
+        world.insert_resource(Configs {
+            sfx_volume: 0.5,
+            ..default()
+        });
+        world.init_resource::<Events<DamageEvent>>();
+        world.init_resource::<Events<ControlEvent>>();
+        world.init_resource::<Events<GameOverEvent>>();
+
+        for _ in 0..50 {
+            world.send_event(DamageEvent {
+                damager: Entity::PLACEHOLDER,
+                damaged: Entity::PLACEHOLDER,
+                amount: 1,
+                velocity: Velocity::ZERO,
+            });
+        }
+        world.send_event(GameOverEvent {
+            winner: Some(Team::Blue),
+        });
+        world.run_system_once(SoundAssets::update);
+
+        let assets = world.resource::<SoundAssets>();
+        let (hit, game_over) = (assets.hit.clone(), assets.game_over.clone());
+        let mut query = world.query::<(&Handle<Pitch>, &PlaybackSettings)>();
+        let sounds: Vec<_> = query.iter(&world).collect();
+        let count = |handle: &Handle<Pitch>| sounds.iter().filter(|(h, _)| *h == handle).count();
+        assert_eq!(count(&hit), SoundAssets::MAX_PER_FRAME);
+        assert_eq!(count(&game_over), 1);
+        assert_eq!(sounds.len(), SoundAssets::MAX_PER_FRAME + 1);
+        assert_eq!(sounds[0].1.volume.get(), 0.5);
+    }
+}
@@ -3,8 +3,9 @@ use bevy::{
         bloom::{BloomCompositeMode, BloomPrefilterSettings, BloomSettings},
         tonemapping::Tonemapping,
     },
+    input::mouse::{MouseScrollUnit, MouseWheel},
     prelude::*,
-    window::PrimaryWindow,
+    window::{PrimaryWindow, WindowResized},
 };
 
 use crate::cursor::CursorAssets;
@@ -21,8 +22,12 @@ impl Plugin for CameraPlugin {
             .add_systems(
                 FixedUpdate,
                 (
-                    CameraController::update_bounds.after(window::resize_window),
-                    CameraController::update,
+                    CameraController::update_zoom,
+                    CameraController::update_bounds
+                        .after(window::resize_window)
+                        .after(CameraController::update_zoom),
+                    CameraController::update_follow,
+                    CameraController::update.after(CameraController::update_follow),
                     CameraController::update_drag,
                     CameraController::pan_to_position,
                 ),
@@ -79,9 +84,19 @@ impl MainCamera {
 pub struct CameraController {
     pub enabled: bool,
     pub initialized: bool,
-    pub sensitivity: f32,
     pub velocity: Vec2,
     pub last_drag_position: Option<Vec2>,
+    /// Centroid of the selection being followed, cleared by manual panning.
+    pub follow_target: Option<Vec2>,
+    /// Rate at which the camera approaches `follow_target`.
+    pub follow_speed: f32,
+    /// Fractional change in zoom per scroll line.
+    pub zoom_sensitivity: f32,
+    /// Rate at which the projection scale approaches `target_zoom`.
+    pub zoom_speed: f32,
+    pub min_zoom: f32,
+    pub max_zoom: f32,
+    pub target_zoom: f32,
     world2d_bounds: Aabb2,
 }
 impl Default for CameraController {
@@ -89,9 +104,15 @@ impl Default for CameraController {
         Self {
             enabled: true,
             initialized: false,
-            sensitivity: 1000.0,
             velocity: Vec2::ZERO,
             last_drag_position: None,
+            follow_target: None,
+            follow_speed: 5.0,
+            zoom_sensitivity: 0.1,
+            zoom_speed: 10.0,
+            min_zoom: 0.25,
+            max_zoom: 2.0,
+            target_zoom: 1.0,
             world2d_bounds: Aabb2::default(),
         }
     }
@@ -101,13 +122,24 @@ impl CameraController {
     fn update_bounds(
         grid_spec: Res<GridSpec>,
         configs: Res<Configs>,
-        mut controller_query: Query<(&mut Self, &Camera, &GlobalTransform), With<MainCamera>>,
+        mut controller_query: Query<
+            (
+                &mut Self,
+                &Camera,
+                &GlobalTransform,
+                Ref<OrthographicProjection>,
+            ),
+            With<MainCamera>,
+        >,
         window: Query<&Window, With<PrimaryWindow>>,
+        mut resized: EventReader<WindowResized>,
     ) {
-        if !(grid_spec.is_changed() || configs.is_changed()) {
+        let (mut controller, camera, camera_transform, projection) = controller_query.single_mut();
+        // Zooming, resizing, and switching window modes change the visible world size.
+        let resized = resized.read().count() > 0;
+        if !(grid_spec.is_changed() || configs.is_changed() || projection.is_changed() || resized) {
             return;
         }
-        let (mut controller, camera, camera_transform) = controller_query.single_mut();
         if let Some(world2d_size) =
             Self::get_world2d_size(camera, camera_transform, window.single())
         {
@@ -139,6 +171,35 @@ impl CameraController {
         Some(camera_max - camera_min)
     }
 
+    /// Zoom with the scroll wheel, smoothly approaching the target scale.
+    pub fn update_zoom(
+        time: Res<Time>,
+        mut wheel_events: EventReader<MouseWheel>,
+        mut controller_query: Query<(&mut Self, &mut OrthographicProjection), With<MainCamera>>,
+    ) {
+        let (mut controller, mut projection) = controller_query.single_mut();
+        for event in wheel_events.read() {
+            let lines = match event.unit {
+                MouseScrollUnit::Line => event.y,
+                MouseScrollUnit::Pixel => event.y / 16.,
+            };
+            controller.target_zoom = (controller.target_zoom
+                * (1. - lines * controller.zoom_sensitivity))
+                .clamp(controller.min_zoom, controller.max_zoom);
+        }
+
+        let delta = controller.target_zoom - projection.scale;
+        if delta == 0. {
+            return;
+        }
+        if delta.abs() < 1e-3 {
+            projection.scale = controller.target_zoom;
+        } else {
+            let alpha = (controller.zoom_speed * time.delta_seconds()).min(1.);
+            projection.scale += delta * alpha;
+        }
+    }
+
     pub fn update_drag(
         mut controller_query: Query<(&mut Self, &mut Transform), With<MainCamera>>,
         cursor: Query<&GlobalTransform, (With<Cursor>, Without<MainCamera>)>,
@@ -151,6 +212,7 @@ impl CameraController {
 
         // Middle mouse drag
         if mouse_input.pressed(MouseButton::Middle) {
+            controller.follow_target = None;
             let delta = if let Some(last_drag_position) = controller.last_drag_position {
                 let delta = last_drag_position - cursor_position;
                 camera_transform.translation += delta.extend(0.);
@@ -171,8 +233,18 @@ impl CameraController {
         });
     }
 
+    /// Keys that pan the camera when `Configs::keyboard_pan` is enabled.
+    pub const PAN_KEYS: [(KeyCode, Vec2); 4] = [
+        (KeyCode::ArrowLeft, Vec2::NEG_X),
+        (KeyCode::ArrowRight, Vec2::X),
+        (KeyCode::ArrowUp, Vec2::Y),
+        (KeyCode::ArrowDown, Vec2::NEG_Y),
+    ];
+
     pub fn update(
         time: Res<Time>,
+        configs: Res<Configs>,
+        keyboard: Res<ButtonInput<KeyCode>>,
         mut controller_query: Query<(&mut Self, &mut Transform), With<MainCamera>>,
         window_query: Query<&Window, With<PrimaryWindow>>,
         mut event_writer: EventWriter<CameraMoveEvent>,
@@ -181,14 +253,12 @@ impl CameraController {
         let window = window_query.single();
         let (mut controller, mut camera_transform) = controller_query.single_mut();
 
-        // let cursor = cursor.single();
-        // let cursor_position = cursor.translation.xy();
         let mut acceleration = Vec2::ZERO;
         controller.velocity = Vec2::ZERO;
         let window_size = window.scaled_size();
 
         if let Some(centered_cursor_position) = window.cursor_position() {
-            let boundary = 1.;
+            let boundary = configs.pan_border;
             // Screen border panning.
             acceleration += if centered_cursor_position.x < boundary {
                 -Vec2::X
@@ -204,34 +274,70 @@ impl CameraController {
             } else {
                 Vec2::ZERO
             };
-
-            controller.velocity += acceleration;
+        }
+        if configs.keyboard_pan {
+            for (key, direction) in Self::PAN_KEYS {
+                if keyboard.pressed(key) {
+                    acceleration += direction;
+                }
+            }
+        }
+        if acceleration != Vec2::ZERO {
+            controller.follow_target = None;
+            controller.velocity += acceleration.clamp(Vec2::NEG_ONE, Vec2::ONE);
             camera_transform.translation +=
-                controller.velocity.extend(0.) * dt * controller.sensitivity;
-            controller
-                .world2d_bounds
-                .clamp3(&mut camera_transform.translation);
-            event_writer.send(CameraMoveEvent {
-                position: camera_transform.translation.xy(),
-            });
+                controller.velocity.extend(0.) * dt * configs.cursor_sensitivity;
+        } else if let Some(target) = controller.follow_target {
+            let alpha = (controller.follow_speed * dt).min(1.);
+            let position = camera_transform.translation.xy().lerp(target, alpha);
+            camera_transform.translation = position.extend(camera_transform.translation.z);
+        } else {
+            return;
+        }
+        controller
+            .world2d_bounds
+            .clamp3(&mut camera_transform.translation);
+        event_writer.send(CameraMoveEvent {
+            position: camera_transform.translation.xy(),
+        });
+    }
+
+    /// Start following the selection on command, and track its centroid while following.
+    pub fn update_follow(
+        mut control_events: EventReader<ControlEvent>,
+        mut controller_query: Query<&mut Self, With<MainCamera>>,
+        selected: Query<(&Selected, &GlobalTransform)>,
+    ) {
+        let mut controller = controller_query.single_mut();
+        let follow = control_events
+            .read()
+            .any(|control| control.is_pressed(ControlAction::FollowSelection));
+        if !follow && controller.follow_target.is_none() {
+            return;
         }
+
+        let (sum, count) = selected
+            .iter()
+            .filter(|(selected, _transform)| selected.is_selected())
+            .fold((Vec2::ZERO, 0), |(sum, count), (_selected, transform)| {
+                (sum + transform.translation().xy(), count + 1)
+            });
+        // Stop following once the selection is empty.
+        controller.follow_target = (count > 0).then(|| sum / count as f32);
     }
 
     pub fn pan_to_position(
         mut control_events: EventReader<ControlEvent>,
-        mut camera: Query<(&CameraController, &mut Transform), With<MainCamera>>,
+        mut camera: Query<(&mut CameraController, &mut Transform), With<MainCamera>>,
     ) {
-        for &ControlEvent {
-            action,
-            state: _,
-            position,
-        } in control_events.read()
-        {
-            if action != ControlAction::PanCamera {
+        for event in control_events.read() {
+            // Releasing the pan button shouldn't jump the camera again.
+            if !event.is_pressed(ControlAction::PanCamera) {
                 continue;
             }
-            let (controller, mut camera_transform) = camera.single_mut();
-            controller.set_position(&mut camera_transform, position);
+            let (mut controller, mut camera_transform) = camera.single_mut();
+            controller.follow_target = None;
+            controller.set_position(&mut camera_transform, event.position);
         }
     }
 
@@ -242,3 +348,135 @@ impl CameraController {
             .clamp3(&mut camera_transform.translation)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{
+        ecs::system::RunSystemOnce,
+        input::{
+            mouse::{MouseScrollUnit, MouseWheel},
+            ButtonState,
+        },
+        prelude::*,
+    };
+
+    use crate::prelude::*;
+
+    use super::{CameraController, MainCamera};
+
+    #[test]
+    fn test_zoom() {
+        let mut world = World::new();
+        world.init_resource::<Events<MouseWheel>>();
+        let mut time = Time::<()>::default();
+        time.advance_by(Duration::from_millis(50));
+        world.insert_resource(time);
+        let camera = world
+            .spawn((
+                MainCamera,
+                CameraController::default(),
+                OrthographicProjection::default(),
+            ))
+            .id();
+        let scale = |world: &World| world.get::<OrthographicProjection>(camera).unwrap().scale;
+
+        // Scrolling out moves smoothly toward the target.
+        for _ in 0..100 {
+            world.send_event(MouseWheel {
+                unit: MouseScrollUnit::Line,
+                x: 0.,
+                y: -1.,
+                window: Entity::PLACEHOLDER,
+            });
+        }
+        world.run_system_once(CameraController::update_zoom);
+        let controller = world.get::<CameraController>(camera).unwrap();
+        assert_eq!(controller.target_zoom, controller.max_zoom);
+        assert!(scale(&world) > 1. && scale(&world) < controller.max_zoom);
+
+        // Eventually settles at the clamped target.
+        for _ in 0..100 {
+            world.run_system_once(CameraController::update_zoom);
+        }
+        assert_eq!(scale(&world), 2.);
+    }
+
+    #[test]
+    fn test_follow_selection() {
+        let mut world = World::new();
+        world.init_resource::<Events<ControlEvent>>();
+        let camera = world.spawn((MainCamera, CameraController::default())).id();
+        let child_entity = world.spawn_empty().id();
+        let mut spawn = |position: Vec2, selected: Selected| {
+            world
+                .spawn((
+                    selected,
+                    GlobalTransform::from_translation(position.extend(0.)),
+                ))
+                .id()
+        };
+        let a = spawn(Vec2::new(0., 0.), Selected::Selected { child_entity });
+        spawn(Vec2::new(10., 10.), Selected::Selected { child_entity });
+        spawn(Vec2::new(100., 100.), Selected::Unselected);
+        let follow_target =
+            |world: &World| world.get::<CameraController>(camera).unwrap().follow_target;
+
+        world.run_system_once(CameraController::update_follow);
+        assert_eq!(follow_target(&world), None);
+
+        world.send_event(ControlEvent {
+            action: ControlAction::FollowSelection,
+            state: ButtonState::Pressed,
+            position: Vec2::ZERO,
+            entity: None,
+        });
+        world.run_system_once(CameraController::update_follow);
+        world.resource_mut::<Events<ControlEvent>>().clear();
+        assert_eq!(follow_target(&world), Some(Vec2::new(5., 5.)));
+
+        // The centroid tracks the living selection.
+        world.despawn(a);
+        world.run_system_once(CameraController::update_follow);
+        assert_eq!(follow_target(&world), Some(Vec2::new(10., 10.)));
+
+        // Following stops once nothing is selected.
+        for mut selected in world.query::<&mut Selected>().iter_mut(&mut world) {
+            *selected = Selected::Unselected;
+        }
+        world.run_system_once(CameraController::update_follow);
+        assert_eq!(follow_target(&world), None);
+    }
+
+    #[test]
+    fn test_pan_to_position() {
+        let mut world = World::new();
+        world.init_resource::<Events<ControlEvent>>();
+        let controller = CameraController {
+            world2d_bounds: Aabb2 {
+                min: Vec2::splat(-100.),
+                max: Vec2::splat(100.),
+            },
+            ..default()
+        };
+        let camera = world
+            .spawn((MainCamera, controller, Transform::default()))
+            .id();
+        let pan = |world: &mut World, state, position| {
+            world.send_event(ControlEvent {
+                action: ControlAction::PanCamera,
+                state,
+                position,
+                entity: None,
+            });
+            world.run_system_once(CameraController::pan_to_position);
+            world.resource_mut::<Events<ControlEvent>>().clear();
+            world.get::<Transform>(camera).unwrap().translation.xy()
+        };
+
+        assert_eq!(pan(&mut world, ButtonState::Pressed, Vec2::X), Vec2::X);
+        // Releasing leaves the camera where it was panned to.
+        assert_eq!(pan(&mut world, ButtonState::Released, Vec2::Y), Vec2::X);
+    }
+}
@@ -1,7 +1,11 @@
+use std::ops::Range;
+
 use bevy::prelude::*;
+use bevy::reflect::GetPath;
 use bevy::utils::HashMap;
+use clap::ValueEnum;
 
-use crate::objects::{InteractionConfig, ObjectConfig, TestInteractionConfigs};
+use crate::objects::{InteractionConfig, ObjectConfig, TargetPriority, TestInteractionConfigs};
 use crate::prelude::*;
 
 pub struct ConfigPlugin;
@@ -18,9 +22,17 @@ impl Plugin for ConfigPlugin {
             .register_type::<HashMap<Object, ObjectConfig>>()
             .register_type::<HashMap<Object, InteractionConfig>>()
             .register_type::<ObjectConfig>()
+            .register_type::<TargetPriority>()
             .register_type::<ObjectConfigs>()
             .register_type::<InteractionConfigs>()
-            .register_type::<Configs>();
+            .register_type::<Configs>()
+            .register_type::<StartingUnits>()
+            .register_type::<StartingHead>()
+            .register_type::<Vec<StartingHead>>()
+            .register_type::<Range<u64>>()
+            .register_type_data::<Range<u64>, ReflectSerialize>()
+            .register_type_data::<Range<u64>, ReflectDeserialize>()
+            .add_systems(FixedUpdate, Configs::clamp_radii.in_set(SystemStage::Spawn));
     }
 }
 
@@ -33,8 +45,177 @@ pub struct Configs {
     pub visibility_radius: u16,
     pub fog_radius: u16,
     pub window_size: Vec2,
+    // Camera pan speed in world units per second.
     pub cursor_sensitivity: f32,
+    // Distance in pixels from the window edge that triggers border panning.
+    pub pan_border: f32,
+    // Pan the camera with the arrow keys.
+    pub keyboard_pan: bool,
+    // Max seconds between clicks on the same unit to select all units of its type on screen.
+    pub double_click_seconds: f32,
+    // Volume of sound effects, where 1.0 is full volume.
+    pub sfx_volume: f32,
+    // Additional path cost per enemy unit in a cell. Zero disables enemy avoidance.
+    pub enemy_influence_weight: f32,
+    // Seed for simulation randomness, so runs are reproducible.
+    pub seed: u64,
+    // Distance beyond the camera view within which objects keep flocking.
+    pub cull_margin: f32,
+    // Group moves keep units' positions relative to the group's centroid,
+    // instead of packing them into a square formation.
+    pub preserve_formation: bool,
+    // Draw the route from each selected unit to its waypoint.
+    pub show_paths: bool,
+    // Draw cell borders and RowCol labels over the grid.
+    pub show_grid_coords: bool,
+    // CSV file that per-step metrics are appended to. Empty disables metrics.
+    pub metrics_path: String,
+    // Seconds between orders from the AI of non-player teams. Zero disables the AI.
+    pub ai_decision_interval: f32,
+    // Uniform world-space current added to the acceleration of every moving object each step.
+    pub current: Vec2,
+    // Units spawned when a scene is loaded.
+    pub starting_units: StartingUnits,
 
     // Configs per object type.
     pub objects: ObjectConfigs,
 }
+
+/// Heads spawned for each team when a scene is loaded.
+#[derive(Reflect, Default, Clone, Debug, PartialEq)]
+pub struct StartingUnits {
+    pub heads: Vec<StartingHead>,
+}
+
+/// A head spawned at scene start.
+#[derive(Reflect, Default, Clone, Debug, PartialEq)]
+pub struct StartingHead {
+    pub team: Team,
+    pub position: Vec2,
+}
+
+impl Configs {
+    /// Upper bound on grid radii, since radius loops scale quadratically.
+    pub const MAX_RADIUS: u16 = 64;
+
+    /// Returns the largest radius allowed for the given grid.
+    pub fn max_radius(spec: &GridSpec) -> u16 {
+        (spec.rows.max(spec.cols) / 2).clamp(1, Self::MAX_RADIUS)
+    }
+
+    /// Keep visibility and fog radii within sane bounds of the grid size.
+    pub fn clamp_radii(mut configs: ResMut<Self>, spec: Res<GridSpec>) {
+        if !configs.is_changed() && !spec.is_changed() {
+            return;
+        }
+        let max_radius = Self::max_radius(&spec);
+        if configs.visibility_radius > max_radius {
+            warn!(
+                "visibility_radius {} too large, clamping to {}.",
+                configs.visibility_radius, max_radius
+            );
+            configs.visibility_radius = max_radius;
+        }
+        if configs.fog_radius > configs.visibility_radius {
+            warn!(
+                "fog_radius {} larger than visibility_radius, clamping to {}.",
+                configs.fog_radius, configs.visibility_radius
+            );
+            configs.fog_radius = configs.visibility_radius;
+        }
+        let too_large: Vec<Object> = configs
+            .objects
+            .iter()
+            .filter(|(_, config)| config.visibility_radius > max_radius)
+            .map(|(&object, _)| object)
+            .collect();
+        for object in too_large {
+            let config = configs.objects.get_mut(&object).unwrap();
+            warn!(
+                "{:?} visibility_radius {} too large, clamping to {}.",
+                object, config.visibility_radius, max_radius
+            );
+            config.visibility_radius = max_radius;
+        }
+    }
+
+    /// Set a numeric or boolean field from a reflection path, e.g. `sfx_volume`.
+    /// Paths starting with an object name set fields of that object's config,
+    /// e.g. `worker.neighbor_radius`.
+    pub fn set_path(&mut self, path: &str, value: &str) -> Result<(), String> {
+        let object_path = path
+            .split_once('.')
+            .and_then(|(object, rest)| Some((Object::from_str(object, true).ok()?, rest)));
+        let field = match object_path {
+            Some((object, rest)) => {
+                let config = self
+                    .objects
+                    .get_mut(&object)
+                    .ok_or_else(|| format!("no config for {:?}", object))?;
+                config.reflect_path_mut(rest)
+            }
+            _ => self.reflect_path_mut(path),
+        }
+        .map_err(|_| format!("unknown path: {}", path))?;
+        Self::set_value(field, value)
+    }
+
+    /// Parse `value` into a reflected field of a supported primitive type.
+    fn set_value(field: &mut dyn Reflect, value: &str) -> Result<(), String> {
+        fn parse<T: std::str::FromStr + Reflect>(
+            field: &mut dyn Reflect,
+            value: &str,
+        ) -> Option<Result<(), String>> {
+            let field = field.downcast_mut::<T>()?;
+            Some(match value.parse() {
+                Ok(parsed) => {
+                    *field = parsed;
+                    Ok(())
+                }
+                Err(_) => Err(format!(
+                    "expected {}, got {}",
+                    std::any::type_name::<T>(),
+                    value
+                )),
+            })
+        }
+        parse::<f32>(field, value)
+            .or_else(|| parse::<f64>(field, value))
+            .or_else(|| parse::<u8>(field, value))
+            .or_else(|| parse::<u16>(field, value))
+            .or_else(|| parse::<u32>(field, value))
+            .or_else(|| parse::<u64>(field, value))
+            .or_else(|| parse::<usize>(field, value))
+            .or_else(|| parse::<i32>(field, value))
+            .or_else(|| parse::<bool>(field, value))
+            .unwrap_or_else(|| Err(format!("unsupported type: {}", field.reflect_type_path())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{objects::ObjectConfig, prelude::*};
+
+    #[test]
+    fn test_set_path() {
+        let mut configs = Configs::default();
+        configs
+            .objects
+            .insert(Object::Worker, ObjectConfig::default());
+
+        configs.set_path("sfx_volume", "0.75").unwrap();
+        assert_eq!(configs.sfx_volume, 0.75);
+        configs.set_path("keyboard_pan", "true").unwrap();
+        assert!(configs.keyboard_pan);
+        configs.set_path("worker.neighbor_radius", "200").unwrap();
+        assert_eq!(configs.objects[&Object::Worker].neighbor_radius, 200.);
+        configs.set_path("Worker.visibility_radius", "3").unwrap();
+        assert_eq!(configs.objects[&Object::Worker].visibility_radius, 3);
+
+        assert!(configs.set_path("worker.missing", "1").is_err());
+        assert!(configs.set_path("head.neighbor_radius", "1").is_err());
+        assert!(configs.set_path("sfx_volume", "loud").is_err());
+        assert!(configs.set_path("player_team", "1").is_err());
+        assert_eq!(configs.sfx_volume, 0.75);
+    }
+}
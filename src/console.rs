@@ -1,6 +1,14 @@
-use crate::{objects::ObjectSpec, prelude::*};
+use crate::{
+    cursor::CursorConfig,
+    metrics::Metrics,
+    objects::{ObjectSpec, TeamResources},
+    prelude::*,
+    scene::LoadSceneEvent,
+};
 use bevy::prelude::*;
-use bevy_console::{reply, AddConsoleCommand, ConsoleCommand, ConsolePlugin};
+use bevy_console::{
+    reply, reply_failed, reply_ok, AddConsoleCommand, ConsoleCommand, ConsolePlugin,
+};
 use clap::Parser;
 
 /// Plugin for input action events.
@@ -8,47 +16,280 @@ pub struct CustomConsolePlugin;
 impl Plugin for CustomConsolePlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(ConsolePlugin)
-            .add_console_command::<SpawnCommand, _>(SpawnCommand::update);
+            .add_console_command::<SpawnCommand, _>(SpawnCommand::update)
+            .add_console_command::<LoadCommand, _>(LoadCommand::update)
+            .add_console_command::<GridStatsCommand, _>(GridStatsCommand::update)
+            .add_console_command::<SetCommand, _>(SetCommand::update)
+            .add_console_command::<ResourcesCommand, _>(ResourcesCommand::update)
+            .add_console_command::<MetricsCommand, _>(MetricsCommand::update)
+            .add_console_command::<CursorCommand, _>(CursorCommand::update);
     }
 }
 
-/// Example command
+/// Spawn `count` objects in a square around world position (x, y).
 #[derive(Parser, ConsoleCommand)]
 #[command(name = "spawn")]
 struct SpawnCommand {
-    count: usize,
-    team: Team,
     object: Object,
+    team: Team,
+    #[arg(allow_negative_numbers = true)]
+    x: f32,
+    #[arg(allow_negative_numbers = true)]
+    y: f32,
+    #[arg(default_value_t = 1)]
+    count: usize,
 }
 impl SpawnCommand {
+    /// Spacing between spawned objects.
+    const SPACING: f32 = 10.;
+
     pub fn update(
         mut log: ConsoleCommand<SpawnCommand>,
         mut commands: ObjectCommands,
-        cursor: Query<&GlobalTransform, With<Cursor>>,
+        grid_spec: Res<GridSpec>,
     ) {
-        if let Some(Ok(SpawnCommand {
-            object,
-            team,
-            count,
-        })) = log.take()
-        {
-            reply!(log, "spawning {} {:?}", count, object);
-            let cursor_position = cursor.single().translation().xy();
-            let sqrt_count = (count as f32).sqrt() as usize;
-            for i in 0..sqrt_count {
-                for j in 0..sqrt_count {
-                    commands.spawn(ObjectSpec {
-                        object,
-                        team,
-                        position: cursor_position
-                            + Vec2 {
-                                x: (i * 10) as f32,
-                                y: (j * 10) as f32,
-                            },
-                        ..default()
-                    })
-                }
+        let Some(Ok(command)) = log.take() else {
+            return;
+        };
+        let positions = match command.positions(&grid_spec) {
+            Ok(positions) => positions,
+            Err(message) => {
+                reply_failed!(log, "{}", message);
+                return;
+            }
+        };
+        reply_ok!(log, "spawning {} {:?}", command.count, command.object);
+        for position in positions {
+            commands.spawn(ObjectSpec {
+                object: command.object,
+                team: command.team,
+                position,
+                ..default()
+            });
+        }
+    }
+
+    /// Positions to spawn at, or an error if the origin is outside the grid.
+    fn positions(&self, grid_spec: &GridSpec) -> Result<Vec<Vec2>, String> {
+        let origin = Vec2::new(self.x, self.y);
+        if !grid_spec.world2d_bounds().contains(origin) {
+            return Err(format!("({}, {}) is outside the grid", self.x, self.y));
+        }
+        let side = (self.count as f32).sqrt().ceil().max(1.) as usize;
+        Ok((0..self.count)
+            .map(|i| origin + Vec2::new((i % side) as f32, (i / side) as f32) * Self::SPACING)
+            .collect())
+    }
+}
+
+/// Load a scene file from the assets folder.
+#[derive(Parser, ConsoleCommand)]
+#[command(name = "load")]
+struct LoadCommand {
+    path: String,
+}
+impl LoadCommand {
+    pub fn update(mut log: ConsoleCommand<LoadCommand>, mut events: EventWriter<LoadSceneEvent>) {
+        if let Some(Ok(LoadCommand { path })) = log.take() {
+            reply!(log, "loading {}", path);
+            events.send(LoadSceneEvent { path });
+        }
+    }
+}
+
+/// Print occupancy statistics for the entity grid.
+#[derive(Parser, ConsoleCommand)]
+#[command(name = "gridstats")]
+struct GridStatsCommand;
+impl GridStatsCommand {
+    pub fn update(
+        mut log: ConsoleCommand<GridStatsCommand>,
+        grid: EntityGrids,
+        teams: Query<&Team>,
+    ) {
+        if let Some(Ok(GridStatsCommand)) = log.take() {
+            let stats = GridStats::new(&*grid, &teams);
+            let spec = grid.spec();
+            reply!(
+                log,
+                "grid: {} rows x {} cols, width {}, {:?} backend",
+                spec.rows,
+                spec.cols,
+                spec.width,
+                spec.backend
+            );
+            reply!(
+                log,
+                "occupied cells: {}, entities: {}, max per cell: {}",
+                stats.occupied_cells,
+                stats.entities,
+                stats.max_per_cell
+            );
+            for team in Team::ALL {
+                reply!(log, "{:?}: {}", team, stats.teams[team as usize]);
+            }
+        }
+    }
+}
+
+/// Occupancy statistics for the entity grid.
+#[derive(Default, Debug, PartialEq)]
+struct GridStats {
+    occupied_cells: usize,
+    entities: usize,
+    max_per_cell: usize,
+    teams: [usize; Team::COUNT],
+}
+impl GridStats {
+    fn new(grid: &dyn EntityGrid, teams: &Query<&Team>) -> Self {
+        let mut stats = Self::default();
+        grid.for_each_occupied_cell(&mut |_rowcol, cell| {
+            stats.occupied_cells += 1;
+            stats.entities += cell.len();
+            stats.max_per_cell = stats.max_per_cell.max(cell.len());
+            for &team in teams.iter_many(cell) {
+                stats.teams[team as usize] += 1;
+            }
+        });
+        stats
+    }
+}
+
+/// Set a config value, e.g. `set worker.neighbor_radius 200`.
+#[derive(Parser, ConsoleCommand)]
+#[command(name = "set")]
+struct SetCommand {
+    path: String,
+    #[arg(allow_negative_numbers = true)]
+    value: String,
+}
+impl SetCommand {
+    pub fn update(mut log: ConsoleCommand<SetCommand>, mut configs: ResMut<Configs>) {
+        if let Some(Ok(SetCommand { path, value })) = log.take() {
+            match configs.set_path(&path, &value) {
+                Ok(()) => reply_ok!(log, "{} = {}", path, value),
+                Err(message) => reply_failed!(log, "{}", message),
             }
         }
     }
 }
+
+/// Print the biomass available to each team.
+#[derive(Parser, ConsoleCommand)]
+#[command(name = "resources")]
+struct ResourcesCommand;
+impl ResourcesCommand {
+    pub fn update(mut log: ConsoleCommand<ResourcesCommand>, resources: Res<TeamResources>) {
+        if let Some(Ok(ResourcesCommand)) = log.take() {
+            for team in Team::ALL {
+                reply!(log, "{:?}: {} biomass", team, resources.get(team));
+            }
+        }
+    }
+}
+
+/// Print the most recent simulation metrics as CSV.
+#[derive(Parser, ConsoleCommand)]
+#[command(name = "metrics")]
+struct MetricsCommand {
+    #[arg(default_value_t = 10)]
+    count: usize,
+}
+impl MetricsCommand {
+    pub fn update(mut log: ConsoleCommand<MetricsCommand>, metrics: Res<Metrics>) {
+        if let Some(Ok(MetricsCommand { count })) = log.take() {
+            for line in metrics.recent_csv(count).lines() {
+                reply!(log, "{}", line);
+            }
+        }
+    }
+}
+
+/// Toggle between the OS cursor and the virtual cursor.
+#[derive(Parser, ConsoleCommand)]
+#[command(name = "cursor")]
+struct CursorCommand;
+impl CursorCommand {
+    pub fn update(mut log: ConsoleCommand<CursorCommand>, mut config: ResMut<CursorConfig>) {
+        if let Some(Ok(CursorCommand)) = log.take() {
+            config.os_cursor = !config.os_cursor;
+            let cursor = if config.os_cursor { "OS" } else { "virtual" };
+            reply_ok!(log, "Using the {} cursor", cursor);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{ecs::system::RunSystemOnce, prelude::*};
+    use clap::Parser;
+
+    use crate::prelude::*;
+
+    use super::{GridStats, SpawnCommand};
+
+    #[test]
+    fn test_grid_stats() {
+        let mut world = World::new();
+        let mut grid = Grid2::<EntitySet>::default();
+        grid.resize_with(GridSpec {
+            rows: 10,
+            cols: 10,
+            width: 10.,
+            ..default()
+        });
+        for (team, position) in [
+            (Team::Blue, Vec2::new(1., 1.)),
+            (Team::Blue, Vec2::new(2., 2.)),
+            (Team::Red, Vec2::new(3., 3.)),
+            (Team::Red, Vec2::new(-30., -30.)),
+        ] {
+            let entity = world.spawn(team).id();
+            grid.update_entity(entity, None, position);
+        }
+        world.insert_resource(grid);
+
+        let stats = world.run_system_once(|grid: EntityGrids, teams: Query<&Team>| {
+            GridStats::new(&*grid, &teams)
+        });
+        let mut teams = [0; Team::COUNT];
+        teams[Team::Blue as usize] = 2;
+        teams[Team::Red as usize] = 2;
+        assert_eq!(
+            stats,
+            GridStats {
+                occupied_cells: 2,
+                entities: 4,
+                max_per_cell: 3,
+                teams,
+            }
+        );
+    }
+
+    #[test]
+    fn test_spawn_command() {
+        let grid_spec = GridSpec {
+            rows: 10,
+            cols: 10,
+            width: 10.,
+            ..default()
+        };
+        let command =
+            SpawnCommand::try_parse_from(["spawn", "food", "blue", "-20", "5", "3"]).unwrap();
+        assert_eq!((command.object, command.team), (Object::Food, Team::Blue));
+        assert_eq!(
+            command.positions(&grid_spec).unwrap(),
+            vec![
+                Vec2::new(-20., 5.),
+                Vec2::new(-10., 5.),
+                Vec2::new(-20., 15.)
+            ]
+        );
+
+        let command = SpawnCommand::try_parse_from(["spawn", "head", "red", "0", "0"]).unwrap();
+        assert_eq!(command.positions(&grid_spec).unwrap().len(), 1);
+
+        let command = SpawnCommand::try_parse_from(["spawn", "head", "red", "100", "0"]).unwrap();
+        assert!(command.positions(&grid_spec).is_err());
+    }
+}
@@ -7,8 +7,123 @@ use bevy::{prelude::*, sprite::MaterialMesh2dBundle, window::PrimaryWindow};
 pub struct CursorPlugin;
 impl Plugin for CursorPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<CursorAssets>()
-            .add_systems(PreUpdate, Cursor::update.in_set(SystemStage::Compute));
+        app.register_type::<CursorConfig>()
+            .register_type::<CursorShape>()
+            .init_resource::<CursorConfig>()
+            .init_resource::<CursorAssets>()
+            .init_resource::<CursorSnap>()
+            .add_systems(
+                PreUpdate,
+                (CursorSnap::update, Cursor::update)
+                    .chain()
+                    .in_set(SystemStage::Compute),
+            )
+            .add_systems(Update, CursorConfig::apply);
+    }
+}
+
+/// Shape of the virtual cursor.
+#[derive(Reflect, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorShape {
+    #[default]
+    Triangle,
+    Square,
+    Circle,
+}
+impl CursorShape {
+    pub fn mesh(self) -> Mesh {
+        let circumcircle = Circle {
+            radius: 2f32.sqrt() / 2.,
+        };
+        match self {
+            Self::Triangle => RegularPolygon {
+                circumcircle,
+                sides: 3,
+            }
+            .into(),
+            Self::Square => Rectangle::new(1., 1.).into(),
+            Self::Circle => Circle::new(0.5).into(),
+        }
+    }
+}
+
+/// Appearance of the cursor.
+#[derive(Resource, Reflect, Clone, Debug, PartialEq)]
+#[reflect(Resource)]
+pub struct CursorConfig {
+    pub color: Color,
+    pub size: Vec2,
+    pub shape: CursorShape,
+    // Show the OS cursor instead of the virtual one.
+    pub os_cursor: bool,
+}
+impl Default for CursorConfig {
+    fn default() -> Self {
+        Self {
+            color: Color::ALICE_BLUE.with_a(0.5),
+            size: Vec2::new(10., 20.),
+            shape: CursorShape::Triangle,
+            os_cursor: false,
+        }
+    }
+}
+impl CursorConfig {
+    /// Apply the shape and color to the cursor assets, and swap between the OS and virtual cursors.
+    pub fn apply(
+        config: Res<Self>,
+        assets: Res<CursorAssets>,
+        mut meshes: ResMut<Assets<Mesh>>,
+        mut materials: ResMut<Assets<ColorMaterial>>,
+        mut window: Query<&mut Window, With<PrimaryWindow>>,
+        mut cursor: Query<&mut Visibility, With<Cursor>>,
+    ) {
+        if !config.is_changed() {
+            return;
+        }
+        meshes.insert(&assets.mesh, config.shape.mesh());
+        if let Some(material) = materials.get_mut(&assets.material) {
+            material.color = config.color;
+        }
+        for mut window in &mut window {
+            window.cursor.visible = config.os_cursor;
+        }
+        // The hidden virtual cursor still tracks the mouse for raycasts.
+        for mut visibility in &mut cursor {
+            *visibility = if config.os_cursor {
+                Visibility::Hidden
+            } else {
+                Visibility::Inherited
+            };
+        }
+    }
+}
+
+/// Whether the cursor snaps to cell centers.
+/// Snapping is active while painting obstacles, so edits land on the intended cells.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorSnap {
+    #[default]
+    Free,
+    Cells,
+}
+impl CursorSnap {
+    /// Snap while the obstacle paint modifier (Alt) is held.
+    pub fn update(mut snap: ResMut<Self>, keyboard: Option<Res<ButtonInput<KeyCode>>>) {
+        let painting = keyboard
+            .is_some_and(|keyboard| keyboard.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]));
+        let next = if painting { Self::Cells } else { Self::Free };
+        if *snap != next {
+            *snap = next;
+        }
+    }
+
+    /// Quantize a world position to the center of its cell when snapping.
+    /// Positions outside the grid are left as is.
+    pub fn snap(self, position: Vec2, spec: &GridSpec) -> Vec2 {
+        match (self, spec.try_to_rowcol(position)) {
+            (Self::Cells, Some(rowcol)) => spec.to_world_position(rowcol),
+            _ => position,
+        }
     }
 }
 
@@ -16,8 +131,12 @@ impl Plugin for CursorPlugin {
 pub struct Cursor;
 impl Cursor {
     pub fn update(
-        mut cursor: Query<&mut Transform, With<Self>>,
+        mut cursor: Query<(&mut Transform, Option<&Parent>), With<Self>>,
+        parents: Query<&GlobalTransform>,
         mut window: Query<&mut Window, With<PrimaryWindow>>,
+        snap: Res<CursorSnap>,
+        spec: Res<GridSpec>,
+        config: Res<CursorConfig>,
     ) {
         let window = window.single_mut();
         let window_size = Vec2 {
@@ -25,11 +144,20 @@ impl Cursor {
             y: window.physical_height() as f32,
         } / window.scale_factor();
 
-        let mut cursor_transform = cursor.single_mut();
+        let (mut cursor_transform, parent) = cursor.single_mut();
+        let scale = config.size.extend(1.);
+        if cursor_transform.scale != scale {
+            cursor_transform.scale = scale;
+        }
 
         if let Some(cursor_pixel_position) = window.cursor_position() {
             let cursor_position =
                 (cursor_pixel_position - window_size / 2.) * Vec2 { x: 1., y: -1. };
+            // The cursor is positioned relative to the camera, but snaps in world space.
+            let origin = parent
+                .and_then(|parent| parents.get(parent.get()).ok())
+                .map_or(Vec2::ZERO, |transform| transform.translation().xy());
+            let cursor_position = snap.snap(origin + cursor_position, &spec) - origin;
             cursor_transform.translation = cursor_position.extend(cursor_transform.translation.z);
         }
     }
@@ -39,10 +167,10 @@ impl Cursor {
             MaterialMesh2dBundle::<ColorMaterial> {
                 mesh: assets.mesh.clone().into(),
                 transform: Transform::default()
-                    .with_scale(Vec2 { x: 10., y: 20. }.extend(1.))
+                    .with_scale(assets.size.extend(1.))
                     .with_rotation(Quat::from_axis_angle(Vec3::Z, PI / 4.))
                     .with_translation(translation),
-                material: assets.blue_material.clone(),
+                material: assets.material.clone(),
                 ..default()
             },
             self,
@@ -55,27 +183,95 @@ impl Cursor {
     }
 }
 
-/// Handles to common grid assets.
+/// Handles to cursor assets, updated in place when `CursorConfig` changes.
 #[derive(Resource)]
 pub struct CursorAssets {
     pub mesh: Handle<Mesh>,
-    pub blue_material: Handle<ColorMaterial>,
+    pub material: Handle<ColorMaterial>,
+    /// Initial size of the cursor.
+    pub size: Vec2,
 }
 impl FromWorld for CursorAssets {
     fn from_world(world: &mut World) -> Self {
+        let config = world
+            .get_resource::<CursorConfig>()
+            .cloned()
+            .unwrap_or_default();
         let mesh = {
             let mut meshes = world.get_resource_mut::<Assets<Mesh>>().unwrap();
-            meshes.add(Mesh::from(RegularPolygon {
-                circumcircle: Circle {
-                    radius: 2f32.sqrt() / 2.,
-                },
-                sides: 3,
-            }))
+            meshes.add(config.shape.mesh())
         };
         let mut materials = world.get_resource_mut::<Assets<ColorMaterial>>().unwrap();
         Self {
             mesh,
-            blue_material: materials.add(ColorMaterial::from(Color::ALICE_BLUE.with_a(0.5))),
+            material: materials.add(ColorMaterial::from(config.color)),
+            size: config.size,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::{ecs::system::RunSystemOnce, prelude::*};
+
+    use crate::prelude::*;
+
+    use bevy::window::PrimaryWindow;
+
+    use super::{Cursor, CursorAssets, CursorConfig, CursorShape, CursorSnap};
+
+    #[test]
+    fn test_cursor_snap() {
+        let spec = GridSpec {
+            rows: 4,
+            cols: 4,
+            width: 10.0,
+            ..default()
+        };
+        let position = Vec2::new(3., -12.);
+        assert_eq!(CursorSnap::Free.snap(position, &spec), position);
+        assert_eq!(CursorSnap::Cells.snap(position, &spec), Vec2::new(5., -15.));
+        assert_eq!(
+            CursorSnap::Cells.snap(position, &spec),
+            spec.to_world_position(spec.to_rowcol(position))
+        );
+
+        // Positions off the grid aren't pulled onto it.
+        let outside = Vec2::new(100., 0.);
+        assert_eq!(CursorSnap::Cells.snap(outside, &spec), outside);
+    }
+
+    #[test]
+    fn test_cursor_config() {
+        let mut world = World::new();
+        world.init_resource::<Assets<Mesh>>();
+        world.init_resource::<Assets<ColorMaterial>>();
+        world.init_resource::<CursorConfig>();
+        world.init_resource::<CursorAssets>();
+        let window = world.spawn((Window::default(), PrimaryWindow)).id();
+        let assets = world.resource::<CursorAssets>();
+        let cursor = Cursor.bundle(assets, Vec3::ZERO);
+        let cursor = world.spawn(cursor).id();
+
+        // By default only the virtual cursor is shown.
+        world.run_system_once(CursorConfig::apply);
+        assert!(!world.get::<Window>(window).unwrap().cursor.visible);
+        assert_eq!(
+            world.get::<Visibility>(cursor),
+            Some(&Visibility::Inherited)
+        );
+
+        let mut config = world.resource_mut::<CursorConfig>();
+        config.os_cursor = true;
+        config.color = Color::RED;
+        config.shape = CursorShape::Square;
+        world.run_system_once(CursorConfig::apply);
+        assert!(world.get::<Window>(window).unwrap().cursor.visible);
+        assert_eq!(world.get::<Visibility>(cursor), Some(&Visibility::Hidden));
+        let assets = world.resource::<CursorAssets>();
+        let materials = world.resource::<Assets<ColorMaterial>>();
+        assert_eq!(materials.get(&assets.material).unwrap().color, Color::RED);
+        let meshes = world.resource::<Assets<Mesh>>();
+        assert_eq!(meshes.get(&assets.mesh).unwrap().count_vertices(), 4);
+    }
+}
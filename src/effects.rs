@@ -1,42 +1,30 @@
-use std::ops::Index;
-
 use crate::prelude::*;
-use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy::{ecs::system::SystemParam, prelude::*, utils::HashMap};
 use bevy_hanabi::prelude::*;
 
 /// Plugin for effects.
+/// Add `HanabiPlugin` first to render them.
 pub struct EffectsPlugin;
 impl Plugin for EffectsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(HanabiPlugin)
-            .init_resource::<EffectAssets>()
+        app.init_resource::<EffectAssets>()
             .add_systems(FixedUpdate, ScheduleDespawn::despawn);
     }
 }
 
-fn color_gradient_from_team(team: Team) -> Gradient<Vec4> {
+fn color_gradient(color: Color) -> Gradient<Vec4> {
+    let color = color.rgba_to_vec4();
     let mut color_gradient = Gradient::new();
-    match team {
-        Team::Blue | Team::None => {
-            let color = Color::TEAL.rgba_to_vec4();
-            color_gradient.add_key(0.0, color * 1.3);
-            color_gradient.add_key(0.1, color);
-            color_gradient.add_key(0.9, color * 0.5);
-            color_gradient.add_key(1.0, Vec4::new(0.0, 0.0, 2.0, 0.0));
-        }
-        Team::Red => {
-            let color = Color::TOMATO.rgba_to_vec4();
-            color_gradient.add_key(0.0, color * 1.3);
-            color_gradient.add_key(0.1, color);
-            color_gradient.add_key(0.7, color * 0.5);
-            color_gradient.add_key(1.0, Vec4::new(2.0, 0.0, 0.0, 0.0));
-        }
-    };
+    color_gradient.add_key(0.0, color * 1.3);
+    color_gradient.add_key(0.1, color);
+    color_gradient.add_key(0.8, color * 0.5);
+    color_gradient.add_key(1.0, (color.truncate() * 2.0).extend(0.0));
     color_gradient
 }
 
-pub fn firework_effect(team: Team, n: f32) -> EffectAsset {
-    let color_gradient = color_gradient_from_team(team);
+pub fn firework_effect(color: Color, size: EffectSize) -> EffectAsset {
+    let color_gradient = color_gradient(color);
+    let n = size.particles();
 
     let mut size_gradient1 = Gradient::new();
     size_gradient1.add_key(0.0, Vec2::splat(10.0));
@@ -60,14 +48,16 @@ pub fn firework_effect(team: Team, n: f32) -> EffectAsset {
 
     let init_pos = SetPositionSphereModifier {
         center: writer.lit(Vec3::ZERO).expr(),
-        radius: writer.lit(2.).expr(),
+        radius: writer.lit(2. * size.spread()).expr(),
         dimension: ShapeDimension::Volume,
     };
 
     // Give a bit of variation by randomizing the initial speed
     let init_vel = SetVelocitySphereModifier {
         center: writer.lit(Vec3::ZERO).expr(),
-        speed: (writer.rand(ScalarType::Float) * writer.lit(20.) + writer.lit(60.)).expr(),
+        speed: (writer.rand(ScalarType::Float) * writer.lit(20. * size.spread())
+            + writer.lit(60. * size.spread()))
+        .expr(),
     };
 
     EffectAsset::new(n as u32, Spawner::once(n.into(), true), writer.finish())
@@ -86,38 +76,84 @@ pub fn firework_effect(team: Team, n: f32) -> EffectAsset {
         })
 }
 
+/// Firework effects by size and color, created on first use.
 #[derive(Resource)]
 pub struct EffectAssets {
-    fireworks: [Handle<EffectAsset>; Team::COUNT],
-    small_fireworks: [Handle<EffectAsset>; Team::COUNT],
+    fireworks: HashMap<(EffectSize, [u8; 4]), Handle<EffectAsset>>,
+}
+impl EffectAssets {
+    /// Returns the firework effect for the given size and color.
+    pub fn get_fireworks(
+        &mut self,
+        size: EffectSize,
+        color: Color,
+        assets: &mut Assets<EffectAsset>,
+    ) -> Handle<EffectAsset> {
+        self.fireworks
+            .entry((size, color.as_rgba_u8()))
+            .or_insert_with(|| assets.add(firework_effect(color, size)))
+            .clone()
+    }
 }
 impl FromWorld for EffectAssets {
     fn from_world(world: &mut World) -> Self {
         let mut assets = world.get_resource_mut::<Assets<EffectAsset>>().unwrap();
-        Self {
-            fireworks: Team::ALL.map(|team| assets.add(firework_effect(team, 20.))),
-            small_fireworks: Team::ALL.map(|team| assets.add(firework_effect(team, 5.))),
+        // Preload team colored fireworks.
+        let mut effects = Self {
+            fireworks: HashMap::default(),
+        };
+        for color in Team::COLORS {
+            for size in EffectSize::ALL {
+                effects.get_fireworks(size, color, &mut assets);
+            }
         }
-    }
-}
-impl Index<Team> for [Handle<EffectAsset>; Team::COUNT] {
-    type Output = Handle<EffectAsset>;
-    fn index(&self, index: Team) -> &Self::Output {
-        &self[index as usize]
+        effects
     }
 }
 
 /// Represents size of an effect.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum EffectSize {
     Small,
     Medium,
+    Large,
+}
+impl EffectSize {
+    pub const ALL: [Self; 3] = [Self::Small, Self::Medium, Self::Large];
+
+    /// Number of particles in a firework of this size.
+    pub fn particles(self) -> f32 {
+        match self {
+            Self::Small => 5.,
+            Self::Medium => 20.,
+            Self::Large => 60.,
+        }
+    }
+
+    /// Scale of the firework's starting radius and speed.
+    pub fn spread(self) -> f32 {
+        match self {
+            Self::Small | Self::Medium => 1.,
+            Self::Large => 1.5,
+        }
+    }
 }
 /// Describes a firework to create.
 pub struct FireworkSpec {
-    pub team: Team,
+    pub color: Color,
     pub transform: Transform,
     pub size: EffectSize,
 }
+impl FireworkSpec {
+    /// Create a firework colored by the team.
+    pub fn new(team: Team, transform: Transform, size: EffectSize) -> Self {
+        Self {
+            color: Team::COLORS[team as usize],
+            transform,
+            size,
+        }
+    }
+}
 
 /// Schedule despawn for a particle.
 #[derive(Component, DerefMut, Deref)]
@@ -141,6 +177,7 @@ impl ScheduleDespawn {
 #[derive(SystemParam)]
 pub struct EffectCommands<'w, 's> {
     assets: ResMut<'w, EffectAssets>,
+    effects: ResMut<'w, Assets<EffectAsset>>,
     commands: Commands<'w, 's>,
 }
 impl EffectCommands<'_, '_> {
@@ -149,13 +186,58 @@ impl EffectCommands<'_, '_> {
             Name::new("firework"),
             ScheduleDespawn(Timer::from_seconds(0.5, TimerMode::Once)),
             ParticleEffectBundle {
-                effect: ParticleEffect::new(match spec.size {
-                    EffectSize::Small => self.assets.small_fireworks[spec.team].clone(),
-                    EffectSize::Medium => self.assets.fireworks[spec.team].clone(),
-                }),
+                effect: ParticleEffect::new(self.assets.get_fireworks(
+                    spec.size,
+                    spec.color,
+                    &mut self.effects,
+                )),
                 transform: spec.transform,
                 ..Default::default()
             },
         ));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use bevy_hanabi::prelude::EffectAsset;
+
+    use crate::prelude::*;
+
+    use super::{EffectAssets, EffectSize, FireworkSpec};
+
+    #[test]
+    fn test_firework_colors() {
+        let mut world = World::new();
+        world.init_resource::<Assets<EffectAsset>>();
+        world.init_resource::<EffectAssets>();
+        let preloaded = world.resource::<Assets<EffectAsset>>().len();
+        assert_eq!(preloaded, Team::COUNT * EffectSize::ALL.len());
+
+        // Neutral deaths don't borrow another team's color.
+        let neutral = FireworkSpec::new(Team::None, Transform::default(), EffectSize::Medium);
+        assert_eq!(neutral.color, Team::COLORS[Team::None as usize]);
+        assert_ne!(neutral.color, Team::COLORS[Team::Blue as usize]);
+
+        world.resource_scope(|world, mut assets: Mut<Assets<EffectAsset>>| {
+            let mut effects = world.resource_mut::<EffectAssets>();
+            let neutral_handle = effects.get_fireworks(neutral.size, neutral.color, &mut assets);
+            let blue_handle = effects.get_fireworks(
+                EffectSize::Medium,
+                Team::COLORS[Team::Blue as usize],
+                &mut assets,
+            );
+            assert_ne!(neutral_handle, blue_handle);
+            assert_eq!(assets.len(), preloaded);
+
+            // Custom colors are created once and then reused.
+            let gold = effects.get_fireworks(EffectSize::Large, Color::GOLD, &mut assets);
+            assert_eq!(
+                effects.get_fireworks(EffectSize::Large, Color::GOLD, &mut assets),
+                gold
+            );
+            assert_eq!(assets.len(), preloaded + 1);
+        });
+    }
+}
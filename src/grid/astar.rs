@@ -7,7 +7,22 @@ use bevy::utils::HashMap;
 
 use crate::prelude::*;
 
-use super::navigation::SparseFlowGrid2;
+use super::{
+    influence::EnemyInfluenceCost,
+    navigation::{NavigationConfig, SparseFlowGrid2},
+};
+
+impl Grid2<Obstacle> {
+    /// Returns true if a diagonal step would cut through a wall corner,
+    /// i.e. both orthogonally adjacent cells are obstacles.
+    pub fn blocks_diagonal(&self, rowcol: RowCol, neighbor_rowcol: RowCol) -> bool {
+        if neighbor_rowcol.0 == rowcol.0 || neighbor_rowcol.1 == rowcol.1 {
+            return false;
+        }
+        self[(neighbor_rowcol.0, rowcol.1)] != Obstacle::Empty
+            && self[(rowcol.0, neighbor_rowcol.1)] != Obstacle::Empty
+    }
+}
 
 /// State for running A* search to fill out flow cost grid.
 /// See https://doc.rust-lang.org/std/collections/binary_heap/index.html#examples
@@ -40,6 +55,7 @@ impl PartialOrd for AStarState {
 }
 
 /// Struct to allow running A* on demand while re-using old results.
+#[derive(Clone)]
 pub struct AStarRunner {
     pub destination: RowCol,
     pub costs: HashMap<RowCol, f32>,
@@ -63,13 +79,10 @@ impl AStarRunner {
 
     /// Compute heuristic factor on naive 8-distance heuristic.
     /// We want to use the heuristic more at higher distances from the destination.
-    pub fn heuristic_factor(&self, source: RowCol) -> f32 {
-        let min_grid_dist = 1.;
-        let max_grid_dist = 30.;
+    pub fn heuristic_factor(&self, source: RowCol, config: &NavigationConfig) -> f32 {
         let dist = self.destination.distance8(source);
-        let max_heuristic = 0.9;
-        let final_dist = dist.clamp(min_grid_dist, max_grid_dist);
-        max_heuristic * final_dist / max_grid_dist
+        let final_dist = dist.clamp(config.min_grid_dist, config.max_grid_dist);
+        config.max_heuristic * final_dist / config.max_grid_dist
     }
     /// Runs A star from the given source to the destination.
     pub fn a_star_from_source(
@@ -77,13 +90,15 @@ impl AStarRunner {
         source: RowCol,
         grid: &SparseFlowGrid2,
         obstacles: &Grid2<Obstacle>,
+        influence: &EnemyInfluenceCost,
+        config: &NavigationConfig,
     ) {
         // We're at `start`, with a zero cost
         if grid.is_boundary(self.destination) {
             return;
         }
 
-        let heuristic_factor = self.heuristic_factor(source);
+        let heuristic_factor = self.heuristic_factor(source, config);
 
         // Examine the frontier with lower cost nodes first (min-heap)
         while let Some(AStarState {
@@ -107,7 +122,7 @@ impl AStarRunner {
 
             // For each node we can reach, see if we can find a way with
             // a lower cost going through this node
-            for (neighbor_rowcol, neighbor_cost) in grid.neighbors8(rowcol) {
+            for (neighbor_rowcol, neighbor_cost) in grid.neighbors(rowcol, config.diagonal) {
                 // Skip out of bounds positions.
                 if grid.is_boundary(neighbor_rowcol) {
                     continue;
@@ -115,9 +130,13 @@ impl AStarRunner {
                 if obstacles[neighbor_rowcol] != Obstacle::Empty {
                     continue;
                 }
+                // Don't clip through wall corners.
+                if obstacles.blocks_diagonal(rowcol, neighbor_rowcol) {
+                    continue;
+                }
 
                 self.heap.push(AStarState {
-                    cost: cost + neighbor_cost,
+                    cost: cost + neighbor_cost + influence.cost(neighbor_rowcol),
                     rowcol: neighbor_rowcol,
                     heuristic: heuristic_factor * neighbor_rowcol.distance8(source),
                 });
@@ -132,6 +151,8 @@ impl AStarRunner {
         destination: RowCol,
         grid: &SparseFlowGrid2,
         obstacles: &Grid2<Obstacle>,
+        influence: &EnemyInfluenceCost,
+        config: &NavigationConfig,
     ) -> HashMap<RowCol, f32> {
         let sources: BTreeSet<RowCol> = sources
             .iter()
@@ -143,8 +164,152 @@ impl AStarRunner {
             if runner.costs.contains_key(&source) {
                 continue;
             }
-            runner.a_star_from_source(source, grid, obstacles);
+            runner.a_star_from_source(source, grid, obstacles, influence, config);
         }
         runner.costs
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        grid::{
+            navigation::{NavigationConfig, SparseFlowGrid2},
+            EnemyInfluenceCost, TeamInfluence,
+        },
+        prelude::*,
+    };
+
+    use super::AStarRunner;
+
+    /// Follow decreasing costs from source to destination.
+    fn path(runner: &AStarRunner, grid: &SparseFlowGrid2, source: RowCol) -> Vec<RowCol> {
+        let mut path = vec![source];
+        let mut rowcol = source;
+        while rowcol != runner.destination {
+            rowcol = grid
+                .neighbors8(rowcol)
+                .into_iter()
+                .filter_map(|(neighbor, _)| Some((neighbor, *runner.costs.get(&neighbor)?)))
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                .unwrap()
+                .0;
+            path.push(rowcol);
+        }
+        path
+    }
+
+    #[test]
+    fn test_enemy_influence() {
+        let spec = GridSpec {
+            rows: 20,
+            cols: 20,
+            width: 10.0,
+            visualize: false,
+            visualize_navigation: false,
+            ..Default::default()
+        };
+        let grid = SparseFlowGrid2::new(spec.clone());
+        let mut obstacles = Grid2::<Obstacle>::default();
+        obstacles.resize_with(spec.clone());
+        let mut influence = Grid2::<TeamInfluence>::default();
+        influence.resize_with(spec.clone());
+
+        // Dense red cluster between source and destination.
+        let cluster = (8..=12).flat_map(|row| (8..=12).map(move |col| (row, col)));
+        for rowcol in cluster.clone() {
+            *influence[rowcol].get_mut(Team::Red) += 10;
+        }
+        let (source, destination) = ((10, 2), (10, 17));
+
+        for (weight, expect_avoid) in [(0., false), (100., true)] {
+            let cost = EnemyInfluenceCost {
+                influence: Some(&influence),
+                team: Team::Blue,
+                weight,
+            };
+            let mut runner = AStarRunner::new(destination);
+            runner.a_star_from_source(
+                source,
+                &grid,
+                &obstacles,
+                &cost,
+                &NavigationConfig::default(),
+            );
+            let path = path(&runner, &grid, source);
+            let crosses_cluster = path
+                .iter()
+                .any(|rowcol| cluster.clone().any(|c| c == *rowcol));
+            assert_eq!(crosses_cluster, !expect_avoid, "{:?}", path);
+        }
+    }
+
+    #[test]
+    fn test_heuristic_config() {
+        let spec = GridSpec {
+            rows: 40,
+            cols: 40,
+            width: 10.0,
+            visualize: false,
+            visualize_navigation: false,
+            ..Default::default()
+        };
+        let grid = SparseFlowGrid2::new(spec.clone());
+        let mut obstacles = Grid2::<Obstacle>::default();
+        obstacles.resize_with(spec.clone());
+        let cost = EnemyInfluenceCost::default();
+        let (source, destination) = ((5, 5), (30, 35));
+
+        // A greedier heuristic should expand fewer cells.
+        let explored = |max_heuristic: f32| {
+            let config = NavigationConfig {
+                max_heuristic,
+                ..NavigationConfig::default()
+            };
+            let mut runner = AStarRunner::new(destination);
+            runner.a_star_from_source(source, &grid, &obstacles, &cost, &config);
+            assert!(runner.costs.contains_key(&source));
+            runner.costs.len()
+        };
+        assert!(explored(0.9) < explored(0.));
+    }
+
+    #[test]
+    fn test_diagonal_blocking() {
+        let spec = GridSpec {
+            rows: 16,
+            cols: 16,
+            width: 10.0,
+            visualize: false,
+            visualize_navigation: false,
+            ..Default::default()
+        };
+        let grid = SparseFlowGrid2::new(spec.clone());
+        let mut obstacles = Grid2::<Obstacle>::default();
+        obstacles.resize_with(spec.clone());
+        // L-shaped wall whose arms only touch diagonally at (6, 6) and (7, 7).
+        for col in 2..=6 {
+            obstacles[(6, col)] = Obstacle::Full;
+        }
+        for row in 7..=12 {
+            obstacles[(row, 7)] = Obstacle::Full;
+        }
+        let (source, destination) = ((7, 6), (6, 7));
+        assert!(obstacles.blocks_diagonal(source, destination));
+        assert!(!obstacles.blocks_diagonal((5, 5), (4, 4)));
+
+        let path = grid
+            .compute_path(
+                source,
+                destination,
+                &obstacles,
+                &EnemyInfluenceCost::default(),
+                &NavigationConfig::default(),
+            )
+            .unwrap();
+        assert!(path.len() > 2, "{:?}", path);
+        for pair in path.windows(2) {
+            assert!(!obstacles.blocks_diagonal(pair[0], pair[1]), "{:?}", path);
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use bevy::prelude::*;
+
+use crate::prelude::*;
+
+/// Plugin for drawing cell borders and RowCol labels over the grid for debugging.
+pub struct GridCoordsPlugin;
+impl Plugin for GridCoordsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                GridCoordLabel::draw_borders.run_if(should_show_grid_coords),
+                GridCoordLabel::update,
+            ),
+        );
+    }
+}
+
+/// Returns true if grid coordinates should be shown.
+fn should_show_grid_coords(configs: Res<Configs>) -> bool {
+    configs.show_grid_coords
+}
+
+/// Text showing the RowCol of a cell.
+#[derive(Component, Debug, PartialEq)]
+pub struct GridCoordLabel(pub RowCol);
+impl GridCoordLabel {
+    /// Most labels shown along each axis, to avoid clutter on large grids.
+    pub const MAX_LABELS_PER_AXIS: u16 = 16;
+
+    /// Cells to label, sampled every Nth row and column.
+    pub fn sampled_rowcols(spec: &GridSpec) -> Vec<RowCol> {
+        let stride = (spec.rows.max(spec.cols) / Self::MAX_LABELS_PER_AXIS).max(1) as usize;
+        (0..spec.rows)
+            .step_by(stride)
+            .flat_map(|row| (0..spec.cols).step_by(stride).map(move |col| (row, col)))
+            .collect()
+    }
+
+    /// Draw a line along each cell boundary.
+    pub fn draw_borders(mut gizmos: Gizmos, spec: Res<GridSpec>) {
+        let bounds = spec.world2d_bounds();
+        let color = Color::WHITE.with_a(0.2);
+        for row in 0..=spec.rows {
+            let y = bounds.min.y + row as f32 * spec.width;
+            gizmos.line_2d(
+                Vec2::new(bounds.min.x, y),
+                Vec2::new(bounds.max.x, y),
+                color,
+            );
+        }
+        for col in 0..=spec.cols {
+            let x = bounds.min.x + col as f32 * spec.width;
+            gizmos.line_2d(
+                Vec2::new(x, bounds.min.y),
+                Vec2::new(x, bounds.max.y),
+                color,
+            );
+        }
+    }
+
+    /// Respawn labels when toggled or when the grid is resized.
+    pub fn update(
+        labels: Query<Entity, With<Self>>,
+        spec: Res<GridSpec>,
+        configs: Res<Configs>,
+        mut commands: Commands,
+    ) {
+        if !configs.is_changed() && !spec.is_changed() {
+            return;
+        }
+        let shown = !labels.is_empty();
+        if shown && configs.show_grid_coords && !spec.is_changed() {
+            return;
+        }
+        for entity in &labels {
+            commands.entity(entity).despawn();
+        }
+        if !configs.show_grid_coords {
+            return;
+        }
+        for rowcol in Self::sampled_rowcols(&spec) {
+            commands.spawn((
+                Self(rowcol),
+                Text2dBundle {
+                    text: Text::from_section(
+                        format!("{:?}", rowcol),
+                        TextStyle {
+                            font_size: 18.0,
+                            ..default()
+                        },
+                    ),
+                    transform: Transform::from_translation(
+                        spec.to_world_position(rowcol).extend(zindex::GRID_COORDS),
+                    ),
+                    ..default()
+                },
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use crate::prelude::*;
+
+    use super::GridCoordLabel;
+
+    #[test]
+    fn test_grid_coord_labels() {
+        let spec = GridSpec {
+            rows: 4,
+            cols: 3,
+            ..default()
+        };
+        assert_eq!(GridCoordLabel::sampled_rowcols(&spec).len(), 12);
+        let spec = GridSpec {
+            rows: 64,
+            cols: 32,
+            ..default()
+        };
+        let rowcols = GridCoordLabel::sampled_rowcols(&spec);
+        assert_eq!(rowcols.len(), 16 * 8);
+        assert_eq!(rowcols[..2], [(0, 0), (0, 4)]);
+
+        let mut world = World::new();
+        world.insert_resource(spec);
+        world.insert_resource(Configs {
+            show_grid_coords: true,
+            ..default()
+        });
+        let mut schedule = Schedule::default();
+        schedule.add_systems(GridCoordLabel::update);
+        let labels = |world: &mut World| {
+            let mut query = world.query::<&GridCoordLabel>();
+            query.iter(world).count()
+        };
+        schedule.run(&mut world);
+        assert_eq!(labels(&mut world), 16 * 8);
+
+        // Labels follow grid resizes.
+        world.resource_mut::<GridSpec>().rows = 128;
+        schedule.run(&mut world);
+        assert_eq!(labels(&mut world), 16 * 4);
+
+        // Other config changes keep the labels.
+        world.resource_mut::<Configs>().sfx_volume = 0.5;
+        schedule.run(&mut world);
+        assert_eq!(labels(&mut world), 16 * 4);
+
+        world.resource_mut::<Configs>().show_grid_coords = false;
+        schedule.run(&mut world);
+        assert_eq!(labels(&mut world), 0);
+    }
+}
@@ -1,7 +1,11 @@
-use bevy::{prelude::*, utils::HashSet};
+use std::ops::{Deref, DerefMut};
+
+use bevy::{ecs::system::SystemParam, prelude::*, utils::HashSet};
 
 use crate::prelude::*;
 
+use super::{spec::GridBackend, SparseGrid2};
+
 /// Stores a set of entities in each grid cell.
 pub type EntitySet = HashSet<Entity>;
 
@@ -15,7 +19,7 @@ pub struct GridEntity {
 impl GridEntity {
     pub fn update(
         mut query: Query<(Entity, &mut Self, &Transform)>,
-        mut grid: ResMut<Grid2<EntitySet>>,
+        mut grid: EntityGridsMut,
         mut event_writer: EventWriter<EntityGridEvent>,
     ) {
         for (entity, mut grid_entity, transform) in &mut query {
@@ -49,6 +53,13 @@ impl Default for EntityGridEvent {
 }
 
 impl Grid2<EntitySet> {
+    /// Resize the grid to match the given spec, reserving `cell_capacity` in new cells.
+    pub fn resize_with_capacity(&mut self, spec: GridSpec) {
+        let num_cells = spec.rows as usize * spec.cols as usize;
+        self.cells.resize_with(num_cells, || spec.new_entity_set());
+        self.spec = spec;
+    }
+
     /// Update an entity's position in the grid.
     pub fn update_entity(
         &mut self,
@@ -56,6 +67,10 @@ impl Grid2<EntitySet> {
         cell: Option<RowCol>,
         position: Vec2,
     ) -> Option<EntityGridEvent> {
+        if self.rows == 0 || self.cols == 0 {
+            return None;
+        }
+        // Positions outside the grid clamp to the nearest edge cell.
         let rowcol = self.to_rowcol(position);
 
         // Remove this entity's old position if it was different.
@@ -88,13 +103,38 @@ impl Grid2<EntitySet> {
 
     pub fn get_entities_in_radius(&self, position: Vec2, radius: f32) -> HashSet<Entity> {
         let mut other_entities: HashSet<Entity> = HashSet::default();
-        let positions = self.get_in_radius(position, radius);
-        for rowcol in positions {
+        self.get_entities_in_radius_with(position, radius, &mut other_entities);
+        other_entities
+    }
+
+    /// Collects entities in radius into a reusable scratch buffer, clearing it first.
+    pub fn get_entities_in_radius_with(
+        &self,
+        position: Vec2,
+        radius: f32,
+        scratch: &mut HashSet<Entity>,
+    ) {
+        scratch.clear();
+        self.for_each_entity_in_radius(position, radius, |entity| {
+            scratch.insert(entity);
+        });
+    }
+
+    /// Calls `f` for each entity in cells overlapping the radius without allocating.
+    /// Each entity is in one cell, so entities are visited at most once.
+    pub fn for_each_entity_in_radius(
+        &self,
+        position: Vec2,
+        radius: f32,
+        mut f: impl FnMut(Entity),
+    ) {
+        self.for_each_in_radius(position, radius, |rowcol| {
             if self.in_bounds(rowcol) {
-                other_entities.extend(&self[rowcol]);
+                for &entity in &self[rowcol] {
+                    f(entity)
+                }
             }
-        }
-        other_entities
+        });
     }
     /// Remove an entity from the grid entirely.
     pub fn remove(&mut self, entity: Entity, grid_entity: &GridEntity) {
@@ -122,11 +162,369 @@ impl Grid2<EntitySet> {
     }
 }
 
+/// Sparse entity grid that only stores occupied cells.
+pub type SparseEntityGrid = SparseGrid2<EntitySet>;
+
+/// Spatial index of entities by cell, implemented by the dense and sparse backends.
+pub trait EntityGrid: Send + Sync {
+    /// Returns the spec of this grid.
+    fn spec(&self) -> &GridSpec;
+
+    /// Returns the entities in a cell, if the cell is in bounds and stored.
+    fn cell(&self, rowcol: RowCol) -> Option<&EntitySet>;
+
+    /// Update an entity's position in the grid.
+    fn update_entity(
+        &mut self,
+        entity: Entity,
+        cell: Option<RowCol>,
+        position: Vec2,
+    ) -> Option<EntityGridEvent>;
+
+    /// Remove an entity from the grid entirely.
+    fn remove(&mut self, entity: Entity, grid_entity: &GridEntity);
+
+    /// Calls `f` for each entity in cells overlapping the radius.
+    fn for_each_entity_in_radius(&self, position: Vec2, radius: f32, f: &mut dyn FnMut(Entity));
+
+    /// Calls `f` for each cell containing at least one entity.
+    fn for_each_occupied_cell(&self, f: &mut dyn FnMut(RowCol, &EntitySet));
+
+    /// Iterates over every tracked entity with its cell.
+    /// Each entity is in one cell, so it is yielded once.
+    fn iter_entities(&self) -> Box<dyn Iterator<Item = (Entity, RowCol)> + '_>;
+
+    /// Returns the number of tracked entities.
+    fn len(&self) -> usize {
+        let mut len = 0;
+        self.for_each_occupied_cell(&mut |_, cell| len += cell.len());
+        len
+    }
+
+    /// Returns true if no entities are tracked.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns true if there are no entities in the cell.
+    fn is_cell_empty(&self, rowcol: RowCol) -> bool {
+        self.cell(rowcol).is_none_or(|cell| cell.is_empty())
+    }
+
+    /// Returns all entities in cells overlapping the given radius around `position`.
+    fn get_entities_in_radius(&self, position: Vec2, radius: f32) -> HashSet<Entity> {
+        let mut entities = HashSet::default();
+        self.for_each_entity_in_radius(position, radius, &mut |entity| {
+            entities.insert(entity);
+        });
+        entities
+    }
+
+    /// Get all entities in a given bounding box.
+    fn get_entities_in_aabb(&self, aabb: &Aabb2) -> Vec<Entity> {
+        let mut result = HashSet::default();
+        for rowcol in self.spec().get_in_aabb(aabb) {
+            if let Some(set) = self.cell(rowcol) {
+                result.extend(set.iter());
+            }
+        }
+        result.into_iter().collect()
+    }
+}
+
+impl dyn EntityGrid + '_ {
+    /// Returns the nearest entity matching `predicate` with its squared distance.
+    /// Distances are measured to the center of each entity's cell, searching ring by ring
+    /// outward and stopping once no further ring can hold a closer match.
+    /// Ties are broken by the lowest entity.
+    pub fn get_nearest(
+        &self,
+        position: Vec2,
+        max_radius: f32,
+        predicate: impl Fn(Entity) -> bool,
+    ) -> Option<(Entity, f32)> {
+        let spec = self.spec();
+        let (row, col) = spec.to_rowcol(position);
+        let max_radius_squared = max_radius * max_radius;
+        let max_ring = spec.discretize(max_radius) + 1;
+        let mut nearest: Option<(Entity, f32)> = None;
+        for ring in 0..=max_ring.min(spec.rows.max(spec.cols)) {
+            // Every cell in this ring is at least this far from `position`.
+            let min_distance = (ring as f32 - 0.5).max(0.) * spec.width;
+            if nearest
+                .is_some_and(|(_, distance_squared)| distance_squared < min_distance * min_distance)
+            {
+                break;
+            }
+            let mut visit = |rowcol: RowCol| {
+                let Some(cell) = self.cell(rowcol) else {
+                    return;
+                };
+                let distance_squared = spec.to_world_position(rowcol).distance_squared(position);
+                if distance_squared > max_radius_squared {
+                    return;
+                }
+                for &entity in cell.iter().filter(|&&entity| predicate(entity)) {
+                    let closer = nearest.is_none_or(|(nearest_entity, nearest_distance)| {
+                        (distance_squared, entity) < (nearest_distance, nearest_entity)
+                    });
+                    if closer {
+                        nearest = Some((entity, distance_squared));
+                    }
+                }
+            };
+            let (min_row, max_row) = (row.saturating_sub(ring), row.saturating_add(ring));
+            let (min_col, max_col) = (col.saturating_sub(ring), col.saturating_add(ring));
+            for other_row in min_row..=max_row.min(spec.rows - 1) {
+                if other_row.abs_diff(row) == ring {
+                    for other_col in min_col..=max_col.min(spec.cols - 1) {
+                        visit((other_row, other_col));
+                    }
+                    continue;
+                }
+                if col >= ring {
+                    visit((other_row, min_col));
+                }
+                if ring > 0 && max_col < spec.cols {
+                    visit((other_row, max_col));
+                }
+            }
+        }
+        nearest
+    }
+}
+
+impl EntityGrid for Grid2<EntitySet> {
+    fn spec(&self) -> &GridSpec {
+        &self.spec
+    }
+    fn cell(&self, rowcol: RowCol) -> Option<&EntitySet> {
+        self.in_bounds(rowcol).then(|| &self[rowcol])
+    }
+    fn update_entity(
+        &mut self,
+        entity: Entity,
+        cell: Option<RowCol>,
+        position: Vec2,
+    ) -> Option<EntityGridEvent> {
+        Grid2::update_entity(self, entity, cell, position)
+    }
+    fn remove(&mut self, entity: Entity, grid_entity: &GridEntity) {
+        Grid2::remove(self, entity, grid_entity)
+    }
+    fn for_each_entity_in_radius(&self, position: Vec2, radius: f32, f: &mut dyn FnMut(Entity)) {
+        Grid2::for_each_entity_in_radius(self, position, radius, f)
+    }
+    fn for_each_occupied_cell(&self, f: &mut dyn FnMut(RowCol, &EntitySet)) {
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                let cell = &self[(row, col)];
+                if !cell.is_empty() {
+                    f((row, col), cell);
+                }
+            }
+        }
+    }
+    fn iter_entities(&self) -> Box<dyn Iterator<Item = (Entity, RowCol)> + '_> {
+        let cols = self.cols as usize;
+        Box::new(self.cells.iter().enumerate().flat_map(move |(i, cell)| {
+            let rowcol = ((i / cols) as u16, (i % cols) as u16);
+            cell.iter().map(move |&entity| (entity, rowcol))
+        }))
+    }
+    fn get_entities_in_aabb(&self, aabb: &Aabb2) -> Vec<Entity> {
+        Grid2::get_entities_in_aabb(self, aabb)
+    }
+}
+
+impl EntityGrid for SparseEntityGrid {
+    fn spec(&self) -> &GridSpec {
+        &self.spec
+    }
+    fn cell(&self, rowcol: RowCol) -> Option<&EntitySet> {
+        self.get(rowcol)
+    }
+    fn update_entity(
+        &mut self,
+        entity: Entity,
+        cell: Option<RowCol>,
+        position: Vec2,
+    ) -> Option<EntityGridEvent> {
+        if self.rows == 0 || self.cols == 0 {
+            return None;
+        }
+        // Positions outside the grid clamp to the nearest edge cell.
+        let rowcol = self.to_rowcol(position);
+        if cell == Some(rowcol) {
+            return None;
+        }
+
+        // Remove this entity's old position, dropping cells that become empty.
+        let mut prev_cell = None;
+        let mut prev_cell_empty = false;
+        if let Some(prev_rowcol) = cell {
+            if let Some(entities) = self.cells.get_mut(&prev_rowcol) {
+                entities.remove(&entity);
+                prev_cell = Some(prev_rowcol);
+                prev_cell_empty = entities.is_empty();
+                if prev_cell_empty {
+                    self.cells.remove(&prev_rowcol);
+                }
+            }
+        }
+
+        let spec = &self.spec;
+        self.cells
+            .entry(rowcol)
+            .or_insert_with(|| spec.new_entity_set())
+            .insert(entity);
+        Some(EntityGridEvent {
+            entity,
+            prev_cell,
+            prev_cell_empty,
+            cell: Some(rowcol),
+        })
+    }
+    fn remove(&mut self, entity: Entity, grid_entity: &GridEntity) {
+        let Some(rowcol) = grid_entity.cell else {
+            error!("No row col for {:?}", entity);
+            return;
+        };
+        if let Some(cell) = self.cells.get_mut(&rowcol) {
+            cell.remove(&entity);
+            if cell.is_empty() {
+                self.cells.remove(&rowcol);
+            }
+        }
+    }
+    fn for_each_entity_in_radius(&self, position: Vec2, radius: f32, f: &mut dyn FnMut(Entity)) {
+        self.spec.for_each_in_radius(position, radius, |rowcol| {
+            if let Some(cell) = self.cells.get(&rowcol) {
+                for &entity in cell {
+                    f(entity)
+                }
+            }
+        });
+    }
+    fn for_each_occupied_cell(&self, f: &mut dyn FnMut(RowCol, &EntitySet)) {
+        for (&rowcol, cell) in &self.cells {
+            f(rowcol, cell);
+        }
+    }
+    fn iter_entities(&self) -> Box<dyn Iterator<Item = (Entity, RowCol)> + '_> {
+        Box::new(
+            self.cells
+                .iter()
+                .flat_map(|(&rowcol, cell)| cell.iter().map(move |&entity| (entity, rowcol))),
+        )
+    }
+}
+
+impl GridBackend {
+    /// Resize the entity grid on spec changes, swapping storage when the backend changes.
+    pub fn update(
+        spec: Res<GridSpec>,
+        dense: Option<ResMut<Grid2<EntitySet>>>,
+        sparse: Option<ResMut<SparseEntityGrid>>,
+        mut grid_entities: Query<&mut GridEntity>,
+        mut commands: Commands,
+    ) {
+        if !spec.is_changed() {
+            return;
+        }
+        match (spec.backend, dense, sparse) {
+            (Self::Dense, Some(mut dense), _) => {
+                dense.resize_with_capacity(spec.clone());
+                return;
+            }
+            (Self::Sparse, _, Some(mut sparse)) => {
+                sparse.resize_with(spec.clone());
+                return;
+            }
+            (Self::Dense, None, _) => {
+                let mut dense = Grid2::<EntitySet>::default();
+                dense.resize_with_capacity(spec.clone());
+                commands.remove_resource::<SparseEntityGrid>();
+                commands.insert_resource(dense);
+            }
+            (Self::Sparse, _, None) => {
+                commands.remove_resource::<Grid2<EntitySet>>();
+                commands.insert_resource(SparseEntityGrid {
+                    spec: spec.clone(),
+                    ..default()
+                });
+            }
+        }
+        // The new storage is empty, so entities re-insert themselves on their next update.
+        for mut grid_entity in &mut grid_entities {
+            grid_entity.cell = None;
+        }
+    }
+}
+
+/// Read access to the entity grid, whichever backend is active.
+#[derive(SystemParam)]
+pub struct EntityGrids<'w> {
+    dense: Option<Res<'w, Grid2<EntitySet>>>,
+    sparse: Option<Res<'w, SparseEntityGrid>>,
+}
+impl Deref for EntityGrids<'_> {
+    type Target = dyn EntityGrid;
+    fn deref(&self) -> &Self::Target {
+        match (&self.dense, &self.sparse) {
+            (_, Some(sparse)) => sparse.as_ref(),
+            (Some(dense), None) => dense.as_ref(),
+            (None, None) => panic!("No entity grid resource."),
+        }
+    }
+}
+
+/// Write access to the entity grid, whichever backend is active.
+#[derive(SystemParam)]
+pub struct EntityGridsMut<'w> {
+    dense: Option<ResMut<'w, Grid2<EntitySet>>>,
+    sparse: Option<ResMut<'w, SparseEntityGrid>>,
+}
+impl Deref for EntityGridsMut<'_> {
+    type Target = dyn EntityGrid;
+    fn deref(&self) -> &Self::Target {
+        match (&self.dense, &self.sparse) {
+            (_, Some(sparse)) => sparse.as_ref(),
+            (Some(dense), None) => dense.as_ref(),
+            (None, None) => panic!("No entity grid resource."),
+        }
+    }
+}
+impl DerefMut for EntityGridsMut<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match (&mut self.dense, &mut self.sparse) {
+            (_, Some(sparse)) => sparse.as_mut(),
+            (Some(dense), None) => dense.as_mut(),
+            (None, None) => panic!("No entity grid resource."),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::grid::{entity::EntitySet, Grid2, GridSpec};
 
-    use bevy::prelude::*;
+    use crate::{
+        aabb::Aabb2,
+        grid::{
+            entity::EntitySet, EntityGrid, EntityGridEvent, Grid2, GridBackend, GridEntity,
+            GridSpec, SparseEntityGrid,
+        },
+    };
+
+    use bevy::{ecs::system::RunSystemOnce, prelude::*, utils::HashSet};
+
+    fn grids(spec: GridSpec) -> (Grid2<EntitySet>, SparseEntityGrid) {
+        let mut dense = Grid2::<EntitySet>::default();
+        dense.resize_with(spec.clone());
+        let mut sparse = SparseEntityGrid::default();
+        sparse.resize_with(spec);
+        (dense, sparse)
+    }
 
     #[test]
     fn test_update() {
@@ -137,6 +535,7 @@ mod tests {
                 width: 10.0,
                 visualize: false,
                 visualize_navigation: false,
+                ..default()
             },
             ..Default::default()
         };
@@ -148,4 +547,274 @@ mod tests {
         assert!(grid.get_mut((5, 5)).is_some());
         assert!(grid.get((5, 5)).is_some());
     }
+
+    #[test]
+    fn test_out_of_bounds() {
+        let mut grid = Grid2::<EntitySet> {
+            spec: GridSpec {
+                rows: 10,
+                cols: 10,
+                width: 10.0,
+                visualize: false,
+                visualize_navigation: false,
+                ..default()
+            },
+            ..Default::default()
+        };
+        grid.resize();
+        let entity = Entity::from_raw(0);
+
+        let event = grid
+            .update_entity(entity, None, Vec2::new(1000., -1000.))
+            .unwrap();
+        assert_eq!(event.cell, Some((0, 9)));
+        assert_eq!(grid[(0, 9)], HashSet::from_iter([entity]));
+
+        let event = grid
+            .update_entity(entity, event.cell, Vec2::new(f32::NAN, f32::INFINITY))
+            .unwrap();
+        assert_eq!(event.cell, Some((9, 0)));
+        assert!(grid[(0, 9)].is_empty());
+
+        // Unsized grids ignore updates.
+        let mut grid = Grid2::<EntitySet>::default();
+        assert!(grid.update_entity(entity, None, Vec2::ZERO).is_none());
+    }
+
+    #[test]
+    fn test_entities_in_radius() {
+        let mut grid = Grid2::<EntitySet> {
+            spec: GridSpec {
+                rows: 10,
+                cols: 10,
+                width: 10.0,
+                visualize: false,
+                visualize_navigation: false,
+                ..default()
+            },
+            ..Default::default()
+        };
+        grid.resize();
+        let near = Entity::from_raw(0);
+        let far = Entity::from_raw(1);
+        grid.update_entity(near, None, Vec2::new(5., 5.));
+        grid.update_entity(far, None, Vec2::new(40., 40.));
+
+        let mut cells = Vec::new();
+        grid.for_each_in_radius(Vec2::ZERO, 15., |rowcol| cells.push(rowcol));
+        assert_eq!(cells, grid.get_in_radius(Vec2::ZERO, 15.));
+
+        // The scratch buffer is cleared and reused.
+        let mut scratch = HashSet::from_iter([far]);
+        grid.get_entities_in_radius_with(Vec2::ZERO, 15., &mut scratch);
+        assert_eq!(scratch, HashSet::from_iter([near]));
+        assert_eq!(scratch, grid.get_entities_in_radius(Vec2::ZERO, 15.));
+    }
+
+    #[test]
+    fn test_sparse_matches_dense() {
+        let (dense, sparse) = grids(GridSpec {
+            rows: 10,
+            cols: 10,
+            width: 10.0,
+            ..default()
+        });
+        let mut grids: [Box<dyn EntityGrid>; 2] = [Box::new(dense), Box::new(sparse)];
+        let a = Entity::from_raw(0);
+        let b = Entity::from_raw(1);
+        for grid in &mut grids {
+            let event = grid.update_entity(a, None, Vec2::new(5., 5.)).unwrap();
+            let cell = grid.update_entity(b, None, Vec2::new(5., 5.)).unwrap().cell;
+            let moved = grid
+                .update_entity(a, event.cell, Vec2::new(40., 40.))
+                .unwrap();
+            assert_eq!(moved.prev_cell, Some((5, 5)));
+            assert!(!moved.prev_cell_empty);
+            assert_eq!(moved.cell, Some((9, 9)));
+
+            assert_eq!(
+                grid.get_entities_in_radius(Vec2::ZERO, 15.),
+                HashSet::from_iter([b])
+            );
+            let aabb = Aabb2 {
+                min: Vec2::splat(30.),
+                max: Vec2::splat(45.),
+            };
+            assert_eq!(grid.get_entities_in_aabb(&aabb), vec![a]);
+            assert!(grid.is_cell_empty((0, 0)));
+            assert!(!grid.is_cell_empty((9, 9)));
+
+            let mut occupied = Vec::new();
+            grid.for_each_occupied_cell(&mut |rowcol, _| occupied.push(rowcol));
+            occupied.sort();
+            assert_eq!(occupied, vec![(5, 5), (9, 9)]);
+
+            grid.remove(b, &GridEntity { cell });
+            assert!(grid.is_cell_empty((5, 5)));
+        }
+    }
+
+    #[test]
+    fn test_iter_entities() {
+        let (dense, sparse) = grids(GridSpec {
+            rows: 10,
+            cols: 8,
+            width: 10.0,
+            ..default()
+        });
+        let mut grids: [Box<dyn EntityGrid>; 2] = [Box::new(dense), Box::new(sparse)];
+        for grid in &mut grids {
+            assert!(grid.is_empty());
+            let entities = [0, 1, 2].map(Entity::from_raw);
+            grid.update_entity(entities[0], None, Vec2::new(5., 5.));
+            grid.update_entity(entities[1], None, Vec2::new(5., 5.));
+            let event = grid.update_entity(entities[2], None, Vec2::ZERO).unwrap();
+            grid.update_entity(entities[2], event.cell, Vec2::new(-35., 25.));
+
+            let mut tracked: Vec<_> = grid.iter_entities().collect();
+            tracked.sort();
+            assert_eq!(
+                tracked,
+                vec![
+                    (entities[0], (5, 4)),
+                    (entities[1], (5, 4)),
+                    (entities[2], (7, 0)),
+                ]
+            );
+            assert_eq!(grid.len(), 3);
+        }
+    }
+
+    #[test]
+    fn test_cell_capacity() {
+        let spec = GridSpec {
+            rows: 4,
+            cols: 4,
+            cell_capacity: 16,
+            ..default()
+        };
+        let mut dense = Grid2::<EntitySet>::default();
+        dense.resize_with_capacity(spec.clone());
+        assert_eq!(dense.cells.len(), 16);
+        assert!(dense.cells.iter().all(|cell| cell.capacity() >= 16));
+
+        let mut sparse = SparseEntityGrid::default();
+        sparse.resize_with(spec);
+        sparse.update_entity(Entity::from_raw(0), None, Vec2::ZERO);
+        assert!(sparse.cells.values().all(|cell| cell.capacity() >= 16));
+    }
+
+    #[test]
+    fn test_switch_backend() {
+        let mut world = World::new();
+        let spec = GridSpec {
+            rows: 10,
+            cols: 10,
+            width: 10.0,
+            ..default()
+        };
+        let (mut dense, _) = grids(spec.clone());
+        let entity = world
+            .spawn((GridEntity::default(), Transform::from_xyz(5., 5., 0.)))
+            .id();
+        let event = dense
+            .update_entity(entity, None, Vec2::new(5., 5.))
+            .unwrap();
+        world.get_mut::<GridEntity>(entity).unwrap().cell = event.cell;
+        world.insert_resource(dense);
+        world.init_resource::<Events<EntityGridEvent>>();
+        world.insert_resource(GridSpec {
+            backend: GridBackend::Sparse,
+            ..spec
+        });
+
+        world.run_system_once(GridBackend::update);
+        assert!(world.get_resource::<Grid2<EntitySet>>().is_none());
+        assert_eq!(world.get::<GridEntity>(entity).unwrap().cell, None);
+
+        world.run_system_once(GridEntity::update);
+        let sparse = world.resource::<SparseEntityGrid>();
+        assert_eq!(sparse.cells.len(), 1);
+        assert_eq!(sparse.cell((5, 5)), Some(&HashSet::from_iter([entity])));
+    }
+
+    #[test]
+    fn test_sparse_matches_dense_when_moving() {
+        let (dense, sparse) = grids(GridSpec {
+            rows: 256,
+            cols: 256,
+            width: 10.0,
+            ..default()
+        });
+        let mut grids: [Box<dyn EntityGrid>; 2] = [Box::new(dense), Box::new(sparse)];
+        let positions: Vec<Vec2> = (0..200)
+            .map(|i| {
+                Vec2::new(
+                    ((i * 37) % 2500) as f32 - 1250.,
+                    ((i * 91) % 2500) as f32 - 1250.,
+                )
+            })
+            .collect();
+
+        let mut results = Vec::new();
+        for grid in &mut grids {
+            let mut cells: Vec<GridEntity> = (0..positions.len())
+                .map(|_| GridEntity::default())
+                .collect();
+            for step in 0..10 {
+                for (i, position) in positions.iter().enumerate() {
+                    let entity = Entity::from_raw(i as u32);
+                    let position = *position + Vec2::splat(step as f32 * 7.);
+                    if let Some(event) = grid.update_entity(entity, cells[i].cell, position) {
+                        cells[i].cell = event.cell;
+                    }
+                }
+            }
+            let mut found = 0;
+            for position in &positions {
+                found += grid.get_entities_in_radius(*position, 30.).len();
+            }
+            results.push(found);
+        }
+        assert_eq!(results[0], results[1]);
+    }
+
+    #[test]
+    fn test_get_nearest() {
+        let (dense, sparse) = grids(GridSpec {
+            rows: 10,
+            cols: 10,
+            width: 10.0,
+            ..default()
+        });
+        let mut grids: [Box<dyn EntityGrid>; 2] = [Box::new(dense), Box::new(sparse)];
+        let entities: Vec<Entity> = (0..4).map(Entity::from_raw).collect();
+        for grid in &mut grids {
+            assert_eq!(grid.get_nearest(Vec2::ZERO, 100., |_| true), None);
+
+            // Two entities tied in opposite cells, one further away, one in the same cell.
+            grid.update_entity(entities[2], None, Vec2::new(15., 5.));
+            grid.update_entity(entities[1], None, Vec2::new(-5., 5.));
+            grid.update_entity(entities[0], None, Vec2::new(-35., 5.));
+            grid.update_entity(entities[3], None, Vec2::new(5., 5.));
+            let grid: &dyn EntityGrid = grid.as_ref();
+
+            let position = Vec2::new(5., 5.);
+            assert_eq!(
+                grid.get_nearest(position, 100., |_| true),
+                Some((entities[3], 0.))
+            );
+            let not_center = |entity| entity != entities[3];
+            assert_eq!(
+                grid.get_nearest(position, 100., not_center),
+                Some((entities[1], 100.))
+            );
+            assert_eq!(
+                grid.get_nearest(position, 100., |entity| entity == entities[0]),
+                Some((entities[0], 1600.))
+            );
+            assert_eq!(grid.get_nearest(position, 5., not_center), None);
+            assert_eq!(grid.get_nearest(position, 100., |_| false), None);
+        }
+    }
 }
@@ -2,6 +2,7 @@ use bevy::{
     prelude::*,
     render::render_resource::{AsBindGroup, ShaderRef},
     sprite::Material2d,
+    utils::HashMap,
 };
 
 use crate::prelude::*;
@@ -18,6 +19,9 @@ impl Plugin for FogPlugin {
         app.add_plugins(ShaderPlanePlugin::<FogShaderMaterial>::default())
             .add_plugins(Grid2Plugin::<TeamVisibility>::default())
             .add_event::<VisibilityUpdateEvent>()
+            .init_resource::<FogAssets>()
+            .init_resource::<DebugFogTeam>()
+            .init_resource::<VisibleCells>()
             .add_systems(
                 FixedUpdate,
                 (
@@ -37,7 +41,17 @@ pub struct VisibilityUpdate {
     pub rowcol: RowCol,
 }
 
+/// Shows another team's fog instead of the player's, for spectating and debugging.
+#[derive(Resource, Default, Debug)]
+pub struct DebugFogTeam(pub Option<Team>);
+
+/// Cells each entity currently adds visibility to.
+/// Removing exactly these cells keeps counts balanced when obstacles or radii change.
+#[derive(Resource, Default, Debug)]
+pub struct VisibleCells(pub HashMap<Entity, Vec<RowCol>>);
+
 /// Communicates to other systems that visibility has been updated.
+/// Updates are sent for every team, so consumers should filter by the team they display.
 #[derive(Event, Default)]
 pub struct VisibilityUpdateEvent {
     pub additions: Vec<VisibilityUpdate>,
@@ -48,8 +62,14 @@ pub struct VisibilityUpdateEvent {
 #[derive(Clone, Default)]
 pub struct TeamVisibility {
     teams: [u32; Team::COUNT],
+    explored: [bool; Team::COUNT],
 }
 impl TeamVisibility {
+    /// Returns true if the team has ever seen this cell.
+    pub fn is_explored(&self, team: Team) -> bool {
+        self.explored[team as usize]
+    }
+
     pub fn get(&self, team: Team) -> u32 {
         self.teams[team as usize]
     }
@@ -59,23 +79,83 @@ impl TeamVisibility {
     }
 }
 
+/// Handles to fog assets.
+#[derive(Resource)]
+pub struct FogAssets {
+    /// Shown for static objects in explored cells that aren't currently visible.
+    pub explored_material: Handle<ColorMaterial>,
+}
+impl FromWorld for FogAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mut materials = world.get_resource_mut::<Assets<ColorMaterial>>().unwrap();
+        Self {
+            explored_material: materials.add(ColorMaterial::from(Color::GRAY.with_a(0.3))),
+        }
+    }
+}
+
+/// Stores the original material of an object dimmed by fog.
+#[derive(Component)]
+pub struct Dimmed(pub Handle<ColorMaterial>);
+
+/// Query for entities whose visibility is controlled by fog.
+type FogQueryData<'a> = (
+    Entity,
+    &'a GridEntity,
+    &'a mut Visibility,
+    Option<&'a Object>,
+    Option<&'a mut Handle<ColorMaterial>>,
+    Option<&'a Dimmed>,
+);
+
 impl Grid2<TeamVisibility> {
     pub fn update_visibility(
-        mut query: Query<(&GridEntity, &mut Visibility)>,
+        mut commands: Commands,
+        mut query: Query<FogQueryData>,
         grid: ResMut<Self>,
         configs: Res<Configs>,
+        assets: Res<FogAssets>,
     ) {
-        for (grid_entity, mut visibility) in &mut query {
-            if let Some(cell) = grid_entity.cell {
-                *visibility = grid.get_visibility(cell, configs.player_team)
+        for (entity, grid_entity, mut visibility, object, material, dimmed) in &mut query {
+            let Some(cell) = grid_entity.cell else {
+                continue;
+            };
+            let mut new_visibility = grid.get_visibility(cell, configs.player_team);
+            // Static objects stay dimly visible in explored cells.
+            let explored = new_visibility == Visibility::Hidden
+                && object.is_some_and(|object| object.is_static())
+                && grid.is_explored(cell, configs.player_team);
+            match (explored, material, dimmed) {
+                (true, Some(mut material), None) => {
+                    commands.entity(entity).insert(Dimmed(material.clone()));
+                    *material = assets.explored_material.clone();
+                    new_visibility = Visibility::Visible;
+                }
+                (true, _, Some(_)) => new_visibility = Visibility::Visible,
+                (false, Some(mut material), Some(dimmed)) => {
+                    *material = dimmed.0.clone();
+                    commands.entity(entity).remove::<Dimmed>();
+                }
+                _ => {}
+            }
+            if *visibility != new_visibility {
+                *visibility = new_visibility;
             }
         }
     }
 
+    /// Returns true if the team has ever seen the cell.
+    pub fn is_explored(&self, rowcol: RowCol, team: Team) -> bool {
+        self.get(rowcol)
+            .is_some_and(|visibility| visibility.is_explored(team))
+    }
+
     pub fn update(
         mut grid: ResMut<Self>,
         configs: Res<Configs>,
-        teams: Query<&Team>,
+        obstacles: Res<Grid2<Obstacle>>,
+        teams: Query<(&Team, Option<&Object>)>,
+        mut visible_cells: ResMut<VisibleCells>,
         mut grid_events: EventReader<EntityGridEvent>,
         mut visibility_events: EventWriter<VisibilityUpdateEvent>,
     ) {
@@ -88,35 +168,54 @@ impl Grid2<TeamVisibility> {
             cell,
         } in grid_events.read()
         {
-            let team = *teams.get(entity).unwrap();
-            if let Some(prev_cell) = prev_cell {
-                updates
-                    .removals
-                    .extend(grid.remove_visibility(prev_cell, team, &configs))
+            let (&team, object) = teams.get(entity).unwrap();
+            let radius = Self::visibility_radius(object, &configs);
+            if prev_cell.is_some() {
+                if let Some(cells) = visible_cells.0.remove(&entity) {
+                    updates
+                        .removals
+                        .extend(grid.remove_visibility(&cells, team));
+                }
             }
             if let Some(cell) = cell {
+                let cells = grid.visible_cells(cell, radius, &obstacles);
                 updates
                     .additions
-                    .extend(grid.add_visibility(cell, team, &configs));
+                    .extend(grid.add_visibility(cell, &cells, team, &configs));
+                visible_cells.0.insert(entity, cells);
             }
         }
 
         visibility_events.send(updates);
     }
 
-    fn remove_visibility(
-        &mut self,
+    /// Returns the visibility radius for an object, falling back to the global default.
+    pub fn visibility_radius(object: Option<&Object>, configs: &Configs) -> u16 {
+        object
+            .and_then(|object| configs.objects.get(object))
+            .map_or(configs.visibility_radius, |config| config.visibility_radius)
+    }
+
+    /// Returns cells within `radius` of `rowcol` that aren't occluded by obstacles.
+    fn visible_cells(
+        &self,
         rowcol: RowCol,
-        team: Team,
-        configs: &Configs,
-    ) -> Vec<VisibilityUpdate> {
+        radius: u16,
+        obstacles: &Grid2<Obstacle>,
+    ) -> Vec<RowCol> {
+        let mut cells = self.get_in_radius_discrete(rowcol, radius);
+        cells.retain(|&other_rowcol| obstacles.line_of_sight(rowcol, other_rowcol));
+        cells
+    }
+
+    /// Removes visibility from the cells previously added by `add_visibility`.
+    fn remove_visibility(&mut self, cells: &[RowCol], team: Team) -> Vec<VisibilityUpdate> {
         let mut updates = Vec::default();
-        let radius = configs.visibility_radius;
-        for other_rowcol in self.get_in_radius_discrete(rowcol, radius) {
+        for &other_rowcol in cells {
             if let Some(grid_visibility) = self.get_mut(other_rowcol) {
                 if grid_visibility.get(team) > 0 {
                     *grid_visibility.get_mut(team) -= 1;
-                    if team == configs.player_team && grid_visibility.get(team) == 0 {
+                    if grid_visibility.get(team) == 0 {
                         updates.push(VisibilityUpdate {
                             team,
                             rowcol: other_rowcol,
@@ -141,17 +240,16 @@ impl Grid2<TeamVisibility> {
     fn add_visibility(
         &mut self,
         cell: RowCol,
+        cells: &[RowCol],
         team: Team,
         configs: &Configs,
     ) -> Vec<VisibilityUpdate> {
         let mut updates = Vec::default();
-        let radius = configs.visibility_radius;
-        for other_rowcol in self.get_in_radius_discrete(cell, radius) {
+        for &other_rowcol in cells {
             if let Some(grid_visibility) = self.get_mut(other_rowcol) {
                 *grid_visibility.get_mut(team) += 1;
-                if team == configs.player_team
-                    && GridSpec::in_radius(cell, other_rowcol, configs.fog_radius)
-                {
+                grid_visibility.explored[team as usize] = true;
+                if GridSpec::in_radius(cell, other_rowcol, configs.fog_radius) {
                     updates.push(VisibilityUpdate {
                         team,
                         rowcol: other_rowcol,
@@ -172,6 +270,10 @@ pub struct FogShaderMaterial {
     pub size: GridSize,
     #[storage(2, read_only)]
     pub grid: Vec<f32>,
+    /// Team whose fog buffer drives `grid`.
+    pub team: Team,
+    /// Fog buffers for each team.
+    pub buffers: [Vec<f32>; Team::COUNT],
 }
 impl Default for FogShaderMaterial {
     fn default() -> Self {
@@ -179,6 +281,8 @@ impl Default for FogShaderMaterial {
             color: Color::BLACK,
             size: GridSize::default(),
             grid: Vec::default(),
+            team: Team::default(),
+            buffers: Default::default(),
         }
     }
 }
@@ -187,8 +291,11 @@ impl ShaderPlaneMaterial for FogShaderMaterial {
         self.size.width = spec.width;
         self.size.rows = spec.rows.into();
         self.size.cols = spec.cols.into();
-        self.grid
-            .resize(spec.rows as usize * spec.cols as usize, 1.);
+        let num_cells = spec.rows as usize * spec.cols as usize;
+        self.grid.resize(num_cells, 1.);
+        for buffer in &mut self.buffers {
+            buffer.resize(num_cells, 1.);
+        }
     }
     fn translation(_window: &Window, _spec: &GridSpec) -> Vec3 {
         Vec2::ZERO.extend(zindex::FOG_OF_WAR)
@@ -202,19 +309,323 @@ impl Material2d for FogShaderMaterial {
 impl FogShaderMaterial {
     pub fn update(
         spec: Res<GridSpec>,
+        configs: Res<Configs>,
+        debug_team: Res<DebugFogTeam>,
         assets: Res<ShaderPlaneAssets<Self>>,
         mut shader_assets: ResMut<Assets<Self>>,
         mut updates: EventReader<VisibilityUpdateEvent>,
     ) {
         let material: &mut FogShaderMaterial =
             shader_assets.get_mut(&assets.shader_material).unwrap();
+
+        // Swap in the displayed team's buffer.
+        let team = debug_team.0.unwrap_or(configs.player_team);
+        if material.team != team {
+            material.team = team;
+            material.grid.clone_from(&material.buffers[team as usize]);
+        }
+
         for event in updates.read() {
-            for &VisibilityUpdate { team: _, rowcol } in &event.removals {
-                material.grid[spec.flat_index(rowcol)] = 0.5;
-            }
-            for &VisibilityUpdate { team: _, rowcol } in &event.additions {
-                material.grid[spec.flat_index(rowcol)] = 0.;
+            let removals = event.removals.iter().map(|update| (update, 0.5));
+            let additions = event.additions.iter().map(|update| (update, 0.));
+            for (&VisibilityUpdate { team, rowcol }, value) in removals.chain(additions) {
+                let index = spec.flat_index(rowcol);
+                material.buffers[team as usize][index] = value;
+                if team == material.team {
+                    material.grid[index] = value;
+                }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use bevy::{ecs::system::RunSystemOnce, prelude::*, utils::HashMap};
+
+    use crate::prelude::*;
+
+    use crate::grid::{ShaderPlaneAssets, ShaderPlaneMaterial};
+
+    use super::{
+        DebugFogTeam, Dimmed, FogAssets, FogShaderMaterial, TeamVisibility, VisibilityUpdate,
+        VisibilityUpdateEvent, VisibleCells,
+    };
+
+    #[test]
+    fn test_clamp_radii() {
+        let spec = GridSpec {
+            rows: 256,
+            cols: 256,
+            width: 10.0,
+            visualize: false,
+            visualize_navigation: false,
+            ..default()
+        };
+        let mut grid = Grid2::<TeamVisibility>::default();
+        grid.resize_with(spec.clone());
+
+        let mut world = World::new();
+        world.insert_resource(spec);
+        world.insert_resource(grid);
+        world.insert_resource(Configs {
+            player_team: Team::Blue,
+            visibility_radius: u16::MAX,
+            fog_radius: u16::MAX,
+            objects: ObjectConfigs(HashMap::from_iter([(
+                Object::Head,
+                ObjectConfig {
+                    visibility_radius: u16::MAX,
+                    ..default()
+                },
+            )])),
+            ..default()
+        });
+        world.init_resource::<Grid2<Obstacle>>();
+        world.init_resource::<Events<EntityGridEvent>>();
+        world.init_resource::<Events<VisibilityUpdateEvent>>();
+        world.init_resource::<VisibleCells>();
+
+        world.run_system_once(Configs::clamp_radii);
+        let configs = world.resource::<Configs>();
+        assert_eq!(configs.visibility_radius, Configs::MAX_RADIUS);
+        assert_eq!(configs.fog_radius, Configs::MAX_RADIUS);
+        assert_eq!(
+            configs.objects[&Object::Head].visibility_radius,
+            Configs::MAX_RADIUS
+        );
+
+        let entity = world.spawn(Team::Blue).id();
+        world.send_event(EntityGridEvent {
+            entity,
+            prev_cell: None,
+            prev_cell_empty: false,
+            cell: Some((128, 128)),
+        });
+        let start = Instant::now();
+        world.run_system_once(Grid2::<TeamVisibility>::update);
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        let grid = world.resource::<Grid2<TeamVisibility>>();
+        assert_eq!(
+            grid.get_visibility((128, 128), Team::Blue),
+            Visibility::Visible
+        );
+        assert_eq!(grid.get_visibility((0, 0), Team::Blue), Visibility::Hidden);
+    }
+
+    #[test]
+    fn test_line_of_sight() {
+        let spec = GridSpec {
+            rows: 10,
+            cols: 10,
+            width: 10.0,
+            visualize: false,
+            visualize_navigation: false,
+            ..default()
+        };
+        let mut grid = Grid2::<TeamVisibility>::default();
+        grid.resize_with(spec.clone());
+        let mut obstacles = Grid2::<Obstacle>::default();
+        obstacles.resize_with(spec.clone());
+        obstacles[(5, 6)] = Obstacle::Full;
+
+        let mut world = World::new();
+        world.insert_resource(spec);
+        world.insert_resource(grid);
+        world.insert_resource(obstacles);
+        world.insert_resource(Configs {
+            player_team: Team::Blue,
+            visibility_radius: 3,
+            fog_radius: 3,
+            ..default()
+        });
+        world.init_resource::<Events<EntityGridEvent>>();
+        world.init_resource::<Events<VisibilityUpdateEvent>>();
+        world.init_resource::<VisibleCells>();
+
+        let entity = world.spawn(Team::Blue).id();
+        world.send_event(EntityGridEvent {
+            entity,
+            prev_cell: None,
+            prev_cell_empty: false,
+            cell: Some((5, 5)),
+        });
+        world.run_system_once(Grid2::<TeamVisibility>::update);
+
+        // The wall itself is visible, but casts a shadow behind it.
+        let grid = world.resource::<Grid2<TeamVisibility>>();
+        assert_eq!(grid.get_visibility((5, 6), Team::Blue), Visibility::Visible);
+        assert_eq!(grid.get_visibility((5, 7), Team::Blue), Visibility::Hidden);
+        assert_eq!(grid.get_visibility((5, 8), Team::Blue), Visibility::Hidden);
+        assert_eq!(grid.get_visibility((5, 4), Team::Blue), Visibility::Visible);
+        assert_eq!(grid.get_visibility((7, 7), Team::Blue), Visibility::Visible);
+
+        // Removing visibility mirrors adding it, even if obstacles changed in between.
+        world.resource_mut::<Grid2<Obstacle>>()[(5, 3)] = Obstacle::Full;
+        world.resource_mut::<Events<EntityGridEvent>>().clear();
+        world.send_event(EntityGridEvent {
+            entity,
+            prev_cell: Some((5, 5)),
+            prev_cell_empty: true,
+            cell: None,
+        });
+        world.run_system_once(Grid2::<TeamVisibility>::update);
+        let grid = world.resource::<Grid2<TeamVisibility>>();
+        assert!(grid.cells.iter().all(|cell| cell.get(Team::Blue) == 0));
+    }
+
+    #[test]
+    fn test_explored() {
+        let spec = GridSpec {
+            rows: 10,
+            cols: 10,
+            width: 10.0,
+            visualize: false,
+            visualize_navigation: false,
+            ..default()
+        };
+        let mut grid = Grid2::<TeamVisibility>::default();
+        grid.resize_with(spec.clone());
+        let configs = || Configs {
+            player_team: Team::Blue,
+            visibility_radius: 2,
+            fog_radius: 2,
+            ..default()
+        };
+        let cells = grid.visible_cells((5, 5), 2, &Grid2::default());
+        grid.add_visibility((5, 5), &cells, Team::Blue, &configs());
+        grid.remove_visibility(&cells, Team::Blue);
+        assert!(grid.is_explored((5, 6), Team::Blue));
+        assert!(!grid.is_explored((5, 6), Team::Red));
+        assert!(!grid.is_explored((0, 0), Team::Blue));
+
+        let mut world = World::new();
+        world.insert_resource(grid);
+        world.insert_resource(configs());
+        world.init_resource::<Assets<ColorMaterial>>();
+        world.init_resource::<FogAssets>();
+        let material = world
+            .resource_mut::<Assets<ColorMaterial>>()
+            .add(ColorMaterial::default());
+        let mut spawn = |object: Object, cell: RowCol| {
+            world
+                .spawn((
+                    object,
+                    GridEntity { cell: Some(cell) },
+                    Visibility::Visible,
+                    material.clone(),
+                ))
+                .id()
+        };
+        let plankton = spawn(Object::Plankton, (5, 6));
+        let worker = spawn(Object::Worker, (5, 6));
+        let unexplored = spawn(Object::Food, (0, 0));
+        world.run_system_once(Grid2::<TeamVisibility>::update_visibility);
+
+        let visibility = |world: &World, entity| *world.get::<Visibility>(entity).unwrap();
+        assert_eq!(visibility(&world, plankton), Visibility::Visible);
+        assert!(world.get::<Dimmed>(plankton).is_some());
+        assert_eq!(visibility(&world, worker), Visibility::Hidden);
+        assert_eq!(visibility(&world, unexplored), Visibility::Hidden);
+
+        // Seeing the cell again restores the original material.
+        world
+            .resource_mut::<Grid2<TeamVisibility>>()
+            .add_visibility((5, 5), &cells, Team::Blue, &configs());
+        world.run_system_once(Grid2::<TeamVisibility>::update_visibility);
+        assert!(world.get::<Dimmed>(plankton).is_none());
+        assert_eq!(
+            world.get::<Handle<ColorMaterial>>(plankton),
+            Some(&material)
+        );
+        assert_eq!(visibility(&world, worker), Visibility::Visible);
+    }
+
+    #[test]
+    fn test_debug_fog_team() {
+        let spec = GridSpec {
+            rows: 10,
+            cols: 10,
+            width: 10.0,
+            visualize: false,
+            visualize_navigation: false,
+            ..default()
+        };
+        let mut world = World::new();
+        world.init_resource::<Assets<Mesh>>();
+        world.init_resource::<Assets<FogShaderMaterial>>();
+        world.init_resource::<ShaderPlaneAssets<FogShaderMaterial>>();
+        world.init_resource::<DebugFogTeam>();
+        world.init_resource::<Events<VisibilityUpdateEvent>>();
+        world.insert_resource(Configs {
+            player_team: Team::Blue,
+            ..default()
+        });
+        let handle = world
+            .resource::<ShaderPlaneAssets<FogShaderMaterial>>()
+            .shader_material
+            .clone();
+        world
+            .resource_mut::<Assets<FogShaderMaterial>>()
+            .get_mut(&handle)
+            .unwrap()
+            .resize(&spec);
+        world.insert_resource(spec.clone());
+
+        world.send_event(VisibilityUpdateEvent {
+            additions: vec![
+                VisibilityUpdate {
+                    team: Team::Blue,
+                    rowcol: (1, 1),
+                },
+                VisibilityUpdate {
+                    team: Team::Red,
+                    rowcol: (8, 8),
+                },
+            ],
+            removals: vec![],
+        });
+        world.run_system_once(FogShaderMaterial::update);
+        let fog = |world: &World, rowcol| {
+            let material = world
+                .resource::<Assets<FogShaderMaterial>>()
+                .get(&handle)
+                .unwrap();
+            material.grid[spec.flat_index(rowcol)]
+        };
+        assert_eq!(fog(&world, (1, 1)), 0.);
+        assert_eq!(fog(&world, (8, 8)), 1.);
+
+        // Switching teams swaps buffers without new updates.
+        world
+            .resource_mut::<Events<VisibilityUpdateEvent>>()
+            .clear();
+        world.resource_mut::<DebugFogTeam>().0 = Some(Team::Red);
+        world.run_system_once(FogShaderMaterial::update);
+        assert_eq!(fog(&world, (1, 1)), 1.);
+        assert_eq!(fog(&world, (8, 8)), 0.);
+    }
+
+    #[test]
+    fn test_visibility_radius() {
+        let configs = Configs {
+            visibility_radius: 6,
+            objects: ObjectConfigs(HashMap::from_iter([(
+                Object::Head,
+                ObjectConfig {
+                    visibility_radius: 10,
+                    ..default()
+                },
+            )])),
+            ..default()
+        };
+        let radius = |object| Grid2::<TeamVisibility>::visibility_radius(object, &configs);
+        assert_eq!(radius(Some(&Object::Head)), 10);
+        // Objects without a config fall back to the global default.
+        assert_eq!(radius(Some(&Object::Worker)), 6);
+        assert_eq!(radius(None), 6);
+    }
+}
@@ -63,4 +63,34 @@ impl<T: Sized + Default + Clone + Send + Sync + 'static> Grid2<T> {
         let index = self.flat_index(rowcol);
         self.cells.get_mut(index)
     }
+
+    /// Walk the cells on the line from `from` to `to` (inclusive) using Bresenham's algorithm.
+    pub fn line(from: RowCol, to: RowCol) -> impl Iterator<Item = RowCol> {
+        let (mut row, mut col) = (from.0 as i32, from.1 as i32);
+        let (to_row, to_col) = (to.0 as i32, to.1 as i32);
+        let (drow, dcol) = ((to_row - row).abs(), -(to_col - col).abs());
+        let (step_row, step_col) = ((to_row - row).signum(), (to_col - col).signum());
+        let mut error = drow + dcol;
+        let mut done = false;
+        std::iter::from_fn(move || {
+            if done {
+                return None;
+            }
+            let rowcol = (row as u16, col as u16);
+            if row == to_row && col == to_col {
+                done = true;
+                return Some(rowcol);
+            }
+            let error2 = 2 * error;
+            if error2 >= dcol {
+                error += dcol;
+                row += step_row;
+            }
+            if error2 <= drow {
+                error += drow;
+                col += step_col;
+            }
+            Some(rowcol)
+        })
+    }
 }
@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+
+use crate::prelude::*;
+
+/// Stores the number of units per team in a cell.
+#[derive(Clone, Default, Debug)]
+pub struct TeamInfluence {
+    teams: [u32; Team::COUNT],
+}
+impl TeamInfluence {
+    pub fn get(&self, team: Team) -> u32 {
+        self.teams[team as usize]
+    }
+
+    pub fn get_mut(&mut self, team: Team) -> &mut u32 {
+        &mut self.teams[team as usize]
+    }
+
+    /// Returns the number of units in this cell on teams hostile to `team`.
+    /// Neutral objects (`Team::None`) are never considered hostile.
+    pub fn enemies(&self, team: Team) -> u32 {
+        Team::ALL
+            .iter()
+            .filter(|&&other| other != team && other != Team::None)
+            .map(|&other| self.get(other))
+            .sum()
+    }
+}
+
+impl Grid2<TeamInfluence> {
+    /// Recompute unit counts per team from the entity grid.
+    pub fn update(mut grid: ResMut<Self>, query: Query<(&GridEntity, &Team)>) {
+        grid.cells.fill(TeamInfluence::default());
+        for (grid_entity, &team) in &query {
+            if let Some(influence) = grid_entity.cell.and_then(|rowcol| grid.get_mut(rowcol)) {
+                *influence.get_mut(team) += 1;
+            }
+        }
+    }
+}
+
+/// Additive A* cost for entering cells controlled by enemies of `team`.
+/// Without an influence grid, no cells cost extra.
+#[derive(Default)]
+pub struct EnemyInfluenceCost<'a> {
+    pub influence: Option<&'a Grid2<TeamInfluence>>,
+    pub team: Team,
+    pub weight: f32,
+}
+impl EnemyInfluenceCost<'_> {
+    /// Returns the extra cost of entering the given cell.
+    pub fn cost(&self, rowcol: RowCol) -> f32 {
+        if self.weight == 0. {
+            return 0.;
+        }
+        self.influence
+            .and_then(|influence| influence.get(rowcol))
+            .map_or(0., |influence| {
+                influence.enemies(self.team) as f32 * self.weight
+            })
+    }
+}
@@ -1,16 +1,17 @@
 use bevy::{
     prelude::*,
     render::render_resource::{AsBindGroup, ShaderRef},
-    sprite::Material2d,
+    sprite::{Material2d, MaterialMesh2dBundle},
 };
 
 use crate::prelude::*;
 
 use self::window::ScalableWindow;
+use crate::waypoint::{FormationSlot, WaypointAssets};
 
 use super::{
     fog::{VisibilityUpdate, VisibilityUpdateEvent},
-    shader_plane::{ShaderPlaneAssets, ShaderPlanePlugin},
+    shader_plane::{ShaderPlane, ShaderPlaneAssets, ShaderPlanePlugin},
     ShaderPlaneMaterial,
 };
 
@@ -23,9 +24,14 @@ impl Plugin for MinimapPlugin {
         app.add_plugins(ShaderPlanePlugin::<MinimapShaderMaterial>::default())
             .add_systems(
                 FixedUpdate,
-                MinimapShaderMaterial::update
-                    .before(CameraController::update)
-                    .after(GridEntity::update),
+                (
+                    MinimapShaderMaterial::update
+                        .before(CameraController::update)
+                        .after(GridEntity::update),
+                    MinimapMarker::update
+                        .in_set(SystemStage::PostApply)
+                        .after(Waypoint::cleanup),
+                ),
             );
     }
 }
@@ -45,6 +51,8 @@ pub struct MinimapShaderMaterial {
     grid: Vec<u32>,
     #[storage(5, read_only)]
     visibility_grid: Vec<f32>,
+    #[uniform(6)]
+    team_colors: [Vec4; Team::COUNT],
 }
 impl Default for MinimapShaderMaterial {
     fn default() -> Self {
@@ -55,6 +63,7 @@ impl Default for MinimapShaderMaterial {
             viewport_size: Vec2 { x: 16., y: 9. },
             grid: Vec::default(),
             visibility_grid: Vec::default(),
+            team_colors: Team::COLORS.map(|color| color.as_linear_rgba_f32().into()),
         }
     }
 }
@@ -103,9 +112,23 @@ impl MinimapShaderMaterial {
         window.scaled_size().xx() * Self::SCREEN_RATIO
     }
 
+    /// Encodes the team occupying a cell: 0 when empty, otherwise the team index plus one.
+    /// When multiple teams share a cell, the player's team is shown.
+    pub fn cell_code(entities: &EntitySet, teams: &Query<&Team>, player_team: Team) -> u32 {
+        entities
+            .iter()
+            .map(|&entity| teams.get(entity).copied().unwrap_or_default())
+            .max_by_key(|&team| (team == player_team, team as u32))
+            .map_or(0, |team| team as u32 + 1)
+    }
+
     /// Update the grid shader material.
+    #[allow(clippy::too_many_arguments)]
     pub fn update(
         spec: Res<GridSpec>,
+        grid: EntityGrids,
+        teams: Query<&Team>,
+        configs: Res<Configs>,
         assets: Res<ShaderPlaneAssets<Self>>,
         mut shader_assets: ResMut<Assets<Self>>,
         mut grid_events: EventReader<EntityGridEvent>,
@@ -122,25 +145,32 @@ impl MinimapShaderMaterial {
         } in grid_events.read()
         {
             if let Some(rowcol) = prev_cell {
-                if prev_cell_empty && spec.in_bounds(rowcol) {
-                    material.grid[spec.flat_index(rowcol)] = 0;
+                if spec.in_bounds(rowcol) {
+                    material.grid[spec.flat_index(rowcol)] = match grid.cell(rowcol) {
+                        Some(cell) if !prev_cell_empty => {
+                            Self::cell_code(cell, &teams, configs.player_team)
+                        }
+                        _ => 0,
+                    };
                 }
             }
             if let Some(rowcol) = rowcol {
                 if spec.in_bounds(rowcol) {
-                    material.grid[spec.flat_index(rowcol)] = 1;
+                    material.grid[spec.flat_index(rowcol)] = grid
+                        .cell(rowcol)
+                        .map_or(0, |cell| Self::cell_code(cell, &teams, configs.player_team));
                 }
             }
         }
 
         for event in visibility_updates.read() {
-            for &VisibilityUpdate { team: _, rowcol } in &event.removals {
-                if spec.in_bounds(rowcol) {
+            for &VisibilityUpdate { team, rowcol } in &event.removals {
+                if team == configs.player_team && spec.in_bounds(rowcol) {
                     material.visibility_grid[spec.flat_index(rowcol)] = 0.5;
                 }
             }
-            for &VisibilityUpdate { team: _, rowcol } in &event.additions {
-                if spec.in_bounds(rowcol) {
+            for &VisibilityUpdate { team, rowcol } in &event.additions {
+                if team == configs.player_team && spec.in_bounds(rowcol) {
                     material.visibility_grid[spec.flat_index(rowcol)] = 0.;
                 }
             }
@@ -160,3 +190,105 @@ impl Material2d for MinimapShaderMaterial {
         "shaders/minimap.wgsl".into()
     }
 }
+
+/// Waypoints shown on the minimap. Formation slots are left out to avoid clutter.
+type MarkedWaypoint = (With<Waypoint>, Without<FormationSlot>);
+
+/// Marker drawn on the minimap at the position of a waypoint.
+/// Markers are children of the minimap plane, so they use its local [-0.5, 0.5] coordinates.
+#[derive(Component, Debug)]
+pub struct MinimapMarker {
+    pub target: Entity,
+}
+impl MinimapMarker {
+    const SIZE: f32 = 0.04;
+
+    /// Compute the position within the minimap plane for a world position.
+    pub fn local_position(spec: &GridSpec, position: Vec2) -> Vec3 {
+        // The minimap plane is flipped vertically.
+        (position / spec.scale() * Vec2 { x: 1., y: -1. }).extend(1.)
+    }
+
+    /// Spawn, move, and despawn markers to match the current waypoints.
+    pub fn update(
+        waypoints: Query<(Entity, &Transform), MarkedWaypoint>,
+        mut markers: Query<(Entity, &Self, &Parent, &mut Transform), Without<Waypoint>>,
+        minimap: Query<Entity, With<ShaderPlane<MinimapShaderMaterial>>>,
+        spec: Res<GridSpec>,
+        assets: Res<WaypointAssets>,
+        mut commands: Commands,
+    ) {
+        let Ok(minimap_entity) = minimap.get_single() else {
+            return;
+        };
+
+        let mut marked = Vec::with_capacity(waypoints.iter().len());
+        for (entity, marker, parent, mut transform) in &mut markers {
+            match waypoints.get(marker.target) {
+                Ok((_, waypoint_transform)) if parent.get() == minimap_entity => {
+                    transform.translation =
+                        Self::local_position(&spec, waypoint_transform.translation.xy());
+                    marked.push(marker.target);
+                }
+                // Waypoint is gone or the minimap was respawned.
+                _ => commands.entity(entity).despawn_recursive(),
+            }
+        }
+
+        for (waypoint_entity, waypoint_transform) in &waypoints {
+            if marked.contains(&waypoint_entity) {
+                continue;
+            }
+            let marker = commands
+                .spawn((
+                    MaterialMesh2dBundle::<ColorMaterial> {
+                        mesh: assets.mesh.clone().into(),
+                        transform: Transform::default()
+                            .with_scale(Vec2::splat(Self::SIZE).extend(1.))
+                            .with_translation(Self::local_position(
+                                &spec,
+                                waypoint_transform.translation.xy(),
+                            )),
+                        material: assets.blue_material.clone(),
+                        ..default()
+                    },
+                    Self {
+                        target: waypoint_entity,
+                    },
+                    Name::new("MinimapMarker"),
+                ))
+                .id();
+            commands.entity(minimap_entity).add_child(marker);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{ecs::system::RunSystemOnce, prelude::*};
+
+    use crate::prelude::*;
+
+    use super::MinimapShaderMaterial;
+
+    #[test]
+    fn test_cell_code() {
+        let mut world = World::new();
+        let red = world.spawn(Team::Red).id();
+        let blue = world.spawn(Team::Blue).id();
+        let neutral = world.spawn(Team::None).id();
+        world.run_system_once(move |teams: Query<&Team>| {
+            let code = |entities: &[Entity], player_team| {
+                let entities = EntitySet::from_iter(entities.iter().copied());
+                MinimapShaderMaterial::cell_code(&entities, &teams, player_team)
+            };
+            assert_eq!(code(&[], Team::Blue), 0);
+            assert_eq!(code(&[neutral], Team::Blue), 1);
+            assert_eq!(code(&[blue], Team::Blue), 2);
+            assert_eq!(code(&[red, neutral], Team::Blue), 3);
+            // The player's team wins shared cells.
+            assert_eq!(code(&[red, blue, neutral], Team::Blue), 2);
+            assert_eq!(code(&[red, blue, neutral], Team::Red), 3);
+        });
+    }
+}
@@ -2,14 +2,17 @@ use crate::prelude::*;
 use bevy::prelude::*;
 
 mod spec;
-pub use spec::{GridSize, GridSpec, RowCol, RowColDistance};
+pub use spec::{GridBackend, GridSize, GridSpec, RowCol, RowColDistance};
 mod fog;
-pub use fog::FogPlugin;
+pub use fog::{DebugFogTeam, Dimmed, FogPlugin};
 mod entity;
 mod visualizer;
-pub use entity::{EntityGridEvent, EntitySet, GridEntity};
+pub use entity::{
+    EntityGrid, EntityGridEvent, EntityGrids, EntityGridsMut, EntitySet, GridEntity,
+    SparseEntityGrid,
+};
 mod obstacles;
-pub use obstacles::{Obstacle, ObstaclesPlugin};
+pub use obstacles::{Obstacle, ObstaclesPlugin, ObstaclesSpec};
 mod grid2;
 pub use grid2::Grid2;
 mod sparse_grid2;
@@ -17,40 +20,64 @@ pub use sparse_grid2::SparseGrid2;
 mod shader_plane;
 pub use shader_plane::{ShaderPlaneAssets, ShaderPlaneMaterial};
 mod astar;
+mod influence;
 pub use astar::AStarRunner;
+pub use influence::{EnemyInfluenceCost, TeamInfluence};
 mod navigation;
-pub use navigation::{CreateWaypointEvent, NavigationCostEvent, NavigationGrid2};
+pub use navigation::{
+    CreateWaypointEvent, NavigationConfig, NavigationCostEvent, NavigationGrid2, SparseFlowGrid2,
+};
 mod minimap;
 pub use minimap::{MinimapPlugin, MinimapShaderMaterial};
+mod coords;
 mod navigation_visualizer;
+pub use coords::{GridCoordLabel, GridCoordsPlugin};
 
 pub use self::{
-    grid2::Grid2Plugin, navigation::NavigationPlugin,
-    navigation_visualizer::NavigationVisualizerPlugin, visualizer::GridVisualizerPlugin,
+    grid2::Grid2Plugin,
+    navigation::NavigationPlugin,
+    navigation_visualizer::NavigationVisualizerPlugin,
+    visualizer::{GridVisualConfig, GridVisualizerPlugin},
 };
 
-/// Plugin for an spacial entity paritioning grid with optional debug functionality.
+/// Plugin for an spacial entity paritioning grid, obstacles, navigation, and fog.
 pub struct GridPlugin;
 impl Plugin for GridPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<GridSpec>()
+            .register_type::<GridBackend>()
+            .init_resource::<Grid2<EntitySet>>()
             .add_event::<EntityGridEvent>()
-            .add_plugins(GridVisualizerPlugin)
-            .add_plugins(MinimapPlugin)
             .add_plugins(ObstaclesPlugin)
             .add_plugins(NavigationPlugin)
-            .add_plugins(NavigationVisualizerPlugin)
             .add_plugins(FogPlugin)
-            .add_plugins(Grid2Plugin::<EntitySet>::default())
             .add_systems(
                 FixedUpdate,
-                GridEntity::update.in_set(SystemStage::PostApply),
+                (
+                    GridBackend::update.in_set(SystemStage::PreCompute),
+                    GridEntity::update.in_set(SystemStage::PostApply),
+                ),
             );
     }
 }
+
+/// Plugin for the grid visualizer, minimap, and debug overlays.
+/// Not needed to run the simulation headless.
+pub struct GridVisualsPlugin;
+impl Plugin for GridVisualsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(GridVisualizerPlugin)
+            .add_plugins(MinimapPlugin)
+            .add_plugins(NavigationVisualizerPlugin)
+            .add_plugins(GridCoordsPlugin);
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::GridSpec;
+    use bevy::prelude::*;
+
+    use crate::prelude::GridSpec;
 
     #[test]
     fn grid_radius() {
@@ -75,4 +102,64 @@ mod tests {
             ));
         }
     }
+
+    #[test]
+    fn grid_radius_rectangular() {
+        let spec = GridSpec {
+            rows: 16,
+            cols: 4,
+            ..default()
+        };
+        // Columns are limited by the column count, not the row count.
+        let rowcols = spec.get_in_radius_discrete((8, 3), 4);
+        assert!(rowcols.iter().all(|&(_, col)| col < spec.cols));
+        assert!(rowcols.iter().any(|&(row, _)| row == 11));
+    }
+
+    #[test]
+    fn grid_radius_edges() {
+        let spec = GridSpec {
+            rows: 8,
+            cols: 6,
+            ..default()
+        };
+        for rowcol in [(7, 5), (7, 0), (0, 5), (6, 4)] {
+            let rowcols = spec.get_in_radius_discrete(rowcol, 3);
+            assert!(rowcols.contains(&rowcol));
+            for other in rowcols {
+                assert!(spec.in_bounds(other), "{:?} near {:?}", other, rowcol);
+            }
+        }
+        let empty = GridSpec {
+            rows: 0,
+            cols: 0,
+            ..default()
+        };
+        assert!(empty.get_in_radius_discrete((0, 0), 2).is_empty());
+    }
+
+    #[test]
+    fn grid_to_rowcol_saturates() {
+        let spec = GridSpec {
+            rows: 8,
+            cols: 6,
+            width: 10.,
+            ..default()
+        };
+        assert_eq!(spec.to_rowcol(Vec2::ZERO), (4, 3));
+        assert_eq!(spec.try_to_rowcol(Vec2::ZERO), Some((4, 3)));
+
+        // Positions past either edge land in the nearest edge cell.
+        for position in [Vec2::new(-31., -41.), Vec2::splat(-1e9), Vec2::NEG_INFINITY] {
+            assert_eq!(spec.to_rowcol(position), (0, 0));
+            assert_eq!(spec.try_to_rowcol(position), None);
+        }
+        for position in [Vec2::new(30., 40.), Vec2::splat(1e9), Vec2::INFINITY] {
+            assert_eq!(spec.to_rowcol(position), (7, 5));
+            assert_eq!(spec.try_to_rowcol(position), None);
+        }
+        assert_eq!(spec.to_rowcol(Vec2::new(1e9, -1e9)), (0, 5));
+        assert_eq!(spec.try_to_rowcol(Vec2::new(29.9, -40.)), Some((0, 5)));
+        assert_eq!(spec.try_to_rowcol(Vec2::NAN), None);
+    }
 }
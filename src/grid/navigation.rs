@@ -5,18 +5,27 @@ use bevy::{
     utils::{Entry, HashMap},
 };
 
-use super::{AStarRunner, SparseGrid2};
+use super::{
+    influence::{EnemyInfluenceCost, TeamInfluence},
+    AStarRunner, SparseGrid2,
+};
 
 /// Plugin for flow-based navigation.
 pub struct NavigationPlugin;
 impl Plugin for NavigationPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<NavigationCostEvent>()
+        app.register_type::<NavigationConfig>()
+            .init_resource::<NavigationConfig>()
+            .add_event::<NavigationCostEvent>()
             .add_event::<CreateWaypointEvent>()
             .insert_resource(NavigationGrid2::default())
+            .add_plugins(Grid2Plugin::<TeamInfluence>::default())
             .add_systems(
                 FixedUpdate,
                 (
+                    Grid2::<TeamInfluence>::update
+                        .in_set(SystemStage::PostApply)
+                        .after(GridEntity::update),
                     NavigationGrid2::resize_on_change,
                     NavigationGrid2::create_waypoints
                         .in_set(SystemStage::PostApply)
@@ -27,6 +36,35 @@ impl Plugin for NavigationPlugin {
     }
 }
 
+/// Tuning for the A* heuristic.
+/// A higher `max_heuristic` makes pathfinding greedier and faster, but less optimal.
+#[derive(Resource, Reflect, Clone, Debug)]
+#[reflect(Resource)]
+pub struct NavigationConfig {
+    /// Grid distance below which the heuristic factor stops shrinking.
+    pub min_grid_dist: f32,
+    /// Grid distance at which the heuristic factor reaches `max_heuristic`.
+    pub max_grid_dist: f32,
+    /// Maximum weight of the heuristic relative to path cost.
+    pub max_heuristic: f32,
+    /// If false, only flow along cardinal directions.
+    pub diagonal: bool,
+    /// Grid distance a waypoint can move while reusing its cached flow.
+    /// Farther moves recompute the flow from scratch.
+    pub max_waypoint_shift: f32,
+}
+impl Default for NavigationConfig {
+    fn default() -> Self {
+        Self {
+            min_grid_dist: 1.,
+            max_grid_dist: 30.,
+            max_heuristic: 0.9,
+            diagonal: true,
+            max_waypoint_shift: 4.,
+        }
+    }
+}
+
 /// Communicates cost updates to the visualizer
 #[derive(Event)]
 pub struct NavigationCostEvent {
@@ -38,54 +76,145 @@ pub struct NavigationCostEvent {
 #[derive(Default, DerefMut, Deref, Clone)]
 pub struct SparseFlowGrid2(SparseGrid2<Acceleration>);
 impl SparseFlowGrid2 {
+    /// Create an empty flow grid with the given spec.
+    pub fn new(spec: GridSpec) -> Self {
+        Self(SparseGrid2 { spec, ..default() })
+    }
+
+    /// Returns all computed cells within `radius` of `rowcol`.
+    pub fn computed_in_radius(&self, rowcol: RowCol, radius: f32) -> Vec<RowCol> {
+        self.cells
+            .keys()
+            .copied()
+            .filter(|cell| cell.distance8(rowcol) <= radius)
+            .collect()
+    }
+
+    /// Weight of a cell's flow at `position`, falling off linearly to zero one cell width away.
+    /// Cells without flow have zero weight.
+    pub fn flow_weight(&self, position: Vec2, rowcol: RowCol) -> f32 {
+        if self.get(rowcol).is_none() {
+            return 0.;
+        }
+        let cell_center = self.to_world_position(rowcol);
+        (1. - cell_center.distance(position) / self.spec.width).max(0.)
+    }
+
     /// Compute the weighted acceleration for flow from a single cell.
     pub fn flow_acceleration(&self, position: Vec2, rowcol: RowCol) -> Acceleration {
         if let Some(&acceleration) = self.get(rowcol) {
-            // Weight each neighboring acceleration by width - distance.
-            let weight = {
-                let cell_center = self.to_world_position(rowcol);
-                (self.spec.width * self.spec.width - cell_center.distance_squared(position)).max(0.)
-            };
-            return acceleration * weight;
+            return acceleration * self.flow_weight(position, rowcol);
         }
         Acceleration::ZERO
     }
 
     /// Adds the amount of acceleration needed to bring the velocity to the underlying flow value.
+    /// Blends the flow of the current and neighboring cells so it varies smoothly across cell edges.
     pub fn flow_acceleration5(&self, position: Vec2, config: &ObjectConfig) -> Acceleration {
-        let mut total_acceleration = Acceleration::ZERO;
-
         let rowcol = self.to_rowcol(position);
-
-        total_acceleration += self.flow_acceleration(position, rowcol);
         if self.is_boundary(rowcol) {
             return Acceleration::ZERO;
         }
+
+        let mut total_acceleration = self.flow_acceleration(position, rowcol);
+        let mut total_weight = self.flow_weight(position, rowcol);
         // Add accelerations from neighboring cells.
         for (neighbor_rowcol, _) in self.neighbors8(rowcol) {
             if self.is_boundary(neighbor_rowcol) {
                 continue;
             }
             total_acceleration += self.flow_acceleration(position, neighbor_rowcol);
+            total_weight += self.flow_weight(position, neighbor_rowcol);
+        }
+        if total_weight <= 0. {
+            return Acceleration::ZERO;
+        }
+        Acceleration(config.nav_flow_factor * total_acceleration.0 / total_weight)
+    }
+
+    /// Follow the computed flow cell by cell from source to destination.
+    /// Returns None if the flow stops or loops before reaching the destination.
+    pub fn flow_path(&self, source: RowCol, destination: RowCol) -> Option<Vec<RowCol>> {
+        let max_len = self.spec.rows as usize * self.spec.cols as usize;
+        let mut rowcol = source;
+        let mut path = vec![source];
+        while rowcol != destination {
+            let flow = self.get(rowcol)?;
+            if flow.0 == Vec2::ZERO || path.len() > max_len {
+                return None;
+            }
+            rowcol = (
+                (rowcol.0 as f32 + flow.y) as u16,
+                (rowcol.1 as f32 + flow.x) as u16,
+            );
+            path.push(rowcol);
         }
-        // What do we need to add to velocity to get total_acccel?
-        // v + x = ta
-        // x = ta - v
-        Acceleration(config.nav_flow_factor * total_acceleration.normalize_or_zero())
+        Some(path)
+    }
+
+    /// Compute the cell-by-cell path from source to destination.
+    /// Returns None if the destination is a boundary or unreachable from source.
+    pub fn compute_path(
+        &self,
+        source: RowCol,
+        destination: RowCol,
+        obstacles: &Grid2<Obstacle>,
+        influence: &EnemyInfluenceCost,
+        config: &NavigationConfig,
+    ) -> Option<Vec<RowCol>> {
+        if self.is_boundary(destination) {
+            return None;
+        }
+        let mut runner = AStarRunner::new(destination);
+        runner.a_star_from_source(source, self, obstacles, influence, config);
+
+        // Walk down the min-cost neighbors until the destination is reached.
+        let mut cost = *runner.costs.get(&source)?;
+        let mut rowcol = source;
+        let mut path = vec![source];
+        while rowcol != destination {
+            let (next_rowcol, next_cost) = self
+                .neighbors(rowcol, config.diagonal)
+                .filter(|&(neighbor_rowcol, _)| !obstacles.blocks_diagonal(rowcol, neighbor_rowcol))
+                .filter_map(|(neighbor_rowcol, _)| {
+                    Some((neighbor_rowcol, *runner.costs.get(&neighbor_rowcol)?))
+                })
+                .filter(|&(_, neighbor_cost)| neighbor_cost < cost)
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))?;
+            rowcol = next_rowcol;
+            cost = next_cost;
+            path.push(rowcol);
+        }
+        Some(path)
     }
 }
 
+#[derive(Clone)]
 pub struct NavigationGrid2Entry {
     pub grid: SparseFlowGrid2,
+    /// Caches the costs of all computed cells.
     pub a_star_runner: AStarRunner,
+    /// Team whose enemies are avoided when computing paths.
+    pub team: Team,
 }
 impl NavigationGrid2Entry {
+    /// Create an entry with no flow computed yet.
+    pub fn new(destination: RowCol, team: Team, spec: &GridSpec) -> Self {
+        Self {
+            a_star_runner: AStarRunner::new(destination),
+            grid: SparseFlowGrid2::new(spec.clone()),
+            team,
+        }
+    }
+
     /// Add a waypoint given rowcols.
     pub fn add_waypoint_rowcols(
         &mut self,
         destination: RowCol,
         sources: &[RowCol],
         obstacles: &Grid2<Obstacle>,
+        influence: &EnemyInfluenceCost,
+        config: &NavigationConfig,
         event_writer: &mut EventWriter<NavigationCostEvent>,
     ) {
         // TODO: consider if we should also add neighboring cells for each source.
@@ -96,15 +225,31 @@ impl NavigationGrid2Entry {
         //     }
         // }
 
-        let costs = self
-            .a_star_runner
-            .a_star(sources, destination, &self.grid, obstacles);
+        let costs = self.a_star_runner.a_star(
+            sources,
+            destination,
+            &self.grid,
+            obstacles,
+            influence,
+            config,
+        );
+        self.update_flow(&costs, &costs, obstacles, config, event_writer);
+        self.a_star_runner.costs.extend(costs);
+    }
 
-        // Compute flow direction.
-        for (&rowcol, &cost) in &costs {
+    /// Recompute flow direction for each cell in `rowcols`, pointing to its cheapest neighbor in `costs`.
+    fn update_flow(
+        &mut self,
+        rowcols: &HashMap<RowCol, f32>,
+        costs: &HashMap<RowCol, f32>,
+        obstacles: &Grid2<Obstacle>,
+        config: &NavigationConfig,
+        event_writer: &mut EventWriter<NavigationCostEvent>,
+    ) {
+        for (&rowcol, &cost) in rowcols {
             let mut min_neighbor_rowcol = rowcol;
             let mut min_neighbor_cost = cost;
-            for (neighbor_rowcol, _) in self.grid.neighbors8(rowcol) {
+            for (neighbor_rowcol, _) in self.grid.neighbors(rowcol, config.diagonal) {
                 // Cornering checks for diagonals.
                 if neighbor_rowcol.0 != rowcol.0 && neighbor_rowcol.1 != rowcol.1 {
                     if obstacles[(neighbor_rowcol.0, rowcol.1)] != Obstacle::Empty {
@@ -131,30 +276,91 @@ impl NavigationGrid2Entry {
         }
     }
 
+    /// Move the waypoint from `old_destination` to `new_destination`.
+    /// Cached flow far from the destination still leads into the area around the move, so only
+    /// cells near the new destination are recomputed. Falls back to a full recompute if the
+    /// waypoint moved more than `config.max_waypoint_shift`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_waypoint(
+        &mut self,
+        old_destination: RowCol,
+        new_destination: RowCol,
+        sources: &[RowCol],
+        obstacles: &Grid2<Obstacle>,
+        influence: &EnemyInfluenceCost,
+        config: &NavigationConfig,
+        event_writer: &mut EventWriter<NavigationCostEvent>,
+    ) {
+        let shift = old_destination.distance8(new_destination);
+        if shift > config.max_waypoint_shift {
+            *self = Self::new(new_destination, self.team, &self.grid.spec);
+            self.add_waypoint_rowcols(
+                new_destination,
+                sources,
+                obstacles,
+                influence,
+                config,
+                event_writer,
+            );
+            return;
+        }
+
+        // Cells near either destination may now flow differently, so recompute them.
+        // The old destination is within this radius, so flow kept elsewhere leads into it.
+        let nearby = self
+            .grid
+            .computed_in_radius(new_destination, shift + config.max_waypoint_shift);
+        for rowcol in &nearby {
+            self.grid.cells.remove(rowcol);
+        }
+
+        // Cached costs are relative to the old destination, so only fresh costs are kept.
+        self.a_star_runner = AStarRunner::new(new_destination);
+        let mut new_sources = nearby;
+        new_sources.extend(
+            sources
+                .iter()
+                .filter(|&&source| self.grid.get(source).is_none()),
+        );
+        let costs = self.a_star_runner.a_star(
+            &new_sources,
+            new_destination,
+            &self.grid,
+            obstacles,
+            influence,
+            config,
+        );
+        self.update_flow(&costs, &costs, obstacles, config, event_writer);
+        self.a_star_runner.costs = costs;
+    }
+
     /// Add a waypoint.
     /// Create flows from all points to the waypoint.
     pub fn add_waypoint(
         &mut self,
         event: &CreateWaypointEvent,
         obstacles: &Grid2<Obstacle>,
+        influence: &EnemyInfluenceCost,
+        config: &NavigationConfig,
         event_writer: &mut EventWriter<NavigationCostEvent>,
     ) {
-        let mut sources: Vec<RowCol> = Vec::with_capacity(event.sources.len());
-        for &source in &event.sources {
-            let rowcol = self.grid.spec.to_rowcol(source);
-            for neighbor_rowcol in self.grid.get_in_radius_discrete(rowcol, 2) {
-                sources.push(neighbor_rowcol);
-            }
-        }
-
+        let sources = event.source_rowcols(&self.grid.spec);
         let destination = self.grid.to_rowcol(event.destination);
-        self.add_waypoint_rowcols(destination, &sources, obstacles, event_writer);
+        self.add_waypoint_rowcols(
+            destination,
+            &sources,
+            obstacles,
+            influence,
+            config,
+            event_writer,
+        );
     }
 }
 
-/// Mapping from goal RowCol to a sparse flow grid with accelerations towards that RowCol.
+/// Mapping from team and goal RowCol to a sparse flow grid with accelerations towards that RowCol.
+/// Teams avoid different enemies, so each team gets its own flow to a shared destination.
 #[derive(Default, Resource, DerefMut, Deref)]
-pub struct NavigationGrid2(HashMap<RowCol, NavigationGrid2Entry>);
+pub struct NavigationGrid2(HashMap<(Team, RowCol), NavigationGrid2Entry>);
 
 /// Stores a flow grid per targeted entity.
 impl NavigationGrid2 {
@@ -166,6 +372,7 @@ impl NavigationGrid2 {
                 NavigationGrid2Entry {
                     grid,
                     a_star_runner: _,
+                    team: _,
                 },
             ) in grid.iter_mut()
             {
@@ -174,104 +381,164 @@ impl NavigationGrid2 {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn create_waypoint(
         &mut self,
         event: &CreateWaypointEvent,
         spec: &GridSpec,
         obstacles: &Grid2<Obstacle>,
+        influence: &Grid2<TeamInfluence>,
+        configs: &Configs,
+        config: &NavigationConfig,
         event_writer: &mut EventWriter<NavigationCostEvent>,
     ) {
         let destination = spec.to_rowcol(event.destination);
-        let nav = match self.entry(destination) {
+        let key = (event.team, destination);
+
+        // Reuse the flow of a nearby waypoint for the same team if there is one.
+        if !self.contains_key(&key) {
+            let nearby = self
+                .keys()
+                .filter(|&&(team, _)| team == event.team)
+                .map(|&(_, rowcol)| (rowcol, rowcol.distance8(destination)))
+                .filter(|&(_, distance)| distance <= config.max_waypoint_shift)
+                .min_by(|(_, a), (_, b)| a.total_cmp(b));
+            if let Some((old_destination, _)) = nearby {
+                let mut nav = self[&(event.team, old_destination)].clone();
+                let influence = EnemyInfluenceCost {
+                    influence: Some(influence),
+                    team: nav.team,
+                    weight: configs.enemy_influence_weight,
+                };
+                nav.update_waypoint(
+                    old_destination,
+                    destination,
+                    &event.source_rowcols(spec),
+                    obstacles,
+                    &influence,
+                    config,
+                    event_writer,
+                );
+                self.insert(key, nav);
+                return;
+            }
+        }
+
+        let nav = match self.entry(key) {
             Entry::Occupied(o) => o.into_mut(),
-            Entry::Vacant(v) => v.insert(NavigationGrid2Entry {
-                a_star_runner: AStarRunner::new(destination),
-                grid: SparseFlowGrid2(SparseGrid2 {
-                    spec: spec.clone(),
-                    ..default()
-                }),
-            }),
+            Entry::Vacant(v) => v.insert(NavigationGrid2Entry::new(destination, event.team, spec)),
         };
-        nav.add_waypoint(event, obstacles, event_writer);
+        let influence = EnemyInfluenceCost {
+            influence: Some(influence),
+            team: nav.team,
+            weight: configs.enemy_influence_weight,
+        };
+        nav.add_waypoint(event, obstacles, &influence, config, event_writer);
     }
 
     /// Consumes CreateWaypointEvent events and populates the navigation grid.
+    #[allow(clippy::too_many_arguments)]
     pub fn create_waypoints(
         mut nav_grid: ResMut<Self>,
         mut event_reader: EventReader<CreateWaypointEvent>,
         mut event_writer: EventWriter<NavigationCostEvent>,
         spec: Res<GridSpec>,
         obstacles: Res<Grid2<Obstacle>>,
+        influence: Res<Grid2<TeamInfluence>>,
+        configs: Res<Configs>,
+        config: Res<NavigationConfig>,
     ) {
         for event in event_reader.read() {
-            nav_grid.create_waypoint(event, &spec, &obstacles, &mut event_writer);
+            nav_grid.create_waypoint(
+                event,
+                &spec,
+                &obstacles,
+                &influence,
+                &configs,
+                &config,
+                &mut event_writer,
+            );
         }
     }
 
     /// Also create new ones for moved waypoints.
+    #[allow(clippy::too_many_arguments)]
     pub fn update_waypoints(
-        all_objectives: Query<(Entity, &Objectives), Without<Waypoint>>,
+        all_objectives: Query<(Entity, &Objectives, &Team), Without<Waypoint>>,
         transforms: Query<&Transform>,
         mut grid: ResMut<Self>,
         obstacles: Res<Grid2<Obstacle>>,
+        influence: Res<Grid2<TeamInfluence>>,
         spec: Res<GridSpec>,
+        configs: Res<Configs>,
+        config: Res<NavigationConfig>,
         mut event_writer: EventWriter<NavigationCostEvent>,
     ) {
         // All active destinations to their current sources.
-        let mut destinations: HashMap<RowCol, Vec<RowCol>> = HashMap::new();
-        for (entity, objectives) in all_objectives.iter() {
-            if let Some(followed_entity) = objectives.last().get_followed_entity() {
-                let source_rowcol = if let Ok(source_transform) = transforms.get(entity) {
-                    spec.to_rowcol(source_transform.translation.xy())
+        // Queued destinations are sourced from the destination before them.
+        let mut destinations: HashMap<(Team, RowCol), Vec<RowCol>> = HashMap::new();
+        for (entity, objectives, &team) in all_objectives.iter() {
+            let Ok(source_transform) = transforms.get(entity) else {
+                continue;
+            };
+            let mut source_rowcol = spec.to_rowcol(source_transform.translation.xy());
+            for objective in objectives.navigation_targets() {
+                let destination = if let Some(followed_entity) = objective.get_followed_entity() {
+                    transforms
+                        .get(followed_entity)
+                        .ok()
+                        .map(|transform| transform.translation.xy())
                 } else {
+                    objective.get_target_position()
+                };
+                let Some(destination) = destination else {
                     continue;
                 };
-                if let Ok(destination_transform) = transforms.get(followed_entity) {
-                    let destination_rowcol = spec.to_rowcol(destination_transform.translation.xy());
-                    let value = match destinations.entry(destination_rowcol) {
-                        Entry::Occupied(o) => o.into_mut(),
-                        Entry::Vacant(v) => v.insert(Vec::with_capacity(1)),
-                    };
-                    value.push(source_rowcol)
-                }
+                let destination_rowcol = spec.to_rowcol(destination);
+                destinations
+                    .entry((team, destination_rowcol))
+                    .or_insert_with(|| Vec::with_capacity(1))
+                    .push(source_rowcol);
+                source_rowcol = destination_rowcol;
             }
         }
 
         // Populate any cells that haven't been computed yet.
-        for (&destination, sources) in &destinations {
-            if let Some(nav) = grid.get_mut(&destination) {
+        for (&(team, destination), sources) in &destinations {
+            if let Some(nav) = grid.get_mut(&(team, destination)) {
+                let influence = EnemyInfluenceCost {
+                    influence: Some(&influence),
+                    team: nav.team,
+                    weight: configs.enemy_influence_weight,
+                };
                 for &source in sources {
                     if nav.grid.get(source).is_none() {
                         nav.add_waypoint_rowcols(
                             destination,
                             &[source],
                             &obstacles,
+                            &influence,
+                            &config,
                             &mut event_writer,
                         )
                     }
                 }
             } else {
                 grid.insert(
-                    destination,
-                    NavigationGrid2Entry {
-                        a_star_runner: AStarRunner::new(destination),
-                        grid: SparseFlowGrid2(SparseGrid2 {
-                            spec: spec.clone(),
-                            ..default()
-                        }),
-                    },
+                    (team, destination),
+                    NavigationGrid2Entry::new(destination, team, &spec),
                 );
             }
         }
 
         // Remove old cells where there is no objective leading to that destination.
-        let rowcols_to_remove: Vec<RowCol> = grid
+        let keys_to_remove: Vec<(Team, RowCol)> = grid
             .keys()
-            .filter(|&destination| !destinations.contains_key(destination))
+            .filter(|&key| !destinations.contains_key(key))
             .copied()
             .collect();
-        for rowcol in rowcols_to_remove {
-            grid.remove(&rowcol);
+        for key in keys_to_remove {
+            grid.remove(&key);
         }
     }
 }
@@ -281,4 +548,299 @@ impl NavigationGrid2 {
 pub struct CreateWaypointEvent {
     pub destination: Vec2,
     pub sources: Vec<Vec2>,
+    pub team: Team,
+}
+impl CreateWaypointEvent {
+    /// Cells around each source that should flow towards the destination.
+    pub fn source_rowcols(&self, spec: &GridSpec) -> Vec<RowCol> {
+        let mut sources: Vec<RowCol> = Vec::with_capacity(self.sources.len());
+        for &source in &self.sources {
+            let rowcol = spec.to_rowcol(source);
+            for neighbor_rowcol in spec.get_in_radius_discrete(rowcol, 2) {
+                sources.push(neighbor_rowcol);
+            }
+        }
+        sources
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{ecs::system::RunSystemOnce, prelude::*};
+
+    use crate::{
+        grid::{EnemyInfluenceCost, TeamInfluence},
+        prelude::*,
+    };
+
+    use super::{
+        CreateWaypointEvent, NavigationConfig, NavigationCostEvent, NavigationGrid2,
+        NavigationGrid2Entry, SparseFlowGrid2,
+    };
+
+    #[test]
+    fn test_compute_path() {
+        let spec = GridSpec {
+            rows: 10,
+            cols: 10,
+            width: 10.0,
+            visualize: false,
+            visualize_navigation: false,
+            ..default()
+        };
+        let grid = SparseFlowGrid2::new(spec.clone());
+        let mut obstacles = Grid2::<Obstacle>::default();
+        obstacles.resize_with(spec);
+        // Wall across column 5 with a gap at row 8.
+        for row in 1..8 {
+            obstacles[(row, 5)] = Obstacle::Full;
+        }
+        let influence = EnemyInfluenceCost::default();
+        let config = NavigationConfig::default();
+
+        let path = grid
+            .compute_path((2, 2), (2, 7), &obstacles, &influence, &config)
+            .unwrap();
+        assert_eq!(path.first(), Some(&(2, 2)));
+        assert_eq!(path.last(), Some(&(2, 7)));
+        assert!(path.contains(&(8, 5)), "{:?}", path);
+        for pair in path.windows(2) {
+            assert_eq!(pair[0].distance8(pair[1]).round(), 1.);
+        }
+
+        // Cardinal-only paths never step diagonally.
+        let cardinal = NavigationConfig {
+            diagonal: false,
+            ..default()
+        };
+        let path = grid
+            .compute_path((2, 2), (2, 7), &obstacles, &influence, &cardinal)
+            .unwrap();
+        assert_eq!(path.last(), Some(&(2, 7)));
+        for pair in path.windows(2) {
+            let ((row, col), (next_row, next_col)) = (pair[0], pair[1]);
+            assert!(row == next_row || col == next_col, "{:?}", path);
+        }
+
+        // Boundary destination.
+        assert_eq!(
+            grid.compute_path((2, 2), (0, 7), &obstacles, &influence, &config),
+            None
+        );
+
+        // Unreachable destination.
+        obstacles[(8, 5)] = Obstacle::Full;
+        assert_eq!(
+            grid.compute_path((2, 2), (2, 7), &obstacles, &influence, &config),
+            None
+        );
+    }
+
+    #[test]
+    fn test_flow_continuous() {
+        let spec = GridSpec {
+            rows: 10,
+            cols: 10,
+            width: 10.0,
+            visualize: false,
+            visualize_navigation: false,
+            ..default()
+        };
+        let mut grid = SparseFlowGrid2::new(spec);
+        for row in 1..9 {
+            for col in 1..9 {
+                let flow = if col < 5 { Vec2::X } else { Vec2::Y };
+                grid.cells.insert((row, col), Acceleration(flow));
+            }
+        }
+        let config = ObjectConfig::default();
+
+        // Step across the edge between columns 4 and 5.
+        let edge = grid.to_world_position((4, 4)) + Vec2::new(5., 2.);
+        let left = edge - Vec2::X * 1e-3;
+        let right = edge + Vec2::X * 1e-3;
+        assert_ne!(grid.to_rowcol(left), grid.to_rowcol(right));
+        let left = grid.flow_acceleration5(left, &config);
+        let right = grid.flow_acceleration5(right, &config);
+        assert!(left.distance(right.0) < 1e-2, "{:?} {:?}", left, right);
+
+        // Blend weights sum to one, so uniform flow is unchanged.
+        let center = grid.to_world_position((2, 2)) + Vec2::new(3., -1.);
+        let acceleration = grid.flow_acceleration5(center, &config);
+        assert!(acceleration.distance(Vec2::X * config.nav_flow_factor) < 1e-5);
+    }
+
+    #[test]
+    fn test_cardinal_flow() {
+        let spec = GridSpec {
+            rows: 20,
+            cols: 20,
+            width: 10.0,
+            visualize: false,
+            visualize_navigation: false,
+            ..default()
+        };
+        let mut world = World::new();
+        world.init_resource::<Events<NavigationCostEvent>>();
+        world.run_system_once(move |mut event_writer: EventWriter<NavigationCostEvent>| {
+            let mut obstacles = Grid2::<Obstacle>::default();
+            obstacles.resize_with(spec.clone());
+            let influence = EnemyInfluenceCost::default();
+            let config = NavigationConfig {
+                diagonal: false,
+                ..NavigationConfig::default()
+            };
+            let destination = (10, 15);
+            let mut nav = NavigationGrid2Entry::new(destination, Team::Blue, &spec);
+            nav.add_waypoint_rowcols(
+                destination,
+                &[(10, 3), (4, 8)],
+                &obstacles,
+                &influence,
+                &config,
+                &mut event_writer,
+            );
+
+            // In line with the destination, flow points straight at it.
+            assert_eq!(nav.grid.get((10, 3)).unwrap().0, Vec2::X);
+            // Flow never points diagonally.
+            for acceleration in nav.grid.cells.values() {
+                assert!(
+                    acceleration.x == 0. || acceleration.y == 0.,
+                    "{:?}",
+                    acceleration
+                );
+            }
+        });
+    }
+
+    #[test]
+    fn test_update_waypoint() {
+        let spec = GridSpec {
+            rows: 20,
+            cols: 20,
+            width: 10.0,
+            visualize: false,
+            visualize_navigation: false,
+            ..default()
+        };
+        let mut world = World::new();
+        world.init_resource::<Events<NavigationCostEvent>>();
+        let mut obstacles = Grid2::<Obstacle>::default();
+        obstacles.resize_with(spec.clone());
+        let config = NavigationConfig::default();
+
+        let nav = {
+            let (spec, obstacles, config) = (spec.clone(), obstacles.clone(), config.clone());
+            world.run_system_once(move |mut event_writer: EventWriter<NavigationCostEvent>| {
+                let influence = EnemyInfluenceCost::default();
+                let mut nav = NavigationGrid2Entry::new((10, 10), Team::Blue, &spec);
+                nav.add_waypoint_rowcols(
+                    (10, 10),
+                    &[(2, 2), (17, 17), (2, 17)],
+                    &obstacles,
+                    &influence,
+                    &config,
+                    &mut event_writer,
+                );
+                nav
+            })
+        };
+        let full_count = world.resource::<Events<NavigationCostEvent>>().len();
+        let far_flow = nav.grid.get((2, 2)).copied().unwrap();
+        world.resource_mut::<Events<NavigationCostEvent>>().clear();
+
+        let updated = {
+            let (obstacles, config) = (obstacles.clone(), config.clone());
+            let mut nav = Some(nav);
+            world.run_system_once(move |mut event_writer: EventWriter<NavigationCostEvent>| {
+                let influence = EnemyInfluenceCost::default();
+                let mut nav = nav.take().unwrap();
+                nav.update_waypoint(
+                    (10, 10),
+                    (10, 11),
+                    &[(2, 2)],
+                    &obstacles,
+                    &influence,
+                    &config,
+                    &mut event_writer,
+                );
+                nav
+            })
+        };
+        // Only cells near the moved destination are recomputed.
+        let update_count = world.resource::<Events<NavigationCostEvent>>().len();
+        assert!(update_count < full_count, "{} {}", update_count, full_count);
+        assert_eq!(updated.a_star_runner.destination, (10, 11));
+        // Costs towards the old destination aren't kept.
+        assert!(!updated.a_star_runner.costs.contains_key(&(2, 2)));
+        assert_eq!(updated.a_star_runner.costs[&(10, 11)], 0.);
+        assert_eq!(updated.grid.get((10, 11)).unwrap().0, Vec2::ZERO);
+        assert_eq!(updated.grid.get((10, 10)).unwrap().0, Vec2::X);
+        assert_eq!(updated.grid.get((2, 2)).copied().unwrap(), far_flow);
+
+        // Moving far recomputes from scratch.
+        let mut updated = Some(updated);
+        world.run_system_once(move |mut event_writer: EventWriter<NavigationCostEvent>| {
+            let influence = EnemyInfluenceCost::default();
+            let mut nav = updated.take().unwrap();
+            nav.update_waypoint(
+                (10, 11),
+                (3, 17),
+                &[(17, 2)],
+                &obstacles,
+                &influence,
+                &config,
+                &mut event_writer,
+            );
+            assert!(nav.grid.get((17, 17)).is_none());
+            assert!(nav.grid.get((17, 2)).is_some());
+            assert_eq!(nav.a_star_runner.costs[&(3, 17)], 0.);
+        });
+    }
+
+    #[test]
+    fn test_team_waypoints() {
+        let spec = GridSpec {
+            rows: 20,
+            cols: 20,
+            width: 10.0,
+            visualize: false,
+            visualize_navigation: false,
+            ..default()
+        };
+        let mut world = World::new();
+        world.init_resource::<Events<NavigationCostEvent>>();
+        let destination = spec.to_rowcol(Vec2::ZERO);
+        let nav =
+            world.run_system_once(move |mut event_writer: EventWriter<NavigationCostEvent>| {
+                let mut obstacles = Grid2::<Obstacle>::default();
+                obstacles.resize_with(spec.clone());
+                let mut influence = Grid2::<TeamInfluence>::default();
+                influence.resize_with(spec.clone());
+                let mut nav = NavigationGrid2::default();
+                for team in [Team::Blue, Team::Red] {
+                    nav.create_waypoint(
+                        &CreateWaypointEvent {
+                            destination: Vec2::ZERO,
+                            sources: vec![Vec2::new(-50., -50.)],
+                            team,
+                        },
+                        &spec,
+                        &obstacles,
+                        &influence,
+                        &Configs::default(),
+                        &NavigationConfig::default(),
+                        &mut event_writer,
+                    );
+                }
+                nav
+            });
+
+        // Each team gets its own flow to the shared destination.
+        assert_eq!(nav.len(), 2);
+        for team in [Team::Blue, Team::Red] {
+            assert_eq!(nav[&(team, destination)].team, team);
+        }
+    }
 }
@@ -12,7 +12,7 @@ use super::{
 
 /// Plugin for obstacles.
 /// Obstacles are implemented as a hacky force field in the center of each cell they are present in.
-/// TODO: prevent glitchy movement when objects try to move past obstacles.
+/// Combined accelerations are clamped by `ObjectConfig::max_acceleration` to limit glitchy movement.
 pub struct ObstaclesPlugin;
 impl Plugin for ObstaclesPlugin {
     fn build(&self, app: &mut App) {
@@ -23,9 +23,11 @@ impl Plugin for ObstaclesPlugin {
             .register_type::<Vec<(RowCol, Obstacle)>>()
             .register_type::<(RowCol, Obstacle)>()
             .register_type::<RowCol>()
+            .init_resource::<ObstacleBrush>()
             .add_systems(
                 FixedUpdate,
                 (
+                    ObstaclesSpec::paint.before(Grid2::<Obstacle>::update),
                     Grid2::<Obstacle>::update.after(Grid2::<Obstacle>::resize_on_change),
                     ObstaclesShaderMaterial::update.after(Grid2::<Obstacle>::resize_on_change),
                 ),
@@ -45,11 +47,67 @@ pub enum Obstacle {
     DownLeft = 4,
     Full = 5,
 }
+impl Obstacle {
+    /// Returns the next non-empty obstacle, wrapping around.
+    pub fn next(self) -> Self {
+        match self {
+            Self::Empty => Self::UpRight,
+            Self::UpRight => Self::UpLeft,
+            Self::UpLeft => Self::DownRight,
+            Self::DownRight => Self::DownLeft,
+            Self::DownLeft => Self::Full,
+            Self::Full => Self::UpRight,
+        }
+    }
+}
 
 /// Grid of obstacle data.
 #[derive(Resource, Default, Deref, DerefMut, Reflect)]
 #[reflect(Resource)]
 pub struct ObstaclesSpec(pub Vec<(RowCol, Obstacle)>);
+impl ObstaclesSpec {
+    /// Toggle obstacles under the cursor in editor mode.
+    pub fn paint(
+        mut spec: ResMut<Self>,
+        mut brush: ResMut<ObstacleBrush>,
+        grid_spec: Res<GridSpec>,
+        mut control_events: EventReader<ControlEvent>,
+    ) {
+        for control in control_events.read() {
+            if control.is_pressed(ControlAction::CycleObstacle) {
+                brush.obstacle = brush.obstacle.next();
+                info!("Obstacle brush: {:?}", brush.obstacle);
+            }
+            if !control.is_pressed(ControlAction::PaintObstacle) {
+                continue;
+            }
+            let Some(rowcol) = grid_spec.try_to_rowcol(control.position) else {
+                continue;
+            };
+            match spec.iter().position(|&(other, _)| other == rowcol) {
+                // Painting over the same obstacle clears it.
+                Some(i) if spec[i].1 == brush.obstacle => {
+                    spec.swap_remove(i);
+                }
+                Some(i) => spec[i].1 = brush.obstacle,
+                None => spec.push((rowcol, brush.obstacle)),
+            }
+        }
+    }
+}
+
+/// Obstacle placed when painting in editor mode.
+#[derive(Resource, Debug)]
+pub struct ObstacleBrush {
+    pub obstacle: Obstacle,
+}
+impl Default for ObstacleBrush {
+    fn default() -> Self {
+        Self {
+            obstacle: Obstacle::Full,
+        }
+    }
+}
 
 impl Grid2<Obstacle> {
     pub fn update(mut grid: ResMut<Self>, spec: Res<ObstaclesSpec>) {
@@ -63,6 +121,13 @@ impl Grid2<Obstacle> {
         }
     }
 
+    /// Returns true if no full obstacle lies strictly between the two cells.
+    pub fn line_of_sight(&self, from: RowCol, to: RowCol) -> bool {
+        Self::line(from, to)
+            .filter(|&rowcol| rowcol != from && rowcol != to)
+            .all(|rowcol| self.get(rowcol) != Some(&Obstacle::Full))
+    }
+
     fn obstacle_acceleration(
         &self,
         position: Vec2,
@@ -101,9 +166,44 @@ impl Grid2<Obstacle> {
         acceleration
     }
 
+    /// Number of steps along the velocity checked for obstacles ahead.
+    pub const LOOK_AHEAD_STEPS: usize = 2;
+
+    /// If the object will enter an obstacle within a few steps, steer it tangentially along
+    /// the obstacle's face so it slides past instead of sticking to the wall.
+    pub fn look_ahead_acceleration(&self, position: Vec2, velocity: Velocity) -> Acceleration {
+        let Some(velocity_direction) = velocity.try_normalize() else {
+            return Acceleration::ZERO;
+        };
+        let rowcol = self.to_rowcol(position);
+        for step in 1..=Self::LOOK_AHEAD_STEPS {
+            let ahead = position + velocity.0 * step as f32;
+            let ahead_rowcol = self.to_rowcol(ahead);
+            let blocked = self
+                .get(ahead_rowcol)
+                .is_some_and(|&obstacle| obstacle != Obstacle::Empty);
+            if ahead_rowcol == rowcol || !blocked {
+                continue;
+            }
+            // Use the dominant axis between the obstacle and the object as the face normal.
+            let delta = position - self.to_world_position(ahead_rowcol);
+            let normal = if delta.x.abs() > delta.y.abs() {
+                Vec2::new(delta.x.signum(), 0.)
+            } else {
+                Vec2::new(0., delta.y.signum())
+            };
+            let tangent = (velocity.0 - normal * velocity.dot(normal))
+                .try_normalize()
+                .unwrap_or(normal.perp());
+            return Acceleration((tangent - velocity_direction) / step as f32);
+        }
+        Acceleration::ZERO
+    }
+
     /// Compute acceleration due to neighboring obstacles.
     /// For each neighboring obstacle, if the object is moving towards the obstacle
     /// we apply a force away from the obstacle.
+    /// Obstacles ahead of the object deflect it along their face.
     pub fn obstacles_acceleration(&self, position: Vec2, velocity: Velocity) -> Acceleration {
         let (row, col) = self.to_rowcol(position);
         if self.is_boundary((row, col)) {
@@ -121,7 +221,7 @@ impl Grid2<Obstacle> {
         //         self.obstacle_acceleration(position, velocity, obstacle_rowcol, (dr, dc))
         //             * 2f32.sqrt();
         // }
-        acceleration
+        acceleration + self.look_ahead_acceleration(position, velocity)
     }
 }
 
@@ -183,3 +283,91 @@ impl Material2d for ObstaclesShaderMaterial {
         "shaders/obstacles.wgsl".into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::{ecs::system::RunSystemOnce, input::ButtonState, prelude::*};
+
+    use crate::prelude::*;
+
+    use super::{ObstacleBrush, ObstaclesSpec};
+
+    #[test]
+    fn test_paint() {
+        let spec = GridSpec {
+            rows: 10,
+            cols: 10,
+            width: 10.0,
+            visualize: false,
+            visualize_navigation: false,
+            ..default()
+        };
+        let mut world = World::new();
+        world.insert_resource(spec);
+        world.init_resource::<ObstaclesSpec>();
+        world.init_resource::<ObstacleBrush>();
+        world.init_resource::<Events<ControlEvent>>();
+        let send = |world: &mut World, action: ControlAction| {
+            world.send_event(ControlEvent {
+                action,
+                state: ButtonState::Pressed,
+                position: Vec2::new(2., 2.),
+                entity: None,
+            });
+            world.run_system_once(ObstaclesSpec::paint);
+            world.resource_mut::<Events<ControlEvent>>().clear();
+        };
+
+        send(&mut world, ControlAction::PaintObstacle);
+        assert_eq!(
+            world.resource::<ObstaclesSpec>().0,
+            vec![((5, 5), Obstacle::Full)]
+        );
+
+        // Cycling the brush replaces the obstacle with the next orientation.
+        send(&mut world, ControlAction::CycleObstacle);
+        send(&mut world, ControlAction::PaintObstacle);
+        assert_eq!(
+            world.resource::<ObstaclesSpec>().0,
+            vec![((5, 5), Obstacle::UpRight)]
+        );
+
+        // Painting the same obstacle again removes it.
+        send(&mut world, ControlAction::PaintObstacle);
+        assert!(world.resource::<ObstaclesSpec>().is_empty());
+    }
+
+    #[test]
+    fn test_look_ahead_deflection() {
+        let spec = GridSpec {
+            rows: 10,
+            cols: 10,
+            width: 10.0,
+            ..default()
+        };
+        let mut obstacles = Grid2::<Obstacle>::default();
+        obstacles.resize_with(spec);
+        obstacles[(5, 6)] = Obstacle::Full;
+
+        // Moving right and slightly up towards the wall.
+        let position = Vec2::new(2., 1.);
+        let velocity = Velocity(Vec2::new(8., 1.));
+        let acceleration = obstacles.look_ahead_acceleration(position, velocity);
+        assert!(acceleration.x < 0.);
+        assert!(acceleration.y > 0.);
+
+        // The deflected velocity slides along the wall face.
+        let deflected = velocity.normalize() + acceleration.0;
+        assert!(deflected.abs_diff_eq(Vec2::Y, 1e-5));
+
+        // Head on collisions still deflect to one side.
+        let acceleration =
+            obstacles.look_ahead_acceleration(Vec2::new(5., 5.), Velocity(Vec2::X * 8.));
+        assert!(acceleration.y.abs() > 0.);
+
+        // Moving away from the wall has no effect.
+        let acceleration =
+            obstacles.look_ahead_acceleration(position, Velocity(Vec2::new(-8., 1.)));
+        assert_eq!(acceleration, Acceleration::ZERO);
+    }
+}
@@ -65,8 +65,11 @@ pub trait ShaderPlaneMaterial: Material2d + Default {
         let material = shader_assets.get_mut(&assets.shader_material).unwrap();
         material.resize(&spec);
 
+        // Without a window, e.g. when running headless, only the material is kept in sync.
+        let Ok(window) = window.get_single() else {
+            return;
+        };
         let plane_entity = {
-            let window = window.single();
             let mut plane =
                 commands.spawn(ShaderPlane::<Self>::default().bundle(&spec, window, &assets));
             if Self::raycast_target() != RaycastTarget::None {
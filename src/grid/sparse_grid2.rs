@@ -5,7 +5,7 @@ use std::ops::{Index, IndexMut};
 use super::GridSpec;
 
 /// 2D Grid containing arbitrary data.
-#[derive(Clone, Default, Debug, Deref, DerefMut)]
+#[derive(Clone, Default, Debug, Deref, DerefMut, Resource)]
 pub struct SparseGrid2<T: Sized + Default + Clone> {
     #[deref]
     pub spec: GridSpec,
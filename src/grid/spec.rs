@@ -76,6 +76,16 @@ pub struct GridSize {
     pub cols: u32,
 }
 
+/// Storage used for the entity grid.
+#[derive(Reflect, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GridBackend {
+    /// One entity set per cell. Fastest for dense scenes.
+    #[default]
+    Dense,
+    /// Entity sets only for occupied cells. Uses less memory for sparse scenes.
+    Sparse,
+}
+
 /// Specification describing how large the grid is.
 #[derive(Resource, Reflect, Clone, Debug)]
 #[reflect(Resource)]
@@ -85,6 +95,9 @@ pub struct GridSpec {
     pub width: f32,
     pub visualize: bool,
     pub visualize_navigation: bool,
+    pub backend: GridBackend,
+    /// Entities reserved per cell to avoid rehashing as units enter.
+    pub cell_capacity: u16,
 }
 impl Default for GridSpec {
     fn default() -> Self {
@@ -94,10 +107,16 @@ impl Default for GridSpec {
             width: 10.0,
             visualize: true,
             visualize_navigation: false,
+            backend: GridBackend::Dense,
+            cell_capacity: 4,
         }
     }
 }
 impl GridSpec {
+    /// Returns an empty entity set with room for `cell_capacity` entities.
+    pub fn new_entity_set(&self) -> EntitySet {
+        EntitySet::with_capacity_and_hasher(self.cell_capacity as usize, default())
+    }
     pub fn discretize(&self, value: f32) -> u16 {
         (value / self.width) as u16
     }
@@ -108,9 +127,23 @@ impl GridSpec {
     }
 
     /// Returns (row, col) from a position in world space.
+    /// Positions outside the grid saturate to the nearest edge cell.
     pub fn to_rowcol(&self, mut position: Vec2) -> RowCol {
         position += self.offset();
-        (self.discretize(position.y), self.discretize(position.x))
+        (
+            self.discretize(position.y).min(self.rows.saturating_sub(1)),
+            self.discretize(position.x).min(self.cols.saturating_sub(1)),
+        )
+    }
+
+    /// Returns (row, col) from a position in world space, or None if it's outside the grid.
+    pub fn try_to_rowcol(&self, mut position: Vec2) -> Option<RowCol> {
+        position += self.offset();
+        if !(position.x >= 0. && position.y >= 0.) {
+            return None;
+        }
+        let rowcol = (self.discretize(position.y), self.discretize(position.x));
+        self.in_bounds(rowcol).then_some(rowcol)
     }
 
     /// Returns the world position of the cell coordinate.
@@ -192,15 +225,16 @@ impl GridSpec {
         ]
     }
 
-    /// Returns the 4 neighboring cells to the given cell rowcol.
-    pub fn neighbors4(&self, rowcol: RowCol) -> [RowCol; 4] {
-        let (row, col) = rowcol;
-        [
-            (row + 1, col), // Up
-            (row, col + 1), // Right
-            (row - 1, col), // Down
-            (row, col - 1), // Left
-        ]
+    /// Indices of the up, right, down, and left neighbors in `neighbors8`.
+    const CARDINAL_NEIGHBORS: [usize; 4] = [1, 3, 5, 7];
+
+    /// Returns the 8 neighbors if `diagonal` is set, otherwise only the 4 cardinal neighbors.
+    pub fn neighbors(&self, rowcol: RowCol, diagonal: bool) -> impl Iterator<Item = (RowCol, f32)> {
+        self.neighbors8(rowcol)
+            .into_iter()
+            .enumerate()
+            .filter(move |(i, _)| diagonal || Self::CARDINAL_NEIGHBORS.contains(i))
+            .map(|(_, neighbor)| neighbor)
     }
 
     /// Get all cells in a given bounding box.
@@ -224,19 +258,33 @@ impl GridSpec {
 
     /// Get in radius, with discrete cell position inputs.
     pub fn get_in_radius_discrete(&self, rowcol: RowCol, radius: u16) -> Vec<RowCol> {
-        let (row, col) = rowcol;
-
         let mut results = Vec::default();
-        for other_row in self.cell_range(row, radius) {
-            for other_col in self.cell_range(col, radius) {
+        self.for_each_in_radius_discrete(rowcol, radius, |other_rowcol| results.push(other_rowcol));
+        results
+    }
+
+    /// Calls `f` for each cell in radius without allocating.
+    pub fn for_each_in_radius(&self, position: Vec2, radius: f32, f: impl FnMut(RowCol)) {
+        self.for_each_in_radius_discrete(self.to_rowcol(position), self.discretize(radius) + 1, f)
+    }
+
+    /// Calls `f` for each cell in radius without allocating, with discrete cell position inputs.
+    pub fn for_each_in_radius_discrete(
+        &self,
+        rowcol: RowCol,
+        radius: u16,
+        mut f: impl FnMut(RowCol),
+    ) {
+        let (row, col) = rowcol;
+        for other_row in Self::cell_range(row, radius, self.rows) {
+            for other_col in Self::cell_range(col, radius, self.cols) {
                 let other_rowcol = (other_row, other_col);
                 if !Self::in_radius(rowcol, other_rowcol, radius) {
                     continue;
                 }
-                results.push(other_rowcol)
+                f(other_rowcol)
             }
         }
-        results
     }
 
     /// Returns true if a cell is within the given radius to another cell.
@@ -249,10 +297,15 @@ impl GridSpec {
     }
 
     /// Returns a range starting at `center - radius` ending at `center + radius`.
-    fn cell_range(&self, center: u16, radius: u16) -> RangeInclusive<u16> {
+    /// `limit` is the number of rows or columns in the dimension being ranged over,
+    /// so the range never includes an index past the last one.
+    fn cell_range(center: u16, radius: u16, limit: u16) -> RangeInclusive<u16> {
+        let Some(last) = limit.checked_sub(1) else {
+            return RangeInclusive::new(1, 0);
+        };
         let (min, max) = (
             (center as i16 - radius as i16).max(0) as u16,
-            (center + radius).min(self.rows),
+            (center + radius).min(last),
         );
         min..=max
     }
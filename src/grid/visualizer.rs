@@ -18,11 +18,16 @@ pub struct GridVisualizerPlugin;
 impl Plugin for GridVisualizerPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(ShaderPlanePlugin::<GridVisualizerShaderMaterial>::default())
+            .register_type::<GridVisualConfig>()
+            .init_resource::<GridVisualConfig>()
             .add_systems(
                 FixedUpdate,
-                (GridVisualizerShaderMaterial::update
-                    .after(GridEntity::update)
-                    .run_if(should_visualize_grid),),
+                (
+                    GridVisualizerShaderMaterial::update
+                        .after(GridEntity::update)
+                        .run_if(should_visualize_grid),
+                    GridVisualizerShaderMaterial::update_visuals,
+                ),
             );
     }
 }
@@ -32,6 +37,26 @@ fn should_visualize_grid(spec: Res<GridSpec>) -> bool {
     spec.visualize
 }
 
+/// Colors for the grid background.
+#[derive(Resource, Reflect, Clone, Debug)]
+#[reflect(Resource)]
+pub struct GridVisualConfig {
+    pub color: Color,
+    // Opacity multiplier for cells without entities.
+    pub empty_opacity: f32,
+    // Opacity multiplier for cells with entities.
+    pub occupied_opacity: f32,
+}
+impl Default for GridVisualConfig {
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE,
+            empty_opacity: 1.,
+            occupied_opacity: 1.,
+        }
+    }
+}
+
 /// Parameters passed to grid background shader.
 #[derive(Asset, TypePath, AsBindGroup, Clone)]
 pub struct GridVisualizerShaderMaterial {
@@ -41,14 +66,20 @@ pub struct GridVisualizerShaderMaterial {
     size: GridSize,
     #[storage(2, read_only)]
     grid: Vec<u32>,
+    /// Opacity of empty (x) and occupied (y) cells.
+    #[uniform(3)]
+    opacity: Vec2,
 }
 impl Default for GridVisualizerShaderMaterial {
     fn default() -> Self {
-        Self {
-            color: Color::WHITE,
+        let mut material = Self {
+            color: Color::default(),
             size: GridSize::default(),
             grid: Vec::default(),
-        }
+            opacity: Vec2::ZERO,
+        };
+        material.apply_visuals(&GridVisualConfig::default());
+        material
     }
 }
 impl ShaderPlaneMaterial for GridVisualizerShaderMaterial {
@@ -68,6 +99,26 @@ impl ShaderPlaneMaterial for GridVisualizerShaderMaterial {
     }
 }
 impl GridVisualizerShaderMaterial {
+    /// Copy colors from the config into the shader uniforms.
+    pub fn apply_visuals(&mut self, config: &GridVisualConfig) {
+        self.color = config.color;
+        self.opacity = Vec2::new(config.empty_opacity, config.occupied_opacity);
+    }
+
+    /// Update the material when the visual config changes.
+    pub fn update_visuals(
+        config: Res<GridVisualConfig>,
+        assets: Res<ShaderPlaneAssets<Self>>,
+        mut shader_assets: ResMut<Assets<Self>>,
+    ) {
+        if !config.is_changed() {
+            return;
+        }
+        if let Some(material) = shader_assets.get_mut(&assets.shader_material) {
+            material.apply_visuals(&config);
+        }
+    }
+
     /// Update the grid shader material.
     pub fn update(
         grid_spec: Res<GridSpec>,
@@ -99,3 +150,46 @@ impl Material2d for GridVisualizerShaderMaterial {
         "shaders/grid_background.wgsl".into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use crate::grid::ShaderPlaneAssets;
+
+    use super::{GridVisualConfig, GridVisualizerShaderMaterial};
+
+    #[test]
+    fn test_update_visuals() {
+        let mut world = World::new();
+        world.init_resource::<Assets<Mesh>>();
+        world.init_resource::<Assets<GridVisualizerShaderMaterial>>();
+        world.init_resource::<ShaderPlaneAssets<GridVisualizerShaderMaterial>>();
+        world.init_resource::<GridVisualConfig>();
+        let mut schedule = Schedule::default();
+        schedule.add_systems(GridVisualizerShaderMaterial::update_visuals);
+        let material = |world: &World| {
+            let handle = &world
+                .resource::<ShaderPlaneAssets<GridVisualizerShaderMaterial>>()
+                .shader_material;
+            let material = world
+                .resource::<Assets<GridVisualizerShaderMaterial>>()
+                .get(handle)
+                .unwrap();
+            (material.color, material.opacity)
+        };
+
+        // Defaults match the original white grid.
+        schedule.run(&mut world);
+        assert_eq!(material(&world), (Color::WHITE, Vec2::ONE));
+
+        // Config changes apply without resizing the grid.
+        *world.resource_mut::<GridVisualConfig>() = GridVisualConfig {
+            color: Color::TEAL,
+            empty_opacity: 0.5,
+            occupied_opacity: 0.8,
+        };
+        schedule.run(&mut world);
+        assert_eq!(material(&world), (Color::TEAL, Vec2::new(0.5, 0.8)));
+    }
+}
@@ -4,7 +4,7 @@ use bevy::input::ButtonState;
 use bevy::{prelude::*, utils::HashMap};
 
 use std::hash::Hash;
-/// Inputs are configured via an input map (TODO).
+/// Inputs are configured via an input map (`InputConfig`), which can be overridden in the scene.
 /// Mouse events are translated into InputActions.
 /// Rays are cast to determine the target of the InputAction.
 /// How can we determine what the target was?
@@ -15,6 +15,7 @@ use std::{
 
 use crate::prelude::*;
 use crate::raycast::RaycastCommands;
+use crate::replay::ReplayPlayback;
 
 /// Plugin for input action events.
 pub struct InputActionPlugin;
@@ -26,14 +27,16 @@ impl Plugin for InputActionPlugin {
             .register_type::<HashMap<MouseButton, InputAction>>()
             .register_type::<HashMap<KeyCode, InputAction>>()
             .register_type::<InputConfig>()
-            .insert_resource(InputConfig::default())
+            .init_resource::<InputConfig>()
             .add_event::<ControlEvent>()
             .add_event::<InputEvent>()
             .add_systems(
                 Update,
                 (
                     InputEvent::update.in_set(SystemStage::Spawn),
-                    ControlEvent::update.after(InputEvent::update),
+                    ControlEvent::update
+                        .after(InputEvent::update)
+                        .run_if(not(resource_exists::<ReplayPlayback>)),
                 ),
             );
     }
@@ -51,15 +54,59 @@ pub enum InputAction {
     SpawnBlue,
     SpawnPlankton,
     SpawnFood,
+    FollowSelection,
+    CycleObstacle,
+    TogglePause,
+    StepFrame,
+    ToggleDebugText,
+    ToggleFullscreen,
+    /// Recall control group N, or assign it while Ctrl is held.
+    ControlGroup(u8),
 }
 
 /// Specifies input mapping.
-#[derive(Resource, Clone, Default, Reflect)]
+/// Insert this resource before adding `InputActionPlugin` to rebind keys.
+#[derive(Resource, Clone, Reflect, Debug, PartialEq)]
 #[reflect(Resource)]
 pub struct InputConfig {
     pub keyboard: HashMap<KeyCode, InputAction>,
     pub mouse: HashMap<MouseButton, InputAction>,
 }
+impl Default for InputConfig {
+    fn default() -> Self {
+        Self {
+            keyboard: HashMap::from_iter([
+                (KeyCode::KeyX, InputAction::SpawnHead),
+                (KeyCode::KeyZ, InputAction::SpawnZooid),
+                (KeyCode::Minus, InputAction::SpawnRed),
+                (KeyCode::Equal, InputAction::SpawnBlue),
+                (KeyCode::KeyP, InputAction::SpawnPlankton),
+                (KeyCode::KeyF, InputAction::SpawnFood),
+                (KeyCode::Space, InputAction::FollowSelection),
+                (KeyCode::KeyO, InputAction::CycleObstacle),
+                (KeyCode::Escape, InputAction::TogglePause),
+                (KeyCode::Period, InputAction::StepFrame),
+                (KeyCode::KeyT, InputAction::ToggleDebugText),
+                (KeyCode::F11, InputAction::ToggleFullscreen),
+                (KeyCode::Digit0, InputAction::ControlGroup(0)),
+                (KeyCode::Digit1, InputAction::ControlGroup(1)),
+                (KeyCode::Digit2, InputAction::ControlGroup(2)),
+                (KeyCode::Digit3, InputAction::ControlGroup(3)),
+                (KeyCode::Digit4, InputAction::ControlGroup(4)),
+                (KeyCode::Digit5, InputAction::ControlGroup(5)),
+                (KeyCode::Digit6, InputAction::ControlGroup(6)),
+                (KeyCode::Digit7, InputAction::ControlGroup(7)),
+                (KeyCode::Digit8, InputAction::ControlGroup(8)),
+                (KeyCode::Digit9, InputAction::ControlGroup(9)),
+            ]),
+            mouse: HashMap::from_iter([
+                (MouseButton::Left, InputAction::Primary),
+                (MouseButton::Right, InputAction::Secondary),
+                (MouseButton::Middle, InputAction::PanCamera),
+            ]),
+        }
+    }
+}
 
 #[derive(Event)]
 pub struct InputEvent {
@@ -72,12 +119,20 @@ impl InputEvent {
         mut keyboard_inputs: EventReader<KeyboardInput>,
         mut mouse_inputs: EventReader<MouseButtonInput>,
         config: Res<InputConfig>,
+        keyboard: Option<Res<ButtonInput<KeyCode>>>,
     ) {
+        // Shift+number saves a scene slot instead of recalling a control group.
+        let shift = keyboard.is_some_and(|keyboard| {
+            keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight])
+        });
         for event in keyboard_inputs.read() {
             let KeyboardInput {
                 key_code, state, ..
             } = event;
             if let Some(&action) = config.keyboard.get(key_code) {
+                if shift && matches!(action, InputAction::ControlGroup(_)) {
+                    continue;
+                }
                 inputs.send(Self {
                     action,
                     state: *state,
@@ -110,6 +165,8 @@ pub struct ControlEvent {
     pub action: ControlAction,
     pub state: ButtonState,
     pub position: Vec2,
+    /// Object under the cursor, if the action targeted one.
+    pub entity: Option<Entity>,
 }
 impl ControlEvent {
     pub fn is_pressed(&self, action: ControlAction) -> bool {
@@ -118,6 +175,7 @@ impl ControlEvent {
     pub fn is_released(&self, action: ControlAction) -> bool {
         self.action == action && self.state == ButtonState::Released
     }
+    #[allow(clippy::too_many_arguments)]
     pub fn update(
         raycast: RaycastCommands,
         mut input_events: EventReader<InputEvent>,
@@ -126,6 +184,8 @@ impl ControlEvent {
         grid_spec: Option<Res<GridSpec>>,
         mut timers: Local<ControlTimers>,
         time: Res<Time>,
+        keyboard: Option<Res<ButtonInput<KeyCode>>>,
+        mut held: Local<HashMap<InputAction, ControlAction>>,
     ) {
         let grid_spec = if let Some(grid_spec) = grid_spec {
             grid_spec
@@ -138,7 +198,36 @@ impl ControlEvent {
                 raycast_event = raycast.raycast(Cursor::ray3d(cursor.single()))
             }
             if let Some(raycast_event) = &raycast_event {
-                let action = ControlAction::from((raycast_event.target, event.action));
+                let mut action = ControlAction::from((raycast_event.target, event.action));
+                let ctrl = keyboard.as_ref().is_some_and(|keyboard| {
+                    keyboard.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+                });
+                if ctrl {
+                    action = action.assign_group();
+                }
+                let alt = keyboard.as_ref().is_some_and(|keyboard| {
+                    keyboard.any_pressed([KeyCode::AltLeft, KeyCode::AltRight])
+                });
+                if alt {
+                    action = action.paint_obstacle();
+                }
+                let shift = keyboard.as_ref().is_some_and(|keyboard| {
+                    keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight])
+                });
+                if shift {
+                    action = action.queue_move();
+                }
+                // Releases end the action started by the press, even if the cursor left its target.
+                match event.state {
+                    ButtonState::Pressed => {
+                        held.insert(event.action, action);
+                    }
+                    ButtonState::Released => {
+                        if let Some(pressed) = held.remove(&event.action) {
+                            action = pressed;
+                        }
+                    }
+                }
 
                 // Skip this action if the timer isn't ready.
                 if let Some(timer) = timers.get_mut(&action) {
@@ -160,9 +249,13 @@ impl ControlEvent {
                         RaycastTarget::Minimap => grid_spec.local_to_world_position(
                             raycast_event.position * Vec2 { x: 1., y: -1. },
                         ),
-                        RaycastTarget::WorldGrid => raycast_event.world_position,
+                        RaycastTarget::WorldGrid | RaycastTarget::Object => {
+                            raycast_event.world_position
+                        }
                         RaycastTarget::None => raycast_event.position,
                     },
+                    entity: (raycast_event.target == RaycastTarget::Object)
+                        .then_some(raycast_event.entity),
                 };
                 control_events.send(event);
             }
@@ -180,6 +273,12 @@ impl ControlEvent {
             if timer.finished() {
                 timer.reset();
                 if let Some(raycast_event) = &raycast_event {
+                    // Only scrub the camera while the cursor is over the minimap.
+                    if action == ControlAction::PanCamera
+                        && raycast_event.target != RaycastTarget::Minimap
+                    {
+                        continue;
+                    }
                     let event = ControlEvent {
                         action,
                         state: ButtonState::Pressed,
@@ -187,12 +286,14 @@ impl ControlEvent {
                             RaycastTarget::Minimap => grid_spec.local_to_world_position(
                                 raycast_event.position * Vec2 { x: 1., y: -1. },
                             ),
-                            RaycastTarget::WorldGrid => raycast_event.world_position,
+                            RaycastTarget::WorldGrid | RaycastTarget::Object => {
+                                raycast_event.world_position
+                            }
                             RaycastTarget::None => raycast_event.position,
                         },
+                        entity: (raycast_event.target == RaycastTarget::Object)
+                            .then_some(raycast_event.entity),
                     };
-                    info!("Held!");
-                    dbg!(&event);
                     control_events.send(event);
                 }
             }
@@ -201,12 +302,17 @@ impl ControlEvent {
 }
 
 /// Describes an action input by the user.
-#[derive(Default, PartialEq, Eq, Clone, Copy, Debug, Hash)]
+#[derive(Default, PartialEq, Eq, Clone, Copy, Debug, Hash, clap::ValueEnum)]
 pub enum ControlAction {
     #[default]
     None,
     Select,
+    /// Select starting on an object, carried in `ControlEvent::entity`.
+    /// Releasing without dragging selects that object; dragging box-selects as usual.
+    SelectSingle,
     Move,
+    /// Move after finishing the current orders. Issued by holding Shift while moving.
+    QueueMove,
     PanCamera,
 
     SpawnHead,
@@ -215,6 +321,97 @@ pub enum ControlAction {
     SpawnBlue,
     SpawnPlankton,
     SpawnFood,
+    FollowSelection,
+    PaintObstacle,
+    CycleObstacle,
+    TogglePause,
+    StepFrame,
+    /// Show the objectives of the selected objects.
+    ToggleDebugText,
+    /// Switch between borderless fullscreen and a window of `Configs.window_size`.
+    ToggleFullscreen,
+
+    RecallGroup0,
+    RecallGroup1,
+    RecallGroup2,
+    RecallGroup3,
+    RecallGroup4,
+    RecallGroup5,
+    RecallGroup6,
+    RecallGroup7,
+    RecallGroup8,
+    RecallGroup9,
+    AssignGroup0,
+    AssignGroup1,
+    AssignGroup2,
+    AssignGroup3,
+    AssignGroup4,
+    AssignGroup5,
+    AssignGroup6,
+    AssignGroup7,
+    AssignGroup8,
+    AssignGroup9,
+}
+impl ControlAction {
+    pub const RECALL_GROUPS: [Self; 10] = [
+        Self::RecallGroup0,
+        Self::RecallGroup1,
+        Self::RecallGroup2,
+        Self::RecallGroup3,
+        Self::RecallGroup4,
+        Self::RecallGroup5,
+        Self::RecallGroup6,
+        Self::RecallGroup7,
+        Self::RecallGroup8,
+        Self::RecallGroup9,
+    ];
+    pub const ASSIGN_GROUPS: [Self; 10] = [
+        Self::AssignGroup0,
+        Self::AssignGroup1,
+        Self::AssignGroup2,
+        Self::AssignGroup3,
+        Self::AssignGroup4,
+        Self::AssignGroup5,
+        Self::AssignGroup6,
+        Self::AssignGroup7,
+        Self::AssignGroup8,
+        Self::AssignGroup9,
+    ];
+
+    /// Returns the control group index and whether the group is being assigned.
+    pub fn control_group(self) -> Option<(usize, bool)> {
+        if let Some(index) = Self::RECALL_GROUPS.iter().position(|&a| a == self) {
+            return Some((index, false));
+        }
+        Self::ASSIGN_GROUPS
+            .iter()
+            .position(|&a| a == self)
+            .map(|index| (index, true))
+    }
+
+    /// Converts a world selection into obstacle painting, otherwise returns self.
+    pub fn paint_obstacle(self) -> Self {
+        match self {
+            Self::Select => Self::PaintObstacle,
+            _ => self,
+        }
+    }
+
+    /// Converts a move into a queued move, otherwise returns self.
+    pub fn queue_move(self) -> Self {
+        match self {
+            Self::Move => Self::QueueMove,
+            _ => self,
+        }
+    }
+
+    /// Converts a control group recall into an assignment, otherwise returns self.
+    pub fn assign_group(self) -> Self {
+        match self.control_group() {
+            Some((index, false)) => Self::ASSIGN_GROUPS[index],
+            _ => self,
+        }
+    }
 }
 impl From<(RaycastTarget, InputAction)> for ControlAction {
     fn from(value: (RaycastTarget, InputAction)) -> Self {
@@ -222,6 +419,9 @@ impl From<(RaycastTarget, InputAction)> for ControlAction {
             (RaycastTarget::Minimap, InputAction::Primary) => Self::PanCamera,
             (RaycastTarget::Minimap, InputAction::PanCamera) => Self::PanCamera,
             (RaycastTarget::Minimap, InputAction::Secondary) => Self::Move,
+            (RaycastTarget::Object, InputAction::Primary) => Self::SelectSingle,
+            // Other actions over an object behave as if they targeted the world.
+            (RaycastTarget::Object, action) => Self::from((RaycastTarget::WorldGrid, action)),
             (RaycastTarget::WorldGrid, InputAction::Primary) => Self::Select,
             (RaycastTarget::WorldGrid, InputAction::Secondary) => Self::Move,
             (RaycastTarget::WorldGrid, InputAction::PanCamera) => Self::PanCamera,
@@ -231,6 +431,15 @@ impl From<(RaycastTarget, InputAction)> for ControlAction {
             (RaycastTarget::WorldGrid, InputAction::SpawnBlue) => Self::SpawnBlue,
             (RaycastTarget::WorldGrid, InputAction::SpawnPlankton) => Self::SpawnPlankton,
             (RaycastTarget::WorldGrid, InputAction::SpawnFood) => Self::SpawnFood,
+            (_, InputAction::FollowSelection) => Self::FollowSelection,
+            (_, InputAction::CycleObstacle) => Self::CycleObstacle,
+            (_, InputAction::TogglePause) => Self::TogglePause,
+            (_, InputAction::StepFrame) => Self::StepFrame,
+            (_, InputAction::ToggleDebugText) => Self::ToggleDebugText,
+            (_, InputAction::ToggleFullscreen) => Self::ToggleFullscreen,
+            (_, InputAction::ControlGroup(index)) => {
+                Self::RECALL_GROUPS[index as usize % Self::RECALL_GROUPS.len()]
+            }
             (RaycastTarget::None, _) => Self::None,
             _ => Self::None,
         }
@@ -251,6 +460,14 @@ impl Default for ControlTimers {
             ControlAction::Select,
             Timer::new(Duration::from_millis(5), TimerMode::Repeating),
         );
+        timers.insert(
+            ControlAction::SelectSingle,
+            Timer::new(Duration::from_millis(5), TimerMode::Repeating),
+        );
+        timers.insert(
+            ControlAction::PanCamera,
+            Timer::new(Duration::from_millis(5), TimerMode::Repeating),
+        );
         for (_action, timer) in timers.iter_mut() {
             timer.pause();
         }
@@ -268,3 +485,90 @@ impl IndexMut<ControlAction> for ControlTimers {
         self.get_mut(&i).unwrap()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        ecs::system::RunSystemOnce,
+        input::{
+            keyboard::{Key, KeyboardInput, NativeKey},
+            mouse::MouseButtonInput,
+            ButtonState,
+        },
+        prelude::*,
+        utils::HashMap,
+    };
+
+    use crate::prelude::*;
+
+    use super::{InputAction, InputActionPlugin, InputConfig, InputEvent};
+
+    #[test]
+    fn test_rebind_input() {
+        let config = InputConfig {
+            keyboard: HashMap::from_iter([(KeyCode::KeyR, InputAction::SpawnRed)]),
+            ..default()
+        };
+        let mut app = App::new();
+        app.insert_resource(config.clone())
+            .add_plugins(InputActionPlugin);
+        assert_eq!(app.world.resource::<InputConfig>(), &config);
+
+        app.world.init_resource::<Events<KeyboardInput>>();
+        app.world.init_resource::<Events<MouseButtonInput>>();
+        for key_code in [KeyCode::Minus, KeyCode::KeyR] {
+            app.world.send_event(KeyboardInput {
+                key_code,
+                logical_key: Key::Unidentified(NativeKey::Unidentified),
+                state: ButtonState::Pressed,
+                window: Entity::PLACEHOLDER,
+            });
+        }
+        app.world.run_system_once(InputEvent::update);
+
+        let events = app.world.resource::<Events<InputEvent>>();
+        let mut reader = events.get_reader();
+        let actions: Vec<InputAction> = reader.read(events).map(|event| event.action).collect();
+        assert_eq!(actions, vec![InputAction::SpawnRed]);
+    }
+
+    #[test]
+    fn test_shift_number_skips_control_group() {
+        let mut app = App::new();
+        app.add_plugins(InputActionPlugin);
+        app.world.init_resource::<Events<KeyboardInput>>();
+        app.world.init_resource::<Events<MouseButtonInput>>();
+        let mut keyboard_input = ButtonInput::<KeyCode>::default();
+        keyboard_input.press(KeyCode::ShiftLeft);
+        app.world.insert_resource(keyboard_input);
+        for key_code in [KeyCode::Digit1, KeyCode::KeyZ] {
+            app.world.send_event(KeyboardInput {
+                key_code,
+                logical_key: Key::Unidentified(NativeKey::Unidentified),
+                state: ButtonState::Pressed,
+                window: Entity::PLACEHOLDER,
+            });
+        }
+        app.world.run_system_once(InputEvent::update);
+
+        let events = app.world.resource::<Events<InputEvent>>();
+        let mut reader = events.get_reader();
+        let actions: Vec<InputAction> = reader.read(events).map(|event| event.action).collect();
+        assert_eq!(actions, vec![InputAction::SpawnZooid]);
+    }
+
+    #[test]
+    fn test_object_target_actions() {
+        let action = |input| ControlAction::from((RaycastTarget::Object, input));
+        assert_eq!(action(InputAction::Primary), ControlAction::SelectSingle);
+        assert_eq!(action(InputAction::Secondary), ControlAction::Move);
+        assert_eq!(ControlAction::Move.queue_move(), ControlAction::QueueMove);
+        assert_eq!(ControlAction::Select.queue_move(), ControlAction::Select);
+        assert_eq!(action(InputAction::SpawnZooid), ControlAction::SpawnZooid);
+        // Missing all objects falls back to box selection on the world grid.
+        assert_eq!(
+            ControlAction::from((RaycastTarget::WorldGrid, InputAction::Primary)),
+            ControlAction::Select
+        );
+    }
+}
@@ -1,9 +1,14 @@
+use std::path::PathBuf;
+
 use bevy::{
     diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
     prelude::*,
 };
+use clap::Parser;
 
 pub mod aabb;
+pub mod ai;
+pub mod audio;
 pub mod camera;
 pub mod config;
 pub mod console;
@@ -12,16 +17,21 @@ pub mod effects;
 pub mod grid;
 pub mod inputs;
 pub mod meshes;
+pub mod metrics;
 pub mod objects;
 pub mod physics;
 pub mod raycast;
+pub mod replay;
+pub mod rng;
 pub mod scene;
 pub mod selector;
+pub mod sim;
 pub mod stages;
 pub mod waypoint;
 pub mod window;
 pub mod zindex;
 
+use bevy_hanabi::prelude::HanabiPlugin;
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
 
 pub mod prelude {
@@ -33,8 +43,9 @@ pub mod prelude {
         effects,
         effects::EffectCommands,
         grid::{
-            CreateWaypointEvent, EntityGridEvent, EntitySet, Grid2, Grid2Plugin, GridEntity,
-            GridSize, GridSpec, NavigationGrid2, Obstacle, RowCol, RowColDistance,
+            CreateWaypointEvent, EntityGrid, EntityGridEvent, EntityGrids, EntityGridsMut,
+            EntitySet, Grid2, Grid2Plugin, GridEntity, GridSize, GridSpec, NavigationGrid2,
+            Obstacle, RowCol, RowColDistance,
         },
         inputs::{ControlAction, ControlEvent},
         meshes,
@@ -42,18 +53,32 @@ pub mod prelude {
             DamageEvent, Health, InteractionConfigs, Object, ObjectCommands, ObjectConfig,
             ObjectConfigs, Objective, ObjectiveConfig, ObjectiveDebugger, Objectives, Team,
         },
-        physics::{Acceleration, PhysicsBundle, PhysicsMaterial, PhysicsMaterialType, Velocity},
+        physics::{
+            Acceleration, Drag, FaceVelocity, PhysicsBundle, PhysicsMaterial, PhysicsMaterialType,
+            SpawnRamp, Upright, Velocity,
+        },
         raycast::{RaycastEvent, RaycastTarget},
-        selector::Selected,
-        stages::SystemStage,
+        rng::SimRng,
+        selector::{ControlGroups, Selected, SelectedCount},
+        stages::{SimulationState, SystemStage},
         waypoint::Waypoint,
         window, zindex,
     };
 }
 
-use prelude::*;
+/// Command line arguments.
+#[derive(Parser, Debug)]
+struct Args {
+    /// Record control events to this replay file.
+    #[arg(long)]
+    record: Option<PathBuf>,
+    /// Play back control events from this replay file instead of live input.
+    #[arg(long)]
+    replay: Option<PathBuf>,
+}
 
 fn main() {
+    let args = Args::parse();
     App::new()
         .add_plugins((
             DefaultPlugins
@@ -62,27 +87,29 @@ fn main() {
                     ..default()
                 })
                 .set(window::custom_plugin()),
-            config::ConfigPlugin,
-            inputs::InputActionPlugin,
-            grid::GridPlugin,
-            objects::ObjectsPlugin,
+            HanabiPlugin,
+            sim::SimulationPlugins,
+            grid::GridVisualsPlugin,
             WorldInspectorPlugin::new(),
             console::CustomConsolePlugin,
             scene::LoadableScenePlugin,
             selector::SelectorPlugin,
-            waypoint::WaypointPlugin,
-            raycast::RaycastPlugin,
             camera::CameraPlugin,
-            physics::PhysicsPlugin,
             cursor::CursorPlugin,
-            effects::EffectsPlugin,
         ))
-        .add_plugins((FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin::default()))
+        .add_plugins((
+            replay::ReplayPlugin {
+                record: args.record,
+                replay: args.replay,
+            },
+            audio::AudioPlugin,
+            ai::AiPlugin,
+            metrics::MetricsPlugin,
+            FrameTimeDiagnosticsPlugin,
+            LogDiagnosticsPlugin::default(),
+        ))
         .add_systems(Startup, startup)
-        .add_systems(
-            FixedUpdate,
-            window::resize_window.in_set(SystemStage::Spawn),
-        )
+        .add_systems(FixedUpdate, window::resize_window)
         .run();
 }
 
@@ -95,7 +122,9 @@ fn startup(mut commands: Commands) {
             "    Move waypoint: right click",
             "    Spawn zooids: 'z'",
             "    Despawn zooids: 'd'",
-            "    Save scene: 's'",
+            "    Save scene: 's', or Shift+number for slot 0-9",
+            "    Pause: 'esc'",
+            "    Step frame while paused: '.'",
             "    Open editor: 'e'",
             "    -",
         ]
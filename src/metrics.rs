@@ -0,0 +1,296 @@
+use std::collections::VecDeque;
+#[cfg(not(target_arch = "wasm32"))]
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+use bevy::{app::AppExit, prelude::*};
+
+use crate::{
+    objects::{AlliedNeighbors, EnemyNeighbors},
+    prelude::*,
+};
+
+/// Plugin for logging aggregate simulation metrics each step.
+/// Rows are appended to the CSV at `Configs.metrics_path`, or kept in memory on wasm.
+pub struct MetricsPlugin;
+impl Plugin for MetricsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Metrics>()
+            .add_systems(
+                FixedUpdate,
+                Metrics::update
+                    .in_set(SystemStage::Despawn)
+                    .run_if(metrics_enabled),
+            )
+            .add_systems(Last, Metrics::flush_on_exit.run_if(metrics_enabled));
+    }
+}
+
+/// Returns true if metrics should be collected.
+fn metrics_enabled(configs: Res<Configs>) -> bool {
+    !configs.metrics_path.is_empty()
+}
+
+/// Aggregate metrics for one simulation step.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct MetricsRow {
+    pub frame: u64,
+    pub mean_speed: f32,
+    pub mean_neighbors: f32,
+    pub team_counts: [usize; Team::COUNT],
+    pub damage_events: usize,
+    pub damage: i32,
+}
+impl MetricsRow {
+    pub fn csv_header() -> String {
+        let teams = Team::ALL.map(|team| format!("{:?}_count", team).to_lowercase());
+        format!(
+            "frame,mean_speed,mean_neighbors,{},damage_events,damage",
+            teams.join(",")
+        )
+    }
+
+    pub fn to_csv(&self) -> String {
+        let teams = self.team_counts.map(|count| count.to_string());
+        format!(
+            "{},{},{},{},{},{}",
+            self.frame,
+            self.mean_speed,
+            self.mean_neighbors,
+            teams.join(","),
+            self.damage_events,
+            self.damage
+        )
+    }
+}
+
+/// Buffered metrics rows.
+#[derive(Resource, Default, Debug)]
+pub struct Metrics {
+    pub frame: u64,
+    /// Most recent rows, for the `metrics` console command.
+    pub recent: VecDeque<MetricsRow>,
+    /// Rows not yet written to the CSV file.
+    pending: Vec<MetricsRow>,
+    /// Path the CSV header was written to.
+    header_path: Option<String>,
+    /// CSV file kept open for appending, with the path it was created at.
+    #[cfg(not(target_arch = "wasm32"))]
+    file: Option<(String, BufWriter<File>)>,
+}
+impl Metrics {
+    /// Most rows kept in memory.
+    pub const MAX_RECENT_ROWS: usize = 10_000;
+    /// Rows buffered between file writes.
+    pub const FLUSH_ROWS: usize = 60;
+
+    /// Compute this step's row from the current objects and damage events.
+    pub fn update(
+        objects: Query<(&Team, &Velocity, &AlliedNeighbors, &EnemyNeighbors), With<Object>>,
+        mut damage_events: EventReader<DamageEvent>,
+        mut metrics: ResMut<Self>,
+        configs: Res<Configs>,
+    ) {
+        let mut row = MetricsRow {
+            frame: metrics.frame,
+            ..default()
+        };
+        let mut total_speed = 0.;
+        let mut total_neighbors = 0;
+        for (&team, velocity, allies, enemies) in &objects {
+            row.team_counts[team as usize] += 1;
+            total_speed += velocity.length();
+            total_neighbors += allies.len() + enemies.len();
+        }
+        let count = row.team_counts.iter().sum::<usize>();
+        if count > 0 {
+            row.mean_speed = total_speed / count as f32;
+            row.mean_neighbors = total_neighbors as f32 / count as f32;
+        }
+        for event in damage_events.read() {
+            row.damage_events += 1;
+            row.damage += event.amount;
+        }
+        metrics.push(row, &configs.metrics_path);
+    }
+
+    /// Record a row, writing buffered rows to `path` once enough have accumulated.
+    pub fn push(&mut self, row: MetricsRow, path: &str) {
+        self.frame += 1;
+        if self.recent.len() >= Self::MAX_RECENT_ROWS {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(row.clone());
+        self.pending.push(row);
+        if self.pending.len() >= Self::FLUSH_ROWS {
+            self.flush(path);
+        }
+    }
+
+    /// Returns the CSV text for the pending rows, starting with a header for a new path.
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    fn take_csv(&mut self, path: &str) -> String {
+        let mut lines = Vec::with_capacity(self.pending.len() + 1);
+        if self.header_path.as_deref() != Some(path) {
+            self.header_path = Some(path.to_string());
+            lines.push(MetricsRow::csv_header());
+        }
+        lines.extend(self.pending.drain(..).map(|row| row.to_csv()));
+        lines.join("\n") + "\n"
+    }
+
+    /// Write remaining rows before the app exits.
+    pub fn flush_on_exit(
+        mut exit_events: EventReader<AppExit>,
+        mut metrics: ResMut<Self>,
+        configs: Res<Configs>,
+    ) {
+        if exit_events.read().last().is_some() {
+            metrics.flush(&configs.metrics_path);
+        }
+    }
+
+    /// Append pending rows to the CSV file. Rows stay in memory on wasm.
+    /// The file is truncated once when first written, then appended to through one handle,
+    /// so rows are always written in order.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn flush(&mut self, path: &str) {
+        let text = self.take_csv(path);
+        if self
+            .file
+            .as_ref()
+            .is_none_or(|(file_path, _)| file_path != path)
+        {
+            match File::create(path) {
+                Ok(file) => self.file = Some((path.to_string(), BufWriter::new(file))),
+                Err(err) => {
+                    error!("Failed to create metrics file {}: {}", path, err);
+                    self.file = None;
+                    return;
+                }
+            }
+        }
+        let (_, file) = self.file.as_mut().unwrap();
+        if let Err(err) = file.write_all(text.as_bytes()).and_then(|()| file.flush()) {
+            error!("Failed to write metrics to {}: {}", path, err);
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn flush(&mut self, _path: &str) {
+        self.pending.clear();
+    }
+
+    /// CSV text for the last `count` rows.
+    pub fn recent_csv(&self, count: usize) -> String {
+        let skip = self.recent.len().saturating_sub(count);
+        std::iter::once(MetricsRow::csv_header())
+            .chain(self.recent.iter().skip(skip).map(MetricsRow::to_csv))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{app::AppExit, ecs::system::RunSystemOnce, prelude::*};
+
+    use crate::{
+        objects::{AlliedNeighbors, EnemyNeighbors},
+        prelude::*,
+    };
+
+    use super::{Metrics, MetricsRow};
+
+    #[test]
+    fn test_metrics_row() {
+        let mut world = World::new();
+        world.init_resource::<Metrics>();
+        world.init_resource::<Events<DamageEvent>>();
+        world.insert_resource(Configs {
+            metrics_path: "metrics.csv".to_string(),
+            ..default()
+        });
+        for (team, speed) in [(Team::Blue, 2.), (Team::Blue, 4.), (Team::Red, 0.)] {
+            world.spawn((
+                Object::Worker,
+                team,
+                Velocity(Vec2::X * speed),
+                AlliedNeighbors::default(),
+                EnemyNeighbors::default(),
+            ));
+        }
+        world.send_event(DamageEvent {
+            damager: Entity::PLACEHOLDER,
+            damaged: Entity::PLACEHOLDER,
+            amount: 2,
+            velocity: Velocity::ZERO,
+        });
+        world.run_system_once(Metrics::update);
+        world.resource_mut::<Events<DamageEvent>>().clear();
+        world.run_system_once(Metrics::update);
+
+        let metrics = world.resource::<Metrics>();
+        assert_eq!(metrics.frame, 2);
+        let row = &metrics.recent[0];
+        assert_eq!(row.mean_speed, 2.);
+        assert_eq!(row.mean_neighbors, 0.);
+        assert_eq!(row.team_counts, [0, 2, 1]);
+        assert_eq!((row.damage_events, row.damage), (1, 2));
+        assert_eq!(metrics.recent[1].frame, 1);
+
+        assert_eq!(
+            MetricsRow::csv_header(),
+            "frame,mean_speed,mean_neighbors,none_count,blue_count,red_count,damage_events,damage"
+        );
+        assert_eq!(
+            metrics.recent_csv(1),
+            format!("{}\n1,2,0,0,2,1,0,0", MetricsRow::csv_header())
+        );
+    }
+
+    #[test]
+    fn test_metrics_flush_on_exit() {
+        let path = std::env::temp_dir().join("birdflow_test_metrics.csv");
+        let path = path.to_str().unwrap().to_string();
+        let mut world = World::new();
+        world.init_resource::<Metrics>();
+        world.init_resource::<Events<AppExit>>();
+        world.insert_resource(Configs {
+            metrics_path: path.clone(),
+            ..default()
+        });
+        let push = |world: &mut World, count: usize| {
+            let mut metrics = world.resource_mut::<Metrics>();
+            for _ in 0..count {
+                metrics.push(MetricsRow::default(), &path);
+            }
+        };
+
+        // A full buffer is written, the rest waits for exit.
+        push(&mut world, Metrics::FLUSH_ROWS + 2);
+        world.run_system_once(Metrics::flush_on_exit);
+        let lines = || std::fs::read_to_string(&path).unwrap().lines().count();
+        assert_eq!(lines(), Metrics::FLUSH_ROWS + 1);
+        world.send_event(AppExit);
+        world.run_system_once(Metrics::flush_on_exit);
+        assert_eq!(lines(), Metrics::FLUSH_ROWS + 3);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_metrics_csv_header() {
+        let mut metrics = Metrics::default();
+        metrics.pending.push(MetricsRow::default());
+        let csv = metrics.take_csv("a.csv");
+        assert_eq!(csv.lines().count(), 2);
+
+        // The header is only written once per file.
+        metrics.pending.push(MetricsRow::default());
+        assert_eq!(metrics.take_csv("a.csv").lines().count(), 1);
+        metrics.pending.push(MetricsRow::default());
+        assert_eq!(metrics.take_csv("b.csv").lines().count(), 2);
+    }
+}
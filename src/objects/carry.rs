@@ -1,21 +1,41 @@
 use crate::prelude::*;
 use bevy::{ecs::query::QueryData, prelude::*};
 
-use super::zooid_head::NearestZooidHead;
+use super::{
+    resources::TeamResources,
+    zooid_head::{NearestZooidHead, ZooidHead},
+};
 
 /// Plugin for picking up items and carrying them.
 /// 1) When one entity begins carrying the other, their velocity is zeroed out.
 /// 2) At each step, we guarantee that all carrying entities have the same acceleration and velocity.
+/// 3) When carried food reaches the carrier's head, it is consumed and counted.
 pub struct CarryPlugin;
 impl Plugin for CarryPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<CarryEvent>().add_systems(
-            FixedUpdate,
-            (CarryEvent::update, Carrier::update, CarriedBy::update)
-                .in_set(SystemStage::PostCompute)
-                .after(Objectives::update)
-                .chain(),
-        );
+        app.add_event::<CarryEvent>()
+            .init_resource::<FoodCollected>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    CarryEvent::update,
+                    Carrier::update,
+                    CarriedBy::update,
+                    CarriedBy::deliver,
+                )
+                    .in_set(SystemStage::PostCompute)
+                    .after(Objectives::update)
+                    .chain(),
+            );
+    }
+}
+
+/// Number of food items delivered to heads per team.
+#[derive(Resource, Default, Debug)]
+pub struct FoodCollected(pub [u32; Team::COUNT]);
+impl FoodCollected {
+    pub fn get(&self, team: Team) -> u32 {
+        self.0[team as usize]
     }
 }
 
@@ -80,16 +100,20 @@ impl Carrier {
     pub fn new(entity: Entity) -> Self {
         Self { entity }
     }
-    /// Cleanup invalid carriers.
+    /// Cleanup invalid carriers, e.g. when the carried entity died.
+    /// The carrier stops returning to its head.
     pub fn update(
-        mut carriers: Query<(Entity, &Carrier)>,
+        mut carriers: Query<(Entity, &Carrier, Option<&mut Objectives>)>,
         carried: Query<&Velocity, With<CarriedBy>>,
         mut commands: Commands,
     ) {
-        for (entity, carrier) in &mut carriers {
+        for (entity, carrier, objectives) in &mut carriers {
             if carried.get(carrier.entity).is_err() {
                 info!("Carrier removed.");
                 commands.entity(entity).remove::<Self>();
+                if let Some(mut objectives) = objectives {
+                    objectives.clear();
+                }
             }
         }
     }
@@ -102,34 +126,35 @@ impl CarriedBy {
         Self(vec![entity])
     }
     /// Accululate acceleration from all carriers.
+    /// The carried entity and its carriers share the averaged velocity so they move as one unit.
     pub fn update(
-        mut carried: Query<(Entity, &mut Self, &Velocity, &mut Acceleration), Without<Carrier>>,
+        mut carried: Query<(Entity, &mut Self, &mut Velocity, &mut Acceleration), Without<Carrier>>,
         mut carriers_query: Query<(&mut Velocity, &mut Acceleration), With<Carrier>>,
         mut commands: Commands,
     ) {
-        for (entity, mut carriers, velocity, mut acceleration) in &mut carried {
+        for (entity, mut carriers, mut velocity, mut acceleration) in &mut carried {
             let mut valid_carriers = Vec::default();
-            // *acceleration = Acceleration::ZERO;
+            let mut total_velocity = *velocity;
 
-            // Sum all accelerations.
+            // Sum all accelerations and velocities.
             for &carrier in carriers.iter() {
-                if let Ok((_carrier_velocity, carrier_acceleration)) = carriers_query.get(carrier) {
+                if let Ok((carrier_velocity, carrier_acceleration)) = carriers_query.get(carrier) {
                     valid_carriers.push(carrier);
                     *acceleration += *carrier_acceleration;
-                    break;
+                    total_velocity += *carrier_velocity;
                 } else {
                     warn!("No carriers!");
                 }
             }
+            *velocity = total_velocity * (1. / (valid_carriers.len() + 1) as f32);
 
-            // Set all carrier accelerations to the parent's acceleration.
+            // Set all carrier accelerations and velocities to the parent's.
             for &carrier in &valid_carriers {
                 if let Ok((mut carrier_velocity, mut carrier_acceleration)) =
                     carriers_query.get_mut(carrier)
                 {
                     *carrier_velocity = *velocity;
                     *carrier_acceleration = *acceleration;
-                    // *carrier_acceleration = Acceleration::ZERO;
                 }
             }
 
@@ -140,4 +165,149 @@ impl CarriedBy {
             }
         }
     }
+
+    /// Consume carried food once it reaches a carrier's head.
+    /// The food is killed so `Object::death` cleans it up, and the carrier's team is credited
+    /// with the food and one biomass.
+    pub fn deliver(
+        mut carried: Query<(&Self, &Object, &Transform, &mut Health)>,
+        mut carriers: Query<(&Team, &NearestZooidHead, &mut Objectives), With<Carrier>>,
+        heads: Query<&Transform, With<ZooidHead>>,
+        configs: Res<Configs>,
+        mut collected: ResMut<FoodCollected>,
+        mut resources: ResMut<TeamResources>,
+    ) {
+        for (carried_by, object, transform, mut health) in &mut carried {
+            if *object != Object::Food || health.health <= 0 {
+                continue;
+            }
+            let Some(config) = configs.objects.get(object) else {
+                warn_once!("No config for {:?}", object);
+                continue;
+            };
+            let position = transform.translation.xy();
+            let delivered_to = carried_by.iter().find_map(|&carrier| {
+                let (&team, nearest_head, _) = carriers.get(carrier).ok()?;
+                let head_transform = heads.get(nearest_head.entity?).ok()?;
+                let distance_squared = head_transform.translation.xy().distance_squared(position);
+                config.is_in_dropoff_range(distance_squared).then_some(team)
+            });
+            let Some(team) = delivered_to else {
+                continue;
+            };
+            health.health = 0;
+            collected.0[team as usize] += 1;
+            resources.add(team, 1);
+            for &carrier in carried_by.iter() {
+                if let Ok((_, _, mut objectives)) = carriers.get_mut(carrier) {
+                    objectives.clear();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{ecs::system::RunSystemOnce, prelude::*, utils::HashMap};
+
+    use crate::{
+        objects::zooid_head::{NearestZooidHead, ZooidHead},
+        prelude::*,
+    };
+
+    use super::{CarriedBy, Carrier, FoodCollected, TeamResources};
+
+    #[test]
+    fn test_velocity_sync() {
+        let mut world = World::new();
+        let carried = world.spawn((Velocity::ZERO, Acceleration(Vec2::Y))).id();
+        let carrier = world
+            .spawn((
+                Carrier::new(carried),
+                Velocity(Vec2::new(10., 0.)),
+                Acceleration(Vec2::X),
+            ))
+            .id();
+        world.entity_mut(carried).insert(CarriedBy::new(carrier));
+
+        world.run_system_once(CarriedBy::update);
+        let velocity = |entity| *world.get::<Velocity>(entity).unwrap();
+        assert_eq!(velocity(carried), Velocity(Vec2::new(5., 0.)));
+        assert_eq!(velocity(carrier), velocity(carried));
+        assert_eq!(
+            *world.get::<Acceleration>(carrier).unwrap(),
+            Acceleration(Vec2::ONE)
+        );
+
+        // Diverging velocities converge again on the next step.
+        *world.get_mut::<Velocity>(carrier).unwrap() = Velocity(Vec2::new(0., 8.));
+        world.run_system_once(CarriedBy::update);
+        let velocity = |entity| *world.get::<Velocity>(entity).unwrap();
+        assert_eq!(velocity(carried), Velocity(Vec2::new(2.5, 4.)));
+        assert_eq!(velocity(carrier), velocity(carried));
+    }
+
+    #[test]
+    fn test_deliver() {
+        let mut world = World::new();
+        world.insert_resource(Configs {
+            objects: ObjectConfigs(HashMap::from_iter([(
+                Object::Food,
+                ObjectConfig {
+                    neighbor_radius: 100.,
+                    ..default()
+                },
+            )])),
+            ..default()
+        });
+        world.init_resource::<FoodCollected>();
+        world.insert_resource(TeamResources([0; Team::COUNT]));
+
+        let head = world.spawn((ZooidHead, Transform::default())).id();
+        let food = world.spawn_empty().id();
+        let worker = world
+            .spawn((
+                Team::Blue,
+                Carrier::new(food),
+                NearestZooidHead { entity: Some(head) },
+                Objectives::new(Objective::FollowEntity(head)),
+            ))
+            .id();
+        world.entity_mut(food).insert((
+            Object::Food,
+            CarriedBy::new(worker),
+            Health::default(),
+            Transform::from_xyz(200., 0., 0.),
+        ));
+
+        // Too far from the head.
+        world.run_system_once(CarriedBy::deliver);
+        assert!(world.get::<Health>(food).unwrap().health > 0);
+        assert_eq!(world.resource::<FoodCollected>().get(Team::Blue), 0);
+
+        // Reaching the head consumes the food and credits the team.
+        world.get_mut::<Transform>(food).unwrap().translation.x = 20.;
+        world.run_system_once(CarriedBy::deliver);
+        world.run_system_once(CarriedBy::deliver);
+        assert_eq!(world.get::<Health>(food).unwrap().health, 0);
+        assert_eq!(world.resource::<FoodCollected>().get(Team::Blue), 1);
+        assert_eq!(world.resource::<TeamResources>().get(Team::Blue), 1);
+        assert_eq!(
+            world.get::<Objectives>(worker).unwrap().last(),
+            &Objective::None
+        );
+
+        // If the carried food dies, the carrier stops carrying.
+        world
+            .entity_mut(worker)
+            .insert(Objectives::new(Objective::FollowEntity(head)));
+        world.despawn(food);
+        world.run_system_once(Carrier::update);
+        assert!(world.get::<Carrier>(worker).is_none());
+        assert_eq!(
+            world.get::<Objectives>(worker).unwrap().last(),
+            &Objective::None
+        );
+    }
 }
@@ -7,7 +7,7 @@ use super::{
     plankton::Plankton,
     zooid_head::{NearestZooidHead, ZooidHead},
     zooid_worker::ZooidWorker,
-    ObjectAssets, TeamMaterials,
+    HealthBar, HealthRegen, ObjectAssets, StuckTimer, TeamMaterials,
 };
 
 #[derive(Default, Debug)]
@@ -16,7 +16,9 @@ pub struct ObjectSpec {
     pub position: Vec2,
     pub zindex: f32,
     pub team: Team,
+    /// Initial velocity. Defaults to the object's spawn velocity (zero for food).
     pub velocity: Option<Velocity>,
+    /// Initial objectives. Heads with no objective follow themselves.
     pub objectives: Objectives,
 }
 
@@ -29,13 +31,36 @@ pub struct ObjectCommands<'w, 's> {
     event_writer: EventWriter<'w, CreateWaypointEvent>,
 }
 impl ObjectCommands<'_, '_> {
-    pub fn spawn(&mut self, spec: ObjectSpec) {
-        let config = &self.configs.objects[&spec.object];
+    /// Spawn an object, returning its entity unless the object has no config.
+    pub fn spawn(&mut self, spec: ObjectSpec) -> Option<Entity> {
         let team_material = self.assets.get_team_material(spec.team);
-        let velocity = if let Some(velocity) = spec.velocity {
-            velocity
-        } else {
-            Velocity(Vec2::ONE) * config.spawn_velocity
+        self.spawn_with_material(spec, team_material)
+    }
+
+    /// Spawn many objects at once, sharing material handles between objects of the same team.
+    pub fn spawn_batch(&mut self, specs: impl IntoIterator<Item = ObjectSpec>) {
+        let mut team_materials: [Option<TeamMaterials>; Team::COUNT] = Default::default();
+        for spec in specs {
+            let team_material = team_materials[spec.team as usize]
+                .get_or_insert_with(|| self.assets.get_team_material(spec.team))
+                .clone();
+            self.spawn_with_material(spec, team_material);
+        }
+    }
+
+    fn spawn_with_material(
+        &mut self,
+        spec: ObjectSpec,
+        team_material: TeamMaterials,
+    ) -> Option<Entity> {
+        let Some(config) = self.configs.objects.get(&spec.object) else {
+            warn_once!("No config for {:?}", spec.object);
+            return None;
+        };
+        let velocity = match (spec.velocity, spec.object) {
+            (Some(velocity), _) => velocity,
+            (None, Object::Food) => Velocity::ZERO,
+            (None, _) => Velocity(Vec2::ONE) * config.spawn_velocity,
         };
         let background = self.background_bundle(
             team_material.clone(),
@@ -44,38 +69,46 @@ impl ObjectCommands<'_, '_> {
                 Object::Plankton | Object::Food => zindex::PLANKTON_BACKGROUND,
             },
         );
-        match spec.object {
-            Object::Worker => {
-                self.commands
-                    .spawn((
-                        ZooidWorker::default(),
-                        NearestZooidHead::default(),
-                        Object::Worker,
-                        spec.team,
-                        PhysicsBundle {
-                            material: config.physics_material,
-                            velocity,
-                            ..default()
-                        },
-                        spec.objectives,
-                        MaterialMesh2dBundle::<ColorMaterial> {
-                            mesh: self.assets.mesh.clone().into(),
-                            transform: Transform::default()
-                                .with_scale(Vec2::splat(10.0).extend(1.))
-                                .with_translation(spec.position.extend(spec.zindex)),
-                            material: team_material.primary,
-                            ..default()
-                        },
-                        Selected::default(),
-                        Health::new(3),
-                        NeighborsBundle::default(),
-                        Name::new("Zooid"),
-                    ))
-                    .with_children(|parent| {
-                        parent.spawn(background);
-                    });
-            }
+        let health_bar = self.health_bar_bundle();
+        let health_regen = config.health_regen;
+        let entity = match spec.object {
+            Object::Worker => self
+                .commands
+                .spawn((
+                    ZooidWorker::default(),
+                    NearestZooidHead::default(),
+                    Object::Worker,
+                    spec.team,
+                    PhysicsBundle {
+                        material: config.physics_material,
+                        velocity,
+                        ..default()
+                    },
+                    SpawnRamp::default(),
+                    FaceVelocity,
+                    StuckTimer::default(),
+                    spec.objectives,
+                    MaterialMesh2dBundle::<ColorMaterial> {
+                        mesh: self.assets.mesh.clone().into(),
+                        transform: Transform::default()
+                            .with_scale(Vec2::splat(10.0).extend(1.))
+                            .with_translation(spec.position.extend(spec.zindex)),
+                        material: team_material.primary,
+                        ..default()
+                    },
+                    Selected::default(),
+                    RaycastTarget::Object,
+                    Health::new(3),
+                    NeighborsBundle::default(),
+                    Name::new("Zooid"),
+                ))
+                .with_children(|parent| {
+                    parent.spawn(background);
+                    parent.spawn(health_bar);
+                })
+                .id(),
             Object::Head => {
+                let idle = spec.objectives.last() == &Objective::None;
                 let mut entity_commands = self.commands.spawn((
                     ZooidHead,
                     Object::Head,
@@ -93,53 +126,64 @@ impl ObjectCommands<'_, '_> {
                         velocity,
                         ..default()
                     },
+                    FaceVelocity,
+                    StuckTimer::default(),
                     spec.objectives,
                     Selected::default(),
+                    RaycastTarget::Object,
                     Health::new(6),
                     NeighborsBundle::default(),
                     Name::new("ZooidHead"),
                 ));
                 entity_commands.with_children(|parent| {
                     parent.spawn(background);
+                    parent.spawn(health_bar);
                 });
+                if idle {
+                    let entity = entity_commands.id();
+                    entity_commands.insert(Objectives::new(Objective::FollowEntity(entity)));
+                }
                 let entity = entity_commands.id();
-                entity_commands.insert(Objectives::new(Objective::FollowEntity(entity)));
                 self.event_writer.send(CreateWaypointEvent {
                     destination: spec.position,
                     sources: vec![spec.position],
+                    team: spec.team,
                 });
+                entity
             }
-            Object::Plankton => {
-                self.commands
-                    .spawn((
-                        Plankton,
-                        Object::Plankton,
-                        Team::None,
-                        MaterialMesh2dBundle::<ColorMaterial> {
-                            mesh: self.assets.mesh.clone().into(),
-                            transform: Transform::default()
-                                .with_scale(Vec2::splat(10.0).extend(1.))
-                                .with_translation(spec.position.extend(zindex::PLANKTON)),
-                            material: team_material.primary,
-                            ..default()
-                        },
-                        PhysicsBundle {
-                            material: config.physics_material,
-                            velocity,
-                            ..default()
-                        },
-                        spec.objectives,
-                        Health::new(1),
-                        Selected::default(),
-                        NeighborsBundle::default(),
-                        Name::new("Plankton"),
-                    ))
-                    .with_children(|parent| {
-                        parent.spawn(background);
-                    });
-            }
-            Object::Food => {
-                self.commands.spawn((
+            Object::Plankton => self
+                .commands
+                .spawn((
+                    Plankton,
+                    Object::Plankton,
+                    Team::None,
+                    MaterialMesh2dBundle::<ColorMaterial> {
+                        mesh: self.assets.mesh.clone().into(),
+                        transform: Transform::default()
+                            .with_scale(Vec2::splat(10.0).extend(1.))
+                            .with_translation(spec.position.extend(zindex::PLANKTON)),
+                        material: team_material.primary,
+                        ..default()
+                    },
+                    PhysicsBundle {
+                        material: config.physics_material,
+                        velocity,
+                        ..default()
+                    },
+                    spec.objectives,
+                    Health::new(1),
+                    Selected::default(),
+                    NeighborsBundle::default(),
+                    Name::new("Plankton"),
+                ))
+                .with_children(|parent| {
+                    parent.spawn(background);
+                    parent.spawn(health_bar);
+                })
+                .id(),
+            Object::Food => self
+                .commands
+                .spawn((
                     Object::Food,
                     Team::None,
                     MaterialMesh2dBundle::<ColorMaterial> {
@@ -152,7 +196,7 @@ impl ObjectCommands<'_, '_> {
                     },
                     PhysicsBundle {
                         material: config.physics_material,
-                        velocity: Velocity::ZERO,
+                        velocity,
                         ..default()
                     },
                     spec.objectives,
@@ -160,9 +204,33 @@ impl ObjectCommands<'_, '_> {
                     Selected::default(),
                     NeighborsBundle::default(),
                     Name::new("Food"),
-                ));
-            }
+                ))
+                .with_children(|parent| {
+                    parent.spawn(health_bar);
+                })
+                .id(),
+        };
+        if health_regen > 0. {
+            self.commands
+                .entity(entity)
+                .insert(HealthRegen::new(health_regen));
         }
+        Some(entity)
+    }
+    pub fn health_bar_bundle(&self) -> impl Bundle {
+        (
+            HealthBar,
+            Upright,
+            MaterialMesh2dBundle::<ColorMaterial> {
+                mesh: self.assets.health_bar_mesh.clone().into(),
+                transform: Transform::default()
+                    .with_scale(Vec3::new(HealthBar::WIDTH, HealthBar::HEIGHT, 1.))
+                    .with_translation(Vec3::new(0., HealthBar::OFFSET, zindex::HEALTH_BAR)),
+                material: self.assets.health_bar_material.clone(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+        )
     }
     pub fn background_bundle(&self, team_material: TeamMaterials, zindex: f32) -> impl Bundle {
         (
@@ -182,3 +250,35 @@ impl ObjectCommands<'_, '_> {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::{ecs::system::RunSystemOnce, prelude::*};
+
+    use crate::{objects::test_world, prelude::*};
+
+    use super::{ObjectBackground, ObjectSpec};
+
+    #[test]
+    fn test_spawn_batch() {
+        let mut world = test_world([(Object::Plankton, ObjectConfig::default())]);
+
+        world.run_system_once(|mut commands: ObjectCommands| {
+            commands.spawn_batch((0..400).map(|i| ObjectSpec {
+                object: Object::Plankton,
+                position: Vec2::new((i % 20) as f32, (i / 20) as f32) * 10.,
+                ..default()
+            }));
+        });
+
+        let mut query = world.query_filtered::<(&Health, &GridEntity, &Children), With<Object>>();
+        assert_eq!(query.iter(&world).count(), 400);
+        for (_, _, children) in query.iter(&world) {
+            let backgrounds = children
+                .iter()
+                .filter(|&&child| world.get::<ObjectBackground>(child).is_some())
+                .count();
+            assert_eq!(backgrounds, 1);
+        }
+    }
+}
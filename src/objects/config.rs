@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 use bevy::prelude::*;
 use bevy::utils::HashMap;
 
@@ -19,6 +21,8 @@ pub struct InteractionConfig {
     pub cohesion_acceleration: f32,
     pub alignment_factor: f32,
     pub slow_factor: f32,
+    /// Any positive value lets the other object damage this one.
+    /// The damage dealt comes from the damager's `ObjectConfig::attack_damage`.
     pub damage_amount: i32,
 }
 impl Default for InteractionConfig {
@@ -34,6 +38,26 @@ impl Default for InteractionConfig {
     }
 }
 
+/// How an object picks which enemy to attack.
+#[derive(Clone, Copy, Reflect, Default, Debug, PartialEq, Eq)]
+pub enum TargetPriority {
+    #[default]
+    Nearest,
+    LowestHealth,
+    // Enemies that deal the most damage per hit.
+    HighestThreat,
+}
+impl TargetPriority {
+    /// Score of an enemy, where lower scores are attacked first. Ties go to the nearest enemy.
+    pub fn score(self, health: Option<&Health>, enemy_config: &ObjectConfig) -> i32 {
+        match self {
+            Self::Nearest => 0,
+            Self::LowestHealth => health.map_or(i32::MAX, |health| health.health),
+            Self::HighestThreat => -enemy_config.attack_damage(),
+        }
+    }
+}
+
 #[derive(Resource, Clone, Default, Deref, DerefMut, Reflect, Debug)]
 #[reflect(Resource)]
 pub struct ObjectConfigs(pub HashMap<Object, ObjectConfig>);
@@ -43,14 +67,42 @@ pub struct ObjectConfigs(pub HashMap<Object, ObjectConfig>);
 pub struct ObjectConfig {
     pub physics_material: PhysicsMaterialType,
     pub neighbor_radius: f32,
+    // Most allied neighbors considered for flocking. Only the nearest are kept in dense flocks.
+    pub max_neighbors: usize,
     pub obstacle_acceleration: f32,
     pub nav_flow_factor: f32,
     pub attack_velocity: f32,
+    // Random delay before the first attack on a new target.
+    pub attack_delay_ms: Range<u64>,
+    // Random delay between attacks.
+    pub attack_cooldown_ms: Range<u64>,
+    // Damage dealt to others per hit. Negative values are treated as zero.
+    pub attack_damage: i32,
     pub spawn_velocity: f32,
+    // Seconds between spawns while the spawn key is held.
+    pub spawn_interval: f32,
+    // Biomass the team spends to spawn one of these.
+    pub spawn_cost: u32,
     pub objective: ObjectiveConfig,
     pub hit_radius: f32,
     pub death_speed: f32,
     pub idle_speed: f32,
+    // Maximum length of the combined acceleration applied each tick.
+    pub max_acceleration: f32,
+    // Speed below which an object far from its target is considered stuck.
+    pub stuck_speed: f32,
+    // Acceleration of the sideways nudge applied to stuck objects.
+    pub stuck_nudge: f32,
+    // Which enemy to attack when several are in range.
+    pub target_priority: TargetPriority,
+    // Objects below this fraction of max health flee from the nearest enemy.
+    pub retreat_health_fraction: f32,
+    // Health regenerated per second while out of combat. Zero disables regeneration.
+    pub health_regen: f32,
+    // Radius in cells revealed around this object.
+    pub visibility_radius: u16,
+    // Seconds an object flashes white after being damaged.
+    pub damage_flash_seconds: f32,
     // Interactions
     pub interactions: InteractionConfigs,
 }
@@ -59,14 +111,28 @@ impl Default for ObjectConfig {
         Self {
             physics_material: PhysicsMaterialType::Default,
             neighbor_radius: 10.0,
+            max_neighbors: 64,
             obstacle_acceleration: 3.,
             nav_flow_factor: 1.,
             attack_velocity: 40.,
+            attack_delay_ms: 0..100,
+            attack_cooldown_ms: 500..1000,
+            attack_damage: 1,
             spawn_velocity: 2.0,
+            spawn_interval: 0.1,
+            spawn_cost: 0,
             objective: ObjectiveConfig::default(),
             hit_radius: 10.0,
             death_speed: 9.0,
             idle_speed: 0.5,
+            max_acceleration: 40.,
+            stuck_speed: 0.2,
+            stuck_nudge: 4.,
+            target_priority: TargetPriority::Nearest,
+            retreat_health_fraction: 0.,
+            health_regen: 0.,
+            visibility_radius: 6,
+            damage_flash_seconds: 0.15,
             interactions: InteractionConfigs({
                 let mut interactions = HashMap::new();
                 interactions.insert(Object::Worker, InteractionConfig::default());
@@ -81,7 +147,15 @@ impl ObjectConfig {
     pub fn is_colliding(&self, distance_squared: f32) -> bool {
         distance_squared < self.hit_radius * self.hit_radius
     }
+    /// Returns true if food is close enough to a head to be consumed.
+    pub fn is_in_dropoff_range(&self, distance_squared: f32) -> bool {
+        distance_squared < self.neighbor_radius * self.neighbor_radius * 0.1
+    }
     pub fn is_damage_velocity(&self, velocity_squared: f32) -> bool {
         velocity_squared > self.death_speed * self.death_speed
     }
+    /// Damage dealt per hit, never negative.
+    pub fn attack_damage(&self) -> i32 {
+        self.attack_damage.max(0)
+    }
 }
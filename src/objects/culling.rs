@@ -0,0 +1,210 @@
+use bevy::prelude::*;
+
+use crate::{
+    prelude::*,
+    replay::{ReplayPlayback, ReplayRecorder},
+};
+
+/// Plugin for skipping expensive simulation for objects far outside the camera view.
+/// Culling depends on the camera, which replays do not record, so it is disabled
+/// while recording or playing back a replay.
+pub struct CullingPlugin;
+impl Plugin for CullingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            Culled::update
+                .in_set(SystemStage::PreCompute)
+                .before(Object::update_acceleration)
+                .run_if(
+                    not(resource_exists::<ReplayRecorder>)
+                        .and_then(not(resource_exists::<ReplayPlayback>)),
+                ),
+        );
+    }
+}
+
+/// Marks objects outside the camera view plus `Configs::cull_margin`.
+/// Culled objects skip the neighbor search and flocking until they come back into view.
+#[derive(Component, Default, Debug)]
+pub struct Culled;
+impl Culled {
+    /// Returns the camera view in world space, expanded by `margin` on each side.
+    pub fn view_bounds(
+        projection: &OrthographicProjection,
+        transform: &GlobalTransform,
+        margin: f32,
+    ) -> Aabb2 {
+        let position = transform.translation().xy();
+        Aabb2 {
+            min: position + projection.area.min - margin,
+            max: position + projection.area.max + margin,
+        }
+    }
+
+    /// Mark objects outside the expanded camera view, and unmark objects that re-entered it.
+    pub fn update(
+        camera: Query<(&OrthographicProjection, &GlobalTransform), With<MainCamera>>,
+        objects: Query<(Entity, &Transform, Has<Self>), With<Object>>,
+        configs: Res<Configs>,
+        mut commands: Commands,
+    ) {
+        let Ok((projection, camera_transform)) = camera.get_single() else {
+            return;
+        };
+        let bounds = Self::view_bounds(projection, camera_transform, configs.cull_margin);
+        for (entity, transform, culled) in &objects {
+            let visible = bounds.contains(transform.translation.xy());
+            if visible && culled {
+                commands.entity(entity).remove::<Self>();
+            } else if !visible && !culled {
+                commands.entity(entity).insert(Self);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        ecs::system::RunSystemOnce,
+        prelude::*,
+        tasks::{ComputeTaskPool, TaskPool},
+        utils::HashMap,
+    };
+
+    use crate::{
+        objects::neighbors::{self, AlliedNeighbors, EnemyNeighbors},
+        prelude::*,
+        replay::ReplayRecorder,
+    };
+
+    use super::{Culled, CullingPlugin};
+
+    #[test]
+    fn test_culled_skip_flocking() {
+        ComputeTaskPool::get_or_init(TaskPool::default);
+        let mut world = World::new();
+        world.insert_resource(Configs {
+            cull_margin: 50.,
+            objects: ObjectConfigs(HashMap::from_iter([(
+                Object::Worker,
+                ObjectConfig::default(),
+            )])),
+            ..default()
+        });
+        world.spawn((
+            MainCamera,
+            OrthographicProjection {
+                area: Rect::new(-100., -100., 100., 100.),
+                ..default()
+            },
+            GlobalTransform::from_translation(Vec3::new(1000., 0., 0.)),
+        ));
+        let mut spawn = |x: f32| {
+            world
+                .spawn((
+                    Object::Worker,
+                    Transform::from_xyz(x, 0., 0.),
+                    Velocity(Vec2::new(0.1, 0.)),
+                    Acceleration::ZERO,
+                    AlliedNeighbors::default(),
+                ))
+                .id()
+        };
+        let visible = spawn(900.);
+        let margin = spawn(1140.);
+        let far = spawn(0.);
+
+        world.run_system_once(Culled::update);
+        assert!(world.get::<Culled>(visible).is_none());
+        assert!(world.get::<Culled>(margin).is_none());
+        assert!(world.get::<Culled>(far).is_some());
+
+        world.run_system_once(Object::update_acceleration);
+        assert_ne!(
+            *world.get::<Acceleration>(visible).unwrap(),
+            Acceleration::ZERO
+        );
+        assert_eq!(*world.get::<Acceleration>(far).unwrap(), Acceleration::ZERO);
+
+        // Objects resume full simulation once back in view.
+        world.get_mut::<Transform>(far).unwrap().translation.x = 1000.;
+        world.run_system_once(Culled::update);
+        assert!(world.get::<Culled>(far).is_none());
+    }
+
+    #[test]
+    fn test_culled_skip_neighbors() {
+        ComputeTaskPool::get_or_init(TaskPool::default);
+        let mut world = World::new();
+        world.insert_resource(Configs {
+            objects: ObjectConfigs(HashMap::from_iter([(
+                Object::Worker,
+                ObjectConfig {
+                    neighbor_radius: 10.,
+                    ..default()
+                },
+            )])),
+            ..default()
+        });
+        let mut grid = Grid2::<EntitySet>::default();
+        grid.resize_with(GridSpec {
+            rows: 10,
+            cols: 10,
+            width: 10.,
+            ..default()
+        });
+        let mut spawn = |position: Vec2| {
+            let entity = world
+                .spawn((
+                    Object::Worker,
+                    Team::Blue,
+                    GlobalTransform::from_translation(position.extend(0.)),
+                    AlliedNeighbors::default(),
+                    EnemyNeighbors::default(),
+                ))
+                .id();
+            grid.update_entity(entity, None, position);
+            entity
+        };
+        let visible = spawn(Vec2::new(1., 1.));
+        let culled = spawn(Vec2::new(2., 2.));
+        world.insert_resource(grid);
+        world.entity_mut(culled).insert(Culled);
+
+        world.run_system_once(neighbors::update);
+        assert_eq!(world.get::<AlliedNeighbors>(visible).unwrap().len(), 1);
+        assert!(world.get::<AlliedNeighbors>(culled).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_no_culling_in_replays() {
+        let mut app = App::new();
+        app.add_plugins(CullingPlugin)
+            .insert_resource(Configs {
+                cull_margin: 50.,
+                ..default()
+            })
+            .insert_resource(ReplayRecorder::new("test.replay".into()));
+        app.world.spawn((
+            MainCamera,
+            OrthographicProjection {
+                area: Rect::new(-100., -100., 100., 100.),
+                ..default()
+            },
+            GlobalTransform::default(),
+        ));
+        let far = app
+            .world
+            .spawn((Object::Worker, Transform::from_xyz(1000., 0., 0.)))
+            .id();
+
+        app.world.run_schedule(FixedUpdate);
+        assert!(app.world.get::<Culled>(far).is_none());
+
+        app.world.remove_resource::<ReplayRecorder>();
+        app.world.run_schedule(FixedUpdate);
+        assert!(app.world.get::<Culled>(far).is_some());
+    }
+}
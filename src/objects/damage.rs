@@ -1,9 +1,11 @@
 use bevy::prelude::*;
 
-use crate::prelude::*;
+use crate::{grid::Dimmed, prelude::*};
 
 use self::effects::{EffectSize, FireworkSpec};
 
+use super::ObjectAssets;
+
 pub struct DamagePlugin;
 impl Plugin for DamagePlugin {
     fn build(&self, app: &mut App) {
@@ -16,20 +18,35 @@ impl Plugin for DamagePlugin {
                 DamageEvent::update
                     .in_set(SystemStage::Compute)
                     .after(Health::update),
+                HealthRegen::update.in_set(SystemStage::PostApply),
+                HealthBar::update
+                    .in_set(SystemStage::PostApply)
+                    .after(HealthRegen::update),
+                DamageFlash::update.in_set(SystemStage::PostApply),
             ),
         );
     }
 }
 
+/// Marks an object that cannot be damaged.
+#[derive(Component, Default, Debug)]
+pub struct Invulnerable;
+
+/// Marks an object that is never automatically targeted by enemies.
+#[derive(Component, Default, Debug)]
+pub struct Passive;
+
 #[derive(Component)]
 pub struct Health {
     pub health: i32,
+    pub max_health: i32,
     pub hit_timer: Timer,
 }
 impl Default for Health {
     fn default() -> Self {
         Self {
             health: 3,
+            max_health: 3,
             hit_timer: Timer::from_seconds(0.2, TimerMode::Once),
         }
     }
@@ -38,6 +55,7 @@ impl Health {
     pub fn new(amount: i32) -> Self {
         Self {
             health: amount,
+            max_health: amount,
             ..default()
         }
     }
@@ -55,6 +73,137 @@ impl Health {
     }
 }
 
+/// Regenerates health over time while out of combat.
+#[derive(Component, Default, Debug)]
+pub struct HealthRegen {
+    /// Health regenerated per second.
+    pub rate: f32,
+    /// Partial health accumulated towards the next whole unit.
+    pub accumulator: f32,
+}
+impl HealthRegen {
+    pub fn new(rate: f32) -> Self {
+        Self {
+            rate,
+            accumulator: 0.,
+        }
+    }
+
+    /// Heal whole units of health up to the max.
+    /// Dead units and units that were just hit don't regenerate.
+    pub fn update(mut query: Query<(&mut Self, &mut Health)>, time: Res<Time>) {
+        for (mut regen, mut health) in &mut query {
+            if health.health <= 0 || !health.damageable() || health.health >= health.max_health {
+                regen.accumulator = 0.;
+                continue;
+            }
+            regen.accumulator += regen.rate * time.delta_seconds();
+            let amount = regen.accumulator.floor();
+            if amount >= 1. {
+                regen.accumulator -= amount;
+                health.health = (health.health + amount as i32).min(health.max_health);
+            }
+        }
+    }
+}
+
+/// Bar above an object showing its remaining health.
+/// Hidden while the parent is at full health.
+#[derive(Component, Default, Debug)]
+pub struct HealthBar;
+impl HealthBar {
+    /// Width of the bar at full health, relative to the parent.
+    pub const WIDTH: f32 = 1.2;
+    /// Height of the bar, relative to the parent.
+    pub const HEIGHT: f32 = 0.15;
+    /// Offset above the parent's center.
+    pub const OFFSET: f32 = 0.8;
+
+    /// Scale the bar to the parent's health fraction.
+    pub fn update(
+        mut query: Query<(&mut Transform, &mut Visibility, &Parent), With<Self>>,
+        healths: Query<&Health>,
+    ) {
+        for (mut transform, mut visibility, parent) in &mut query {
+            let Ok(health) = healths.get(parent.get()) else {
+                continue;
+            };
+            *visibility = if health.health >= health.max_health {
+                Visibility::Hidden
+            } else {
+                Visibility::Inherited
+            };
+            let fraction = health.health as f32 / health.max_health.max(1) as f32;
+            transform.scale.x = Self::WIDTH * fraction.clamp(0., 1.);
+        }
+    }
+}
+
+/// Query for objects that are flashing.
+type DamageFlashQueryData<'a> = (
+    Entity,
+    &'a mut DamageFlash,
+    &'a mut Handle<ColorMaterial>,
+    &'a Team,
+);
+
+/// Flashes an object's material white after it is damaged.
+#[derive(Component, Debug)]
+pub struct DamageFlash {
+    pub timer: Timer,
+    /// Material to restore once the flash ends.
+    pub material: Option<Handle<ColorMaterial>>,
+}
+impl DamageFlash {
+    /// Number of blend steps between the team color and white.
+    pub const STEPS: usize = 4;
+
+    pub fn new(seconds: f32) -> Self {
+        Self {
+            timer: Timer::from_seconds(seconds, TimerMode::Once),
+            material: None,
+        }
+    }
+
+    /// Amount of white to blend in, rising then falling over the timer's duration.
+    pub fn amount(&self) -> f32 {
+        1. - (2. * self.timer.fraction() - 1.).abs()
+    }
+
+    /// Swap each flashing object's material through its team's flash materials towards white
+    /// and back, then restore the original material.
+    pub fn update(
+        mut query: Query<DamageFlashQueryData, Without<Dimmed>>,
+        assets: Res<ObjectAssets>,
+        time: Res<Time>,
+        mut commands: Commands,
+    ) {
+        for (entity, mut flash, mut handle, &team) in &mut query {
+            let material = flash.material.get_or_insert_with(|| handle.clone()).clone();
+            flash.timer.tick(time.delta());
+            if flash.timer.finished() {
+                *handle = material;
+                commands.entity(entity).remove::<Self>();
+                continue;
+            }
+            *handle = assets
+                .flash_material(team, flash.amount())
+                .unwrap_or(material);
+        }
+    }
+}
+
+/// Query for objects that deal and receive damage.
+type DamageQueryData<'a> = (
+    &'a mut Acceleration,
+    &'a mut Health,
+    &'a Team,
+    &'a Transform,
+    &'a Object,
+    Has<Invulnerable>,
+    Option<&'a mut DamageFlash>,
+);
+
 #[derive(Event)]
 pub struct DamageEvent {
     pub damager: Entity,
@@ -64,28 +213,284 @@ pub struct DamageEvent {
 }
 impl DamageEvent {
     pub fn update(
-        mut query: Query<(&mut Acceleration, &mut Health, &Team, &Transform)>,
+        mut query: Query<DamageQueryData>,
         mut events: EventReader<DamageEvent>,
         mut effects: EffectCommands,
+        configs: Res<Configs>,
+        mut commands: Commands,
     ) {
         for event in events.read() {
             // Knock back the damager
-            if let Ok((mut acceleration, _health, _team, _transform)) = query.get_mut(event.damager)
+            if let Ok((
+                mut acceleration,
+                _health,
+                _team,
+                _transform,
+                _object,
+                _invulnerable,
+                _flash,
+            )) = query.get_mut(event.damager)
             {
                 *acceleration -= Acceleration(event.velocity.0 * 5.);
             }
-            // Reduce health and set off firework for the damaged.
-            if let Ok((mut acceleration, mut health, &team, &transform)) =
+            // Reduce health, set off firework, and flash the damaged.
+            if let Ok((mut acceleration, mut health, &team, &transform, object, false, flash)) =
                 query.get_mut(event.damaged)
             {
                 health.damage(event.amount);
-                effects.make_fireworks(FireworkSpec {
-                    size: EffectSize::Small,
-                    team,
-                    transform,
-                });
+                effects.make_fireworks(FireworkSpec::new(team, transform, EffectSize::Small));
                 *acceleration += Acceleration(event.velocity.0 * 2.);
+                // Restart an ongoing flash so its original material is kept.
+                if let Some(mut flash) = flash {
+                    flash.timer.reset();
+                } else if let Some(config) = configs.objects.get(object) {
+                    commands
+                        .entity(event.damaged)
+                        .insert(DamageFlash::new(config.damage_flash_seconds));
+                }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{
+        ecs::system::RunSystemOnce,
+        prelude::*,
+        tasks::{ComputeTaskPool, TaskPool},
+        utils::HashMap,
+    };
+    use bevy_hanabi::prelude::EffectAsset;
+
+    use crate::{
+        effects::EffectAssets,
+        objects::{
+            carry::CarryEvent, neighbors::Neighbor, test_world, EnemyNeighbors, ObjectAssets,
+            ObjectSpec,
+        },
+        prelude::*,
+    };
+
+    use super::{DamageEvent, DamageFlash, HealthBar, HealthRegen, Invulnerable};
+
+    #[test]
+    fn test_damage_flash() {
+        let mut world = test_world([]);
+        let shared = world
+            .resource::<ObjectAssets>()
+            .get_team_material(Team::Blue)
+            .primary;
+        let num_materials = world.resource::<Assets<ColorMaterial>>().len();
+        let mut time = Time::<()>::default();
+        time.advance_by(Duration::from_millis(250));
+        world.insert_resource(time);
+
+        let flashing = world
+            .spawn((shared.clone(), Team::Blue, DamageFlash::new(0.5)))
+            .id();
+        let other = world.spawn((shared.clone(), Team::Blue)).id();
+        let material =
+            |world: &World, entity| world.get::<Handle<ColorMaterial>>(entity).unwrap().clone();
+
+        // Halfway through, the flashing object uses the team's white flash material.
+        world.run_system_once(DamageFlash::update);
+        let white = world
+            .resource::<ObjectAssets>()
+            .flash_material(Team::Blue, 1.)
+            .unwrap();
+        assert_eq!(material(&world, flashing), white);
+        assert_eq!(
+            world
+                .resource::<Assets<ColorMaterial>>()
+                .get(&white)
+                .unwrap()
+                .color,
+            Color::WHITE.with_a(Team::COLORS[Team::Blue as usize].a())
+        );
+        assert_eq!(material(&world, other), shared);
+
+        // Once finished, the original material is restored.
+        // Flashing reuses the cached materials instead of adding new ones.
+        world.run_system_once(DamageFlash::update);
+        assert_eq!(material(&world, flashing), shared);
+        assert_eq!(
+            world.resource::<Assets<ColorMaterial>>().len(),
+            num_materials
+        );
+        assert!(world.get::<DamageFlash>(flashing).is_none());
+    }
+
+    #[test]
+    fn test_invulnerable() {
+        ComputeTaskPool::get_or_init(TaskPool::default);
+        let mut world = World::new();
+        world.insert_resource(Configs {
+            objects: ObjectConfigs(HashMap::from_iter([(
+                Object::Worker,
+                ObjectConfig::default(),
+            )])),
+            ..default()
+        });
+        world.init_resource::<Events<DamageEvent>>();
+        world.init_resource::<Events<CarryEvent>>();
+        world.init_resource::<SimRng>();
+        world.init_resource::<Assets<EffectAsset>>();
+        world.init_resource::<EffectAssets>();
+
+        let enemy_bundle = || {
+            (
+                Object::Worker,
+                Team::Red,
+                Velocity(Vec2::splat(100.)),
+                Acceleration::ZERO,
+                Health::default(),
+                Transform::default(),
+            )
+        };
+        let invulnerable = world.spawn((enemy_bundle(), Invulnerable)).id();
+        let vulnerable = world.spawn(enemy_bundle()).id();
+        let neighbor = |entity, distance_squared| Neighbor {
+            entity,
+            object: Object::Worker,
+            delta: Vec2::X,
+            distance_squared,
+        };
+        let worker = world
+            .spawn((
+                Object::Worker,
+                Team::Blue,
+                Objectives::default(),
+                Velocity::ZERO,
+                Acceleration::ZERO,
+                Health::default(),
+                Transform::default(),
+                EnemyNeighbors(vec![neighbor(invulnerable, 1.), neighbor(vulnerable, 4.)]),
+            ))
+            .id();
+
+        // The closer invulnerable enemy is skipped in favor of the vulnerable one.
+        world.run_system_once(Object::update_objective);
+        let objectives = world.get::<Objectives>(worker).unwrap();
+        assert_eq!(objectives.last().get_followed_entity(), Some(vulnerable));
+
+        // With only the invulnerable enemy nearby, nothing is targeted.
+        world.entity_mut(worker).insert((
+            Objectives::default(),
+            EnemyNeighbors(vec![neighbor(invulnerable, 1.)]),
+        ));
+        world.run_system_once(Object::update_objective);
+        let objectives = world.get::<Objectives>(worker).unwrap();
+        assert_eq!(objectives.last(), &Objective::None);
+
+        // Damage events against invulnerable objects are ignored.
+        for damaged in [invulnerable, vulnerable] {
+            world.send_event(DamageEvent {
+                damager: worker,
+                damaged,
+                amount: 1,
+                velocity: Velocity::ZERO,
+            });
+        }
+        world.run_system_once(DamageEvent::update);
+        let full_health = Health::default().health;
+        assert_eq!(
+            world.get::<Health>(invulnerable).unwrap().health,
+            full_health
+        );
+        assert_eq!(
+            world.get::<Health>(vulnerable).unwrap().health,
+            full_health - 1
+        );
+    }
+
+    #[test]
+    fn test_spawn_health_regen() {
+        let mut world = test_world([
+            (
+                Object::Worker,
+                ObjectConfig {
+                    health_regen: 0.5,
+                    ..default()
+                },
+            ),
+            (Object::Plankton, ObjectConfig::default()),
+        ]);
+
+        world.run_system_once(|mut commands: ObjectCommands| {
+            commands.spawn(ObjectSpec {
+                object: Object::Worker,
+                ..default()
+            });
+            commands.spawn(ObjectSpec {
+                object: Object::Plankton,
+                ..default()
+            });
+        });
+
+        // Only objects configured with a regen rate regenerate.
+        let mut query = world.query::<(&Object, Option<&HealthRegen>)>();
+        let regen: HashMap<Object, Option<f32>> = query
+            .iter(&world)
+            .map(|(&object, regen)| (object, regen.map(|regen| regen.rate)))
+            .collect();
+        assert_eq!(regen[&Object::Worker], Some(0.5));
+        assert_eq!(regen[&Object::Plankton], None);
+    }
+
+    #[test]
+    fn test_health_regen() {
+        let mut world = World::new();
+        let mut time = Time::<()>::default();
+        time.advance_by(Duration::from_secs_f32(0.6));
+        world.insert_resource(time);
+
+        let regen = |health: i32| {
+            let mut health = Health {
+                health,
+                max_health: 3,
+                ..default()
+            };
+            health.hit_timer.tick(Duration::from_secs(1));
+            (health, HealthRegen::new(2.))
+        };
+        let damaged = world.spawn(regen(1)).id();
+        let dead = world.spawn(regen(0)).id();
+        let full = world.spawn(regen(3)).id();
+        let in_combat = world.spawn(regen(1)).id();
+        world.get_mut::<Health>(in_combat).unwrap().damage(0);
+
+        // 0.6s at 2/s heals one unit and keeps the remainder.
+        world.run_system_once(HealthRegen::update);
+        let health = |world: &World, entity| world.get::<Health>(entity).unwrap().health;
+        assert_eq!(health(&world, damaged), 2);
+        assert_eq!(health(&world, dead), 0);
+        assert_eq!(health(&world, full), 3);
+        assert_eq!(health(&world, in_combat), 1);
+
+        // Regen caps at max health.
+        world.run_system_once(HealthRegen::update);
+        world.run_system_once(HealthRegen::update);
+        assert_eq!(health(&world, damaged), 3);
+    }
+
+    #[test]
+    fn test_health_bar() {
+        let mut world = test_world([]);
+        let bar = world
+            .spawn((HealthBar, Transform::default(), Visibility::Hidden))
+            .id();
+        let parent = world.spawn(Health::new(4)).add_child(bar).id();
+
+        world.run_system_once(HealthBar::update);
+        assert_eq!(world.get::<Visibility>(bar), Some(&Visibility::Hidden));
+
+        world.get_mut::<Health>(parent).unwrap().damage(1);
+        world.run_system_once(HealthBar::update);
+        assert_eq!(world.get::<Visibility>(bar), Some(&Visibility::Inherited));
+        let scale = world.get::<Transform>(bar).unwrap().scale.x;
+        assert_eq!(scale, HealthBar::WIDTH * 0.75);
+    }
+}
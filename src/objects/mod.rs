@@ -2,19 +2,30 @@ use crate::prelude::*;
 use bevy::prelude::*;
 
 use self::{
-    carry::CarryPlugin, damage::DamagePlugin, neighbors::NeighborsPlugin, object::ObjectPlugin,
-    objective::ObjectivePlugin, plankton::PlanktonPlugin, zooid_head::ZooidHeadPlugin,
+    carry::CarryPlugin,
+    culling::CullingPlugin,
+    damage::{DamageFlash, DamagePlugin},
+    neighbors::NeighborsPlugin,
+    object::ObjectPlugin,
+    objective::ObjectivePlugin,
+    plankton::PlanktonPlugin,
+    resources::ResourcesPlugin,
+    zooid_head::ZooidHeadPlugin,
     zooid_worker::ZooidWorkerPlugin,
 };
 pub use self::{
-    carry::{CarriedBy, Carrier},
+    carry::{CarriedBy, Carrier, FoodCollected},
     commands::{ObjectCommands, ObjectSpec},
     config::{
-        InteractionConfig, InteractionConfigs, ObjectConfig, ObjectConfigs, TestInteractionConfigs,
+        InteractionConfig, InteractionConfigs, ObjectConfig, ObjectConfigs, TargetPriority,
+        TestInteractionConfigs,
     },
-    damage::{DamageEvent, Health},
-    object::Object,
-    objective::{Objective, ObjectiveConfig, ObjectiveDebugger, Objectives},
+    culling::Culled,
+    damage::{DamageEvent, Health, HealthBar, HealthRegen, Invulnerable, Passive},
+    neighbors::{AlliedNeighbors, EnemyNeighbors},
+    object::{GameOverEvent, Object},
+    objective::{Objective, ObjectiveConfig, ObjectiveDebugger, Objectives, StuckTimer},
+    resources::{InsufficientBiomassEvent, TeamResources},
 };
 
 /// Plugin for running zooids simulation.
@@ -23,6 +34,7 @@ impl Plugin for ObjectsPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins((
             CarryPlugin,
+            CullingPlugin,
             NeighborsPlugin,
             ObjectivePlugin,
             ZooidHeadPlugin,
@@ -30,20 +42,26 @@ impl Plugin for ObjectsPlugin {
             PlanktonPlugin,
             ObjectPlugin,
             DamagePlugin,
+            ResourcesPlugin,
         ))
         .init_resource::<ObjectAssets>()
-        .configure_sets(FixedUpdate, SystemStage::get_config());
+        .init_resource::<SimulationState>()
+        .configure_sets(FixedUpdate, SystemStage::get_config())
+        .add_systems(Update, SimulationState::update)
+        .add_systems(FixedPostUpdate, SimulationState::finish_step);
     }
 }
 
 mod carry;
 mod commands;
 mod config;
+mod culling;
 mod damage;
 mod neighbors;
 mod object;
 mod objective;
 mod plankton;
+mod resources;
 mod zooid_head;
 mod zooid_worker;
 
@@ -74,13 +92,24 @@ pub struct TeamMaterials {
     pub primary: Handle<ColorMaterial>,
     pub secondary: Handle<ColorMaterial>,
     pub background: Handle<ColorMaterial>,
+    /// Primary color blended towards white, one material per `DamageFlash` step.
+    pub flash: Vec<Handle<ColorMaterial>>,
 }
 impl TeamMaterials {
     pub fn new(color: Color, assets: &mut Assets<ColorMaterial>) -> Self {
+        let white = Color::WHITE.with_a(color.a());
         Self {
             primary: assets.add(ColorMaterial::from(color)),
             secondary: assets.add(ColorMaterial::from(color.with_a(0.8).with_g(0.8))),
             background: assets.add(ColorMaterial::from(color.with_a(0.3))),
+            flash: (1..=DamageFlash::STEPS)
+                .map(|step| {
+                    let amount = step as f32 / DamageFlash::STEPS as f32;
+                    assets.add(ColorMaterial::from(Color::rgba_from_array(
+                        color.rgba_to_vec4().lerp(white.rgba_to_vec4(), amount),
+                    )))
+                })
+                .collect(),
         }
     }
 }
@@ -89,22 +118,36 @@ impl TeamMaterials {
 #[derive(Resource)]
 pub struct ObjectAssets {
     pub mesh: Handle<Mesh>,
+    pub health_bar_mesh: Handle<Mesh>,
+    pub health_bar_material: Handle<ColorMaterial>,
     team_materials: Vec<TeamMaterials>,
 }
 impl ObjectAssets {
     fn get_team_material(&self, team: Team) -> TeamMaterials {
         self.team_materials.get(team as usize).unwrap().clone()
     }
+
+    /// Returns the team's flash material for the given amount of white, if any.
+    pub fn flash_material(&self, team: Team, amount: f32) -> Option<Handle<ColorMaterial>> {
+        let step = (amount.clamp(0., 1.) * DamageFlash::STEPS as f32).round() as usize;
+        let flash = &self.team_materials.get(team as usize)?.flash;
+        step.checked_sub(1).and_then(|i| flash.get(i)).cloned()
+    }
 }
 impl FromWorld for ObjectAssets {
     fn from_world(world: &mut World) -> Self {
-        let mesh = {
+        let (mesh, health_bar_mesh) = {
             let mut meshes = world.get_resource_mut::<Assets<Mesh>>().unwrap();
-            meshes.add(Mesh::from(Circle::default()))
+            (
+                meshes.add(Mesh::from(Circle::default())),
+                meshes.add(Mesh::from(Rectangle::new(1., 1.))),
+            )
         };
         let mut materials = world.get_resource_mut::<Assets<ColorMaterial>>().unwrap();
         Self {
             mesh,
+            health_bar_mesh,
+            health_bar_material: materials.add(ColorMaterial::from(Color::WHITE.with_a(0.8))),
             team_materials: Team::COLORS
                 .iter()
                 .map(|color| TeamMaterials::new(*color, &mut materials))
@@ -113,6 +156,21 @@ impl FromWorld for ObjectAssets {
     }
 }
 
+/// Returns a world with the resources `ObjectCommands` needs to spawn objects with the given configs.
+#[cfg(test)]
+pub fn test_world(configs: impl IntoIterator<Item = (Object, ObjectConfig)>) -> World {
+    let mut world = World::new();
+    world.insert_resource(Configs {
+        objects: ObjectConfigs(configs.into_iter().collect()),
+        ..default()
+    });
+    world.init_resource::<Assets<Mesh>>();
+    world.init_resource::<Assets<ColorMaterial>>();
+    world.init_resource::<ObjectAssets>();
+    world.init_resource::<Events<CreateWaypointEvent>>();
+    world
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -1,6 +1,8 @@
 use crate::prelude::*;
 use bevy::prelude::*;
 
+use super::Culled;
+
 /// Plugin for running zooids simulation.
 pub struct NeighborsPlugin;
 impl Plugin for NeighborsPlugin {
@@ -17,8 +19,17 @@ pub struct Neighbor {
     pub distance_squared: f32,
 }
 
+impl Neighbor {
+    /// Sort neighbors nearest first.
+    pub fn sort_by_distance(neighbors: &mut [Self]) {
+        neighbors.sort_unstable_by(|a, b| a.distance_squared.total_cmp(&b.distance_squared));
+    }
+}
+
+/// Neighbors on other teams, nearest first.
 #[derive(Component, Deref, DerefMut, Default)]
 pub struct EnemyNeighbors(pub Vec<Neighbor>);
+/// Neighbors on the same team, nearest first.
 #[derive(Component, Deref, DerefMut, Default)]
 pub struct AlliedNeighbors(pub Vec<Neighbor>);
 
@@ -29,33 +40,40 @@ pub struct NeighborsBundle {
     grid_entity: GridEntity,
 }
 
+/// Query for objects that track their neighbors.
+type NeighborsQueryData<'a> = (
+    Entity,
+    &'a mut EnemyNeighbors,
+    &'a mut AlliedNeighbors,
+    &'a Object,
+    &'a Team,
+    &'a GlobalTransform,
+    Has<Culled>,
+);
+
+/// Find each object's neighbors. Culled objects skip the search and have no neighbors.
 pub fn update(
-    mut query: Query<(
-        Entity,
-        &mut EnemyNeighbors,
-        &mut AlliedNeighbors,
-        &Object,
-        &Team,
-        &GlobalTransform,
-    )>,
+    mut query: Query<NeighborsQueryData>,
     others: Query<(&Object, &Team, &GlobalTransform)>,
-    grid: Res<Grid2<EntitySet>>,
+    grid: EntityGrids,
     configs: Res<Configs>,
 ) {
     query.par_iter_mut().for_each(
-        |(entity, mut enemy_neighbors, mut allied_neighbors, object, team, transform)| {
-            let config = &configs.objects[object];
-            let position = transform.translation().xy();
-            let other_entities = grid.get_entities_in_radius(position, config.neighbor_radius);
-
+        |(entity, mut enemy_neighbors, mut allied_neighbors, object, team, transform, culled)| {
             enemy_neighbors.clear();
-            enemy_neighbors.reserve_exact(other_entities.len());
             allied_neighbors.clear();
-            allied_neighbors.reserve_exact(other_entities.len());
+            if culled {
+                return;
+            }
+            let Some(config) = configs.objects.get(object) else {
+                warn_once!("No config for {:?}", object);
+                return;
+            };
+            let position = transform.translation().xy();
 
-            for other_entity in other_entities {
+            grid.for_each_entity_in_radius(position, config.neighbor_radius, &mut |other_entity| {
                 if entity == other_entity {
-                    continue;
+                    return;
                 }
                 let (other_object, other_team, other_transform) = others.get(other_entity).unwrap();
                 let other_position = other_transform.translation().xy();
@@ -63,7 +81,7 @@ pub fn update(
                 let delta = other_position - position;
                 let distance_squared = delta.length_squared();
                 if distance_squared > config.neighbor_radius * config.neighbor_radius {
-                    continue;
+                    return;
                 }
 
                 let neighbor = Neighbor {
@@ -77,7 +95,110 @@ pub fn update(
                 } else {
                     enemy_neighbors.push(neighbor)
                 }
+            });
+
+            // Keep only the nearest allies in dense flocks to bound the work per object.
+            if allied_neighbors.len() > config.max_neighbors {
+                allied_neighbors.select_nth_unstable_by(config.max_neighbors, |a, b| {
+                    a.distance_squared.total_cmp(&b.distance_squared)
+                });
+                allied_neighbors.truncate(config.max_neighbors);
             }
+            // Sort after capping, since sorting every ally costs more than the cap saves.
+            Neighbor::sort_by_distance(&mut allied_neighbors);
+            Neighbor::sort_by_distance(&mut enemy_neighbors);
         },
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{
+        ecs::system::RunSystemOnce,
+        prelude::*,
+        tasks::{ComputeTaskPool, TaskPool},
+        utils::HashMap,
+    };
+
+    use crate::prelude::*;
+
+    use super::{AlliedNeighbors, EnemyNeighbors};
+
+    /// Step a dense 2000 unit flock once, returning the time taken and the most neighbors.
+    fn step_dense_flock(max_neighbors: usize) -> (Duration, usize) {
+        ComputeTaskPool::get_or_init(TaskPool::default);
+        let spec = GridSpec {
+            rows: 64,
+            cols: 64,
+            width: 32.,
+            ..default()
+        };
+        let config = ObjectConfig {
+            neighbor_radius: 128.,
+            max_neighbors,
+            ..default()
+        };
+        let mut world = World::new();
+        world.insert_resource(Configs {
+            objects: ObjectConfigs(HashMap::from_iter([(Object::Worker, config)])),
+            ..default()
+        });
+        let mut grid = Grid2::<EntitySet>::default();
+        grid.resize_with(spec);
+        for i in 0..2000 {
+            let position = Vec2::new((i % 50) as f32, (i / 50) as f32) * 12. - 250.;
+            let entity = world
+                .spawn((
+                    Object::Worker,
+                    Team::Blue,
+                    GlobalTransform::from_translation(position.extend(0.)),
+                    Velocity(Vec2::X),
+                    Acceleration::ZERO,
+                    AlliedNeighbors::default(),
+                    EnemyNeighbors::default(),
+                ))
+                .id();
+            grid.update_entity(entity, None, position);
+        }
+        world.insert_resource(grid);
+
+        let start = std::time::Instant::now();
+        world.run_system_once(super::update);
+        world.run_system_once(Object::update_acceleration);
+        let elapsed = start.elapsed();
+        let mut query = world.query::<&AlliedNeighbors>();
+        for allies in query.iter(&world) {
+            let sorted = allies
+                .windows(2)
+                .all(|pair| pair[0].distance_squared <= pair[1].distance_squared);
+            assert!(sorted, "allies aren't sorted by distance");
+        }
+        let most = query.iter(&world).map(|allies| allies.len()).max();
+        (elapsed, most.unwrap())
+    }
+
+    #[test]
+    fn test_max_neighbors() {
+        let (_, uncapped_most) = step_dense_flock(usize::MAX);
+        let (_, capped_most) = step_dense_flock(32);
+        assert!(uncapped_most > 32);
+        assert_eq!(capped_most, 32);
+    }
+
+    #[test]
+    #[ignore = "benchmark"]
+    fn test_max_neighbors_benchmark() {
+        let (uncapped, uncapped_most) = step_dense_flock(usize::MAX);
+        let (capped, capped_most) = step_dense_flock(32);
+        assert!(
+            capped < uncapped,
+            "2000 units: {:?} with up to {} neighbors, {:?} capped at {}",
+            uncapped,
+            uncapped_most,
+            capped,
+            capped_most
+        );
+    }
+}
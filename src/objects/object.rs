@@ -1,28 +1,38 @@
-use std::f32::consts::PI;
+use std::{f32::consts::PI, sync::mpsc};
 
 use self::effects::{EffectCommands, EffectSize, FireworkSpec};
 
 use super::{
     carry::{CarriedBy, Carrier, CarryEvent},
+    culling::Culled,
+    damage::{Invulnerable, Passive},
     neighbors::{AlliedNeighbors, EnemyNeighbors},
     DamageEvent, InteractionConfig, ObjectSpec,
 };
 use crate::prelude::*;
-use bevy::{ecs::query::QueryData, prelude::*};
+use bevy::{ecs::query::QueryData, prelude::*, utils::HashSet};
 
 /// Plugin for running zooids simulation.
 pub struct ObjectPlugin;
 impl Plugin for ObjectPlugin {
     fn build(&self, app: &mut App) {
-        app.register_type::<Object>().add_systems(
-            FixedUpdate,
-            (
-                Object::update_acceleration.in_set(SystemStage::Compute),
-                Object::update_objective.in_set(SystemStage::Compute),
-                Object::death.in_set(SystemStage::Despawn),
-                ObjectBackground::update.in_set(SystemStage::Compute),
-            ),
-        );
+        app.register_type::<Object>()
+            .add_event::<GameOverEvent>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    Object::update_acceleration.in_set(SystemStage::Compute),
+                    Object::update_objective.in_set(SystemStage::Compute),
+                    Object::clamp_acceleration
+                        .in_set(SystemStage::Apply)
+                        .before(crate::physics::update),
+                    Object::death.in_set(SystemStage::Despawn),
+                    GameOverEvent::update
+                        .in_set(SystemStage::Despawn)
+                        .after(Object::death),
+                    ObjectBackground::update.in_set(SystemStage::Compute),
+                ),
+            );
     }
 }
 
@@ -46,18 +56,6 @@ struct NearestNeighbor {
     pub carrier: Option<Carrier>,
     pub carried_by: Option<CarriedBy>,
 }
-trait NearestNeighborExtension {
-    fn distance_squared(&self) -> f32;
-}
-impl NearestNeighborExtension for Option<NearestNeighbor> {
-    fn distance_squared(&self) -> f32 {
-        if let Some(nearest_neighbor) = self {
-            nearest_neighbor.distance_squared
-        } else {
-            f32::INFINITY
-        }
-    }
-}
 
 #[derive(QueryData)]
 #[query_data(mutable)]
@@ -87,23 +85,68 @@ pub struct UpdateObjectiveNeighborQueryData {
     velocity: &'static Velocity,
     carrier: Option<&'static Carrier>,
     carried_by: Option<&'static CarriedBy>,
+    health: Option<&'static Health>,
+    invulnerable: Has<Invulnerable>,
+    passive: Has<Passive>,
+}
+
+/// Side effect of `Object::update_objective`, computed in parallel and applied serially.
+enum ObjectiveDecision {
+    Attack(Entity),
+    Carry(CarryEvent),
+    Damage(DamageEvent),
 }
 
 impl Object {
+    /// Returns true for objects that don't move on their own, which remain visible once explored.
+    pub fn is_static(self) -> bool {
+        matches!(self, Self::Plankton | Self::Food)
+    }
+
+    /// Clamp the combined acceleration so no single tick causes a large jump.
+    /// Only the magnitude is limited, so the direction of avoidance is preserved.
+    pub fn clamp_acceleration(mut query: Query<(&Self, &mut Acceleration)>, configs: Res<Configs>) {
+        for (object, mut acceleration) in &mut query {
+            let Some(config) = configs.objects.get(object) else {
+                warn_once!("No config for {:?}", object);
+                continue;
+            };
+            acceleration.0 = acceleration.clamp_length_max(config.max_acceleration);
+        }
+    }
+
     pub fn update_acceleration(
-        mut query: Query<UpdateAccelerationQueryData>,
+        mut query: Query<UpdateAccelerationQueryData, Without<Culled>>,
         others: Query<(&Self, &Velocity)>,
         configs: Res<Configs>,
     ) {
         query.par_iter_mut().for_each(|mut object| {
             let mut seaparation_acceleration = Acceleration::ZERO;
             let mut alignment_acceleration = Acceleration::ZERO;
-            let config = &configs.objects[object.object];
+            let mut cohesion_delta = Vec2::ZERO;
+            let mut cohesion_count = 0;
+            // Missing configs skip the object rather than crashing the simulation.
+            let Some(config) = configs.objects.get(object.object) else {
+                warn_once!("No config for {:?}", object.object);
+                return;
+            };
             for neighbor in object.neighbors.iter() {
                 let (other_object, other_velocity) = others.get(neighbor.entity).unwrap();
-                let interaction = &config.interactions[other_object];
+                let Some(interaction) = config.interactions.get(other_object) else {
+                    warn_once!(
+                        "No interaction config for {:?} with {:?}",
+                        object.object,
+                        other_object
+                    );
+                    continue;
+                };
                 let radius_squared = config.neighbor_radius * config.neighbor_radius;
 
+                if other_object == object.object {
+                    cohesion_delta += neighbor.delta;
+                    cohesion_count += 1;
+                }
+
                 // Don't apply neighbor forces when carrying items.
                 if object.carrier.is_none() {
                     seaparation_acceleration += Self::separation_acceleration(
@@ -126,6 +169,16 @@ impl Object {
                     * (1.0 / (object.neighbors.len() as f32))
                     + seaparation_acceleration;
             }
+            let cohesion = config
+                .interactions
+                .get(object.object)
+                .map_or(0., |interaction| interaction.cohesion_acceleration);
+            if cohesion_count > 0 && object.carrier.is_none() && config.neighbor_radius > 0. {
+                // Steer towards the centroid, scaled so the neighbor radius maps to `cohesion`.
+                let centroid_delta = cohesion_delta / cohesion_count as f32;
+                *object.acceleration +=
+                    Acceleration(centroid_delta * cohesion / config.neighbor_radius);
+            }
             let spin_amount = (config.idle_speed * 2. - object.velocity.length_squared()).max(0.);
             let turn_vector = Mat2::from_angle(PI / 8.) * object.velocity.0 * spin_amount;
             *object.acceleration += Acceleration(turn_vector);
@@ -138,73 +191,129 @@ impl Object {
         configs: Res<Configs>,
         mut damage_events: EventWriter<DamageEvent>,
         mut carry_events: EventWriter<CarryEvent>,
+        mut rng: ResMut<SimRng>,
     ) {
-        for mut object in &mut query {
-            let config = &configs.objects[object.object];
+        // Search neighbors in parallel, buffering decisions that need mutable access.
+        let (sender, receiver) = mpsc::channel();
+        query.par_iter().for_each(|object| {
+            let decide = |decision| sender.send((object.entity, decision)).unwrap();
+            let Some(config) = configs.objects.get(object.object) else {
+                warn_once!("No config for {:?}", object.object);
+                return;
+            };
             let mut nearest_neighbor: Option<NearestNeighbor> = None;
+            // Attack target chosen by the target priority, with its score.
+            let mut target: Option<(i32, NearestNeighbor)> = None;
             for neighbor in object.neighbors.iter() {
                 let other = others.get(neighbor.entity).unwrap();
 
-                if neighbor.distance_squared < nearest_neighbor.distance_squared() {
-                    nearest_neighbor = Some(NearestNeighbor {
+                // Never target objects that can't be damaged or shouldn't be engaged.
+                // Neighbors are sorted, so the first targetable one is the nearest.
+                let targetable = !other.invulnerable && !other.passive;
+                if targetable {
+                    let candidate = || NearestNeighbor {
                         distance_squared: neighbor.distance_squared,
                         entity: neighbor.entity,
                         velocity: *other.velocity,
                         object: *other.object,
                         carrier: other.carrier.copied(),
                         carried_by: other.carried_by.cloned(),
-                    });
+                    };
+                    // Neighbors without a config can't be scored, so they are never targeted.
+                    if let Some(other_config) = configs.objects.get(other.object) {
+                        let score = config.target_priority.score(other.health, other_config);
+                        if target.as_ref().is_none_or(|(best, _)| score < *best) {
+                            target = Some((score, candidate()));
+                        }
+                    }
+                    if nearest_neighbor.is_none() {
+                        nearest_neighbor = Some(candidate());
+                    }
                 }
 
                 // Food specific behavior.
-                let radius_squared = config.neighbor_radius * config.neighbor_radius;
                 if *object.object == Object::Food
                     && neighbor.object == Object::Head
-                    && neighbor.distance_squared < radius_squared * 0.1
+                    && config.is_in_dropoff_range(neighbor.distance_squared)
                 {
-                    damage_events.send(DamageEvent {
-                        damager: neighbor.entity,
-                        damaged: object.entity,
-                        amount: 1,
-                        velocity: Velocity::ZERO,
-                    });
+                    if let Some(neighbor_config) = configs.objects.get(&neighbor.object) {
+                        decide(ObjectiveDecision::Damage(DamageEvent {
+                            damager: neighbor.entity,
+                            damaged: object.entity,
+                            amount: neighbor_config.attack_damage(),
+                            velocity: Velocity::ZERO,
+                        }));
+                    }
                 }
             }
-            if let Some(neighbor) = nearest_neighbor {
+            if let Some((_, target)) = target {
                 // An object should only attack a neighbor if that neighbor is not being carried.
                 if object.object.can_attack()
-                    && neighbor.object.can_be_attacked()
+                    && target.object.can_be_attacked()
                     && object.carrier.is_none()
-                    && neighbor.carried_by.is_none()
+                    && target.carried_by.is_none()
                 {
-                    object.objectives.start_attacking(neighbor.entity)
+                    decide(ObjectiveDecision::Attack(target.entity));
                 }
-                let interaction = &config.interactions[&neighbor.object];
+            }
+            if let Some(neighbor) = nearest_neighbor {
+                let Some(interaction) = config.interactions.get(&neighbor.object) else {
+                    warn_once!(
+                        "No interaction config for {:?} with {:?}",
+                        object.object,
+                        neighbor.object
+                    );
+                    return;
+                };
                 if config.is_colliding(neighbor.distance_squared) {
                     // If we can carry
                     if object.object.can_be_carried()
                         && neighbor.object.can_carry()
                         && neighbor.carrier.is_none()
                     {
-                        carry_events.send(CarryEvent {
+                        decide(ObjectiveDecision::Carry(CarryEvent {
                             carrier: neighbor.entity,
                             carried: object.entity,
-                        });
+                        }));
                     }
                     // If we can be damaged this frame.
+                    // The interaction enables damage, the damager's config sets the amount.
                     else if interaction.damage_amount > 0
                         && config.is_damage_velocity(neighbor.velocity.length_squared())
                         && object.health.damageable()
                     {
-                        damage_events.send(DamageEvent {
-                            damager: neighbor.entity,
-                            damaged: object.entity,
-                            amount: interaction.damage_amount,
-                            velocity: neighbor.velocity,
-                        });
+                        if let Some(neighbor_config) = configs.objects.get(&neighbor.object) {
+                            decide(ObjectiveDecision::Damage(DamageEvent {
+                                damager: neighbor.entity,
+                                damaged: object.entity,
+                                amount: neighbor_config.attack_damage(),
+                                velocity: neighbor.velocity,
+                            }));
+                        }
                     }
                 }
             }
+        });
+        drop(sender);
+
+        // Apply decisions serially, ordered by entity so results don't depend on thread timing.
+        let mut decisions: Vec<(Entity, ObjectiveDecision)> = receiver.try_iter().collect();
+        decisions.sort_by_key(|&(entity, _)| entity);
+        for (entity, decision) in decisions {
+            match decision {
+                ObjectiveDecision::Attack(target) => {
+                    let mut object = query.get_mut(entity).unwrap();
+                    // Decisions are only made for objects with a config.
+                    let config = &configs.objects[object.object];
+                    object.objectives.start_attacking(target, config, &mut *rng);
+                }
+                ObjectiveDecision::Carry(event) => {
+                    carry_events.send(event);
+                }
+                ObjectiveDecision::Damage(event) => {
+                    damage_events.send(event);
+                }
+            }
         }
     }
 
@@ -246,23 +355,23 @@ impl Object {
         mut commands: Commands,
         mut object_commands: ObjectCommands,
         mut effect_commands: EffectCommands,
-        mut grid: ResMut<Grid2<EntitySet>>,
+        mut grid: EntityGridsMut,
     ) {
         for (entity, object, grid_entity, health, transform, team) in &mut objects {
             if health.health <= 0 {
                 grid.remove(entity, grid_entity);
                 commands.entity(entity).despawn_recursive();
-                effect_commands.make_fireworks(FireworkSpec {
-                    size: EffectSize::Medium,
-                    transform: *transform,
-                    team: *team,
-                });
+                let size = match object {
+                    Object::Head => EffectSize::Large,
+                    Object::Worker | Object::Plankton | Object::Food => EffectSize::Medium,
+                };
+                effect_commands.make_fireworks(FireworkSpec::new(*team, *transform, size));
                 if object == &Object::Plankton {
                     object_commands.spawn(ObjectSpec {
                         object: Object::Food,
                         position: transform.translation.xy(),
                         ..default()
-                    })
+                    });
                 }
             }
         }
@@ -300,8 +409,12 @@ impl Object {
         )
     }
 
+    /// Squared distances below this are treated as zero to avoid NaN accelerations.
+    const ALIGNMENT_EPSILON: f32 = 1e-6;
+
     /// Alignment acceleration.
     /// Compute the difference between this object's velocity and the other object's velocity.
+    /// Coincident neighbors contribute nothing.
     fn alignment_acceleration(
         distance_squared: f32,
         radius_squared: f32,
@@ -309,6 +422,9 @@ impl Object {
         other_velocity: Velocity,
         config: &InteractionConfig,
     ) -> Acceleration {
+        if distance_squared < Self::ALIGNMENT_EPSILON || radius_squared < Self::ALIGNMENT_EPSILON {
+            return Acceleration::ZERO;
+        }
         let magnitude = (radius_squared - distance_squared) / radius_squared;
         Acceleration((other_velocity.0 - velocity.0) * config.alignment_factor * magnitude)
     }
@@ -329,3 +445,495 @@ impl ObjectBackground {
         }
     }
 }
+
+/// Sent once when at most one team has units left that can attack.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct GameOverEvent {
+    /// The surviving team, or `None` for a draw.
+    pub winner: Option<Team>,
+}
+impl GameOverEvent {
+    /// Check for a single surviving team among all teams that have fielded attackers.
+    pub fn update(
+        objects: Query<(&Object, &Team, &Health)>,
+        mut seen_teams: Local<HashSet<Team>>,
+        mut game_over: Local<bool>,
+        mut events: EventWriter<Self>,
+    ) {
+        let mut alive_teams = HashSet::new();
+        for (object, &team, health) in &objects {
+            if team != Team::None && object.can_attack() && health.health > 0 {
+                alive_teams.insert(team);
+            }
+        }
+        if *game_over {
+            // Start a new game once every attacker is gone, e.g. after loading a scene.
+            if alive_teams.is_empty() {
+                seen_teams.clear();
+                *game_over = false;
+            }
+            return;
+        }
+        seen_teams.extend(alive_teams.iter().copied());
+
+        // Only end the game once at least two teams have fought.
+        if seen_teams.len() < 2 || alive_teams.len() > 1 {
+            return;
+        }
+        let winner = alive_teams.into_iter().next();
+        info!("Game over! Winner: {:?}", winner);
+        *game_over = true;
+        events.send(Self { winner });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{
+        ecs::system::RunSystemOnce,
+        prelude::*,
+        tasks::{ComputeTaskPool, TaskPool},
+        utils::HashMap,
+    };
+    use bevy_hanabi::prelude::EffectAsset;
+
+    use crate::{
+        effects::EffectAssets,
+        objects::{
+            carry::CarryEvent, neighbors::Neighbor, test_world, AlliedNeighbors, DamageEvent,
+            EnemyNeighbors, GameOverEvent, InteractionConfig, ObjectSpec, TargetPriority,
+        },
+        prelude::*,
+    };
+
+    #[test]
+    fn test_alignment_coincident() {
+        ComputeTaskPool::get_or_init(TaskPool::default);
+        for neighbor_radius in [10., 0.] {
+            let mut config = ObjectConfig {
+                neighbor_radius,
+                ..default()
+            };
+            config.interactions.insert(
+                Object::Worker,
+                InteractionConfig {
+                    alignment_factor: 1.,
+                    ..default()
+                },
+            );
+            let mut world = World::new();
+            world.insert_resource(Configs {
+                objects: ObjectConfigs([(Object::Worker, config)].into_iter().collect()),
+                ..default()
+            });
+            let other = world.spawn((Object::Worker, Velocity(Vec2::Y))).id();
+            let entity = world
+                .spawn((
+                    Object::Worker,
+                    Velocity(Vec2::X),
+                    Acceleration::ZERO,
+                    AlliedNeighbors(vec![Neighbor {
+                        entity: other,
+                        object: Object::Worker,
+                        delta: Vec2::ZERO,
+                        distance_squared: 0.,
+                    }]),
+                ))
+                .id();
+            world.run_system_once(Object::update_acceleration);
+
+            // Units at the same position don't align, rather than producing NaN.
+            let acceleration = world.get::<Acceleration>(entity).unwrap();
+            assert!(acceleration.is_finite(), "{:?}", acceleration);
+            assert_eq!(acceleration.0, Vec2::ZERO);
+        }
+    }
+
+    #[test]
+    fn test_missing_interaction_config() {
+        ComputeTaskPool::get_or_init(TaskPool::default);
+        let mut config = ObjectConfig::default();
+        config.interactions.remove(&Object::Plankton);
+        let mut world = World::new();
+        world.insert_resource(Configs {
+            objects: ObjectConfigs([(Object::Worker, config)].into_iter().collect()),
+            ..default()
+        });
+        let neighbor = |entity| {
+            AlliedNeighbors(vec![Neighbor {
+                entity,
+                object: Object::Plankton,
+                delta: Vec2::X,
+                distance_squared: 1.,
+            }])
+        };
+        let plankton = world.spawn((Object::Plankton, Velocity(Vec2::Y))).id();
+        let worker = world
+            .spawn((
+                Object::Worker,
+                Velocity(Vec2::X),
+                Acceleration::ZERO,
+                neighbor(plankton),
+            ))
+            .id();
+        // Plankton has no config at all.
+        let unconfigured = world
+            .spawn((
+                Object::Plankton,
+                Velocity(Vec2::X),
+                Acceleration::ZERO,
+                neighbor(worker),
+            ))
+            .id();
+
+        // Missing configs are skipped instead of panicking.
+        world.run_system_once(Object::update_acceleration);
+        assert!(world.get::<Acceleration>(worker).unwrap().is_finite());
+        assert_eq!(
+            *world.get::<Acceleration>(unconfigured).unwrap(),
+            Acceleration::ZERO
+        );
+    }
+
+    #[test]
+    fn test_clamp_acceleration() {
+        let spec = GridSpec {
+            rows: 10,
+            cols: 10,
+            width: 10.0,
+            ..default()
+        };
+        let mut obstacles = Grid2::<Obstacle>::default();
+        obstacles.resize_with(spec);
+        obstacles[(5, 6)] = Obstacle::Full;
+
+        // Moving quickly into a wall to the right produces a large avoidance force.
+        let velocity = Velocity(Vec2::new(50., 10.));
+        let avoidance = obstacles.obstacles_acceleration(Vec2::new(2., 0.), velocity) * 1000.;
+        assert!(avoidance.length() > 40.);
+
+        let mut world = World::new();
+        let mut configs = Configs::default();
+        configs
+            .objects
+            .insert(Object::Worker, ObjectConfig::default());
+        world.insert_resource(configs);
+        let entity = world.spawn((Object::Worker, avoidance)).id();
+        world.run_system_once(Object::clamp_acceleration);
+
+        let clamped = world.get::<Acceleration>(entity).unwrap();
+        assert!((clamped.length() - ObjectConfig::default().max_acceleration).abs() < 1e-3);
+        assert!(clamped.normalize().abs_diff_eq(avoidance.normalize(), 1e-5));
+    }
+
+    #[test]
+    fn test_target_priority() {
+        ComputeTaskPool::get_or_init(TaskPool::default);
+        let mut world = World::new();
+        world.init_resource::<Events<DamageEvent>>();
+        world.init_resource::<Events<CarryEvent>>();
+        world.init_resource::<SimRng>();
+
+        let mut enemy = |object: Object, health: i32| {
+            world
+                .spawn((
+                    object,
+                    Team::Red,
+                    Velocity::ZERO,
+                    Health {
+                        health,
+                        max_health: 3,
+                        ..default()
+                    },
+                ))
+                .id()
+        };
+        let near = enemy(Object::Worker, 3);
+        let weak = enemy(Object::Worker, 1);
+        let head = enemy(Object::Head, 3);
+        let unscored = enemy(Object::Plankton, 1);
+        let neighbor = |entity, object, distance_squared| Neighbor {
+            entity,
+            object,
+            delta: Vec2::X,
+            distance_squared,
+        };
+        let neighbors = vec![
+            // Plankton has no config here, so it's never targeted.
+            neighbor(unscored, Object::Plankton, 0.5),
+            neighbor(near, Object::Worker, 1.),
+            neighbor(weak, Object::Worker, 4.),
+            neighbor(head, Object::Head, 9.),
+        ];
+
+        for (priority, expected) in [
+            (TargetPriority::Nearest, near),
+            (TargetPriority::LowestHealth, weak),
+            (TargetPriority::HighestThreat, head),
+        ] {
+            let worker_config = ObjectConfig {
+                target_priority: priority,
+                // Stay out of collision range to only test targeting.
+                hit_radius: 0.,
+                ..default()
+            };
+            let head_config = ObjectConfig {
+                attack_damage: 3,
+                ..default()
+            };
+            world.insert_resource(Configs {
+                objects: ObjectConfigs(HashMap::from_iter([
+                    (Object::Worker, worker_config),
+                    (Object::Head, head_config),
+                ])),
+                ..default()
+            });
+            let worker = world
+                .spawn((
+                    Object::Worker,
+                    Team::Blue,
+                    Objectives::default(),
+                    Health::default(),
+                    EnemyNeighbors(neighbors.clone()),
+                ))
+                .id();
+            world.run_system_once(Object::update_objective);
+            let objectives = world.get::<Objectives>(worker).unwrap();
+            assert_eq!(
+                objectives.last().get_followed_entity(),
+                Some(expected),
+                "{:?}",
+                priority
+            );
+        }
+    }
+
+    #[test]
+    fn test_attack_damage() {
+        ComputeTaskPool::get_or_init(TaskPool::default);
+        let mut world = World::new();
+        let mut config = ObjectConfig {
+            attack_damage: 3,
+            ..default()
+        };
+        config
+            .interactions
+            .get_mut(&Object::Worker)
+            .unwrap()
+            .damage_amount = 1;
+        world.insert_resource(Configs {
+            objects: ObjectConfigs(HashMap::from_iter([(Object::Worker, config)])),
+            ..default()
+        });
+        world.init_resource::<Events<DamageEvent>>();
+        world.init_resource::<Events<CarryEvent>>();
+        world.init_resource::<SimRng>();
+        world.init_resource::<Assets<EffectAsset>>();
+        world.init_resource::<EffectAssets>();
+
+        let attacker = world
+            .spawn((
+                Object::Worker,
+                Team::Red,
+                Velocity(Vec2::splat(100.)),
+                Acceleration::ZERO,
+                Health::new(6),
+                Transform::default(),
+            ))
+            .id();
+        let mut health = Health::new(6);
+        health.hit_timer.tick(Duration::from_secs(1));
+        let target = world
+            .spawn((
+                Object::Worker,
+                Team::Blue,
+                Objectives::default(),
+                Velocity::ZERO,
+                Acceleration::ZERO,
+                health,
+                Transform::default(),
+                EnemyNeighbors(vec![Neighbor {
+                    entity: attacker,
+                    object: Object::Worker,
+                    delta: Vec2::X,
+                    distance_squared: 1.,
+                }]),
+            ))
+            .id();
+
+        world.run_system_once(Object::update_objective);
+        world.run_system_once(DamageEvent::update);
+        assert_eq!(world.get::<Health>(target).unwrap().health, 3);
+
+        // Negative damage is clamped so it can't heal.
+        world.resource_mut::<Events<DamageEvent>>().clear();
+        let mut health = world.get_mut::<Health>(target).unwrap();
+        health.hit_timer.tick(Duration::from_secs(1));
+        world
+            .resource_mut::<Configs>()
+            .objects
+            .get_mut(&Object::Worker)
+            .unwrap()
+            .attack_damage = -2;
+        world.run_system_once(Object::update_objective);
+        world.run_system_once(DamageEvent::update);
+        assert_eq!(world.get::<Health>(target).unwrap().health, 3);
+    }
+
+    #[test]
+    fn test_game_over() {
+        let mut world = World::new();
+        world.init_resource::<Events<GameOverEvent>>();
+        let system = world.register_system(GameOverEvent::update);
+        let game_overs = |world: &mut World| {
+            let events = world.resource::<Events<GameOverEvent>>();
+            let mut reader = events.get_reader();
+            let winners: Vec<Option<Team>> =
+                reader.read(events).map(|event| event.winner).collect();
+            world.resource_mut::<Events<GameOverEvent>>().clear();
+            winners
+        };
+
+        let blue = world
+            .spawn((Object::Worker, Team::Blue, Health::new(3)))
+            .id();
+        world.spawn((Object::Plankton, Team::None, Health::new(1)));
+        world.run_system(system).unwrap();
+        // A single team that never had an opponent hasn't won yet.
+        assert_eq!(game_overs(&mut world), vec![]);
+
+        let red = world
+            .spawn((Object::Worker, Team::Red, Health::new(3)))
+            .id();
+        world.spawn((Object::Head, Team::Red, Health::new(6)));
+        world.run_system(system).unwrap();
+        assert_eq!(game_overs(&mut world), vec![]);
+
+        // Red only has a head left, which can't attack.
+        world.get_mut::<Health>(red).unwrap().health = 0;
+        world.run_system(system).unwrap();
+        assert_eq!(game_overs(&mut world), vec![Some(Team::Blue)]);
+
+        // The event is only sent once.
+        world.run_system(system).unwrap();
+        assert_eq!(game_overs(&mut world), vec![]);
+
+        // Once every attacker is gone, a new game starts. It ends in a draw if both teams die.
+        world.get_mut::<Health>(blue).unwrap().health = 0;
+        world.run_system(system).unwrap();
+        assert_eq!(game_overs(&mut world), vec![]);
+        world.get_mut::<Health>(blue).unwrap().health = 3;
+        world.get_mut::<Health>(red).unwrap().health = 3;
+        world.run_system(system).unwrap();
+        assert_eq!(game_overs(&mut world), vec![]);
+        world.get_mut::<Health>(blue).unwrap().health = 0;
+        world.get_mut::<Health>(red).unwrap().health = 0;
+        world.run_system(system).unwrap();
+        assert_eq!(game_overs(&mut world), vec![None]);
+    }
+
+    #[test]
+    fn test_death_spawns_food() {
+        let mut world = test_world([
+            (Object::Plankton, ObjectConfig::default()),
+            (Object::Food, ObjectConfig::default()),
+        ]);
+        world.init_resource::<Assets<EffectAsset>>();
+        world.init_resource::<EffectAssets>();
+        world.init_resource::<Grid2<EntitySet>>();
+
+        let position = Vec2::new(30., 40.);
+        world.spawn((
+            Object::Plankton,
+            GridEntity::default(),
+            Health {
+                health: 0,
+                ..default()
+            },
+            Transform::from_translation(position.extend(0.)),
+            Team::None,
+        ));
+        world.run_system_once(Object::death);
+
+        let mut query = world.query::<(&Object, &Transform, &Velocity)>();
+        let objects: Vec<_> = query.iter(&world).collect();
+        assert_eq!(objects.len(), 1);
+        let (&object, transform, &velocity) = objects[0];
+        assert_eq!(object, Object::Food);
+        assert_eq!(transform.translation.xy(), position);
+        assert_eq!(velocity, Velocity::ZERO);
+
+        // Spawned food can start moving with its own objectives.
+        world.run_system_once(|mut commands: ObjectCommands| {
+            commands.spawn(ObjectSpec {
+                object: Object::Food,
+                velocity: Some(Velocity(Vec2::X)),
+                objectives: Objectives::new(Objective::HoldPosition {
+                    position: Vec2::ZERO,
+                }),
+                ..default()
+            });
+        });
+        let mut query = world.query::<(&Velocity, &Objectives)>();
+        assert!(query.iter(&world).any(|(&velocity, objectives)| {
+            velocity == Velocity(Vec2::X)
+                && matches!(objectives.last(), Objective::HoldPosition { .. })
+        }));
+    }
+
+    #[test]
+    fn test_food_cohesion() {
+        ComputeTaskPool::get_or_init(TaskPool::default);
+        let acceleration = |cohesion: f32| {
+            let mut config = ObjectConfig {
+                neighbor_radius: 10.,
+                ..default()
+            };
+            config.interactions.insert(
+                Object::Food,
+                InteractionConfig {
+                    cohesion_acceleration: cohesion,
+                    ..default()
+                },
+            );
+            let mut world = World::new();
+            world.insert_resource(Configs {
+                objects: ObjectConfigs(HashMap::from_iter([(Object::Food, config)])),
+                ..default()
+            });
+            let neighbor = |delta: Vec2, world: &mut World| {
+                let entity = world.spawn((Object::Food, Velocity::ZERO)).id();
+                Neighbor {
+                    entity,
+                    object: Object::Food,
+                    delta,
+                    distance_squared: delta.length_squared(),
+                }
+            };
+            let neighbors = vec![
+                neighbor(Vec2::new(6., 2.), &mut world),
+                neighbor(Vec2::new(4., -2.), &mut world),
+            ];
+            let food = world
+                .spawn((
+                    Object::Food,
+                    Velocity::ZERO,
+                    Acceleration::ZERO,
+                    AlliedNeighbors(neighbors),
+                ))
+                .id();
+            world.run_system_once(Object::update_acceleration);
+            *world.get::<Acceleration>(food).unwrap()
+        };
+
+        // Food is pulled towards the centroid of nearby food.
+        let cohesion = acceleration(1.).0 - acceleration(0.).0;
+        assert!(
+            cohesion.distance(Vec2::new(0.5, 0.)) < 1e-5,
+            "{:?}",
+            cohesion
+        );
+    }
+}
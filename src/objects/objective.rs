@@ -1,10 +1,10 @@
-use std::time::Duration;
+use std::{ops::Range, time::Duration};
 
 use crate::prelude::*;
 use bevy::{prelude::*, text::Text2dBounds};
 use rand::Rng;
 
-use super::CarriedBy;
+use super::{neighbors::EnemyNeighbors, CarriedBy};
 
 pub struct ObjectivePlugin;
 impl Plugin for ObjectivePlugin {
@@ -16,16 +16,30 @@ impl Plugin for ObjectivePlugin {
             .add_systems(
                 FixedUpdate,
                 (
+                    Objectives::retreat.in_set(SystemStage::PostCompute),
                     Objectives::update
                         .in_set(SystemStage::PostCompute)
-                        .after(NavigationGrid2::update_waypoints),
+                        .after(NavigationGrid2::update_waypoints)
+                        .after(Objectives::retreat),
+                    ObjectiveDebugger::toggle.in_set(SystemStage::PostCompute),
                     ObjectiveDebugger::update
                         .in_set(SystemStage::PostCompute)
-                        .after(Objectives::update),
+                        .after(Objectives::update)
+                        .after(ObjectiveDebugger::toggle),
                 ),
             );
     }
 }
+type ObjectivesQueryData<'a> = (
+    &'a mut Objectives,
+    &'a Object,
+    &'a Team,
+    &'a Transform,
+    &'a Velocity,
+    &'a mut Acceleration,
+    Option<&'a mut StuckTimer>,
+);
+
 #[derive(Debug, Clone, Reflect)]
 pub struct ObjectiveConfig {
     pub repell_radius: f32,
@@ -93,31 +107,54 @@ pub enum Objective {
         frame: u16,
         cooldown: Timer,
     },
+    /// Stay near a position, only engaging enemies that come within range.
+    HoldPosition { position: Vec2 },
+    /// Cycle between points, advancing when within `repell_radius` of the current one.
+    Patrol { points: Vec<Vec2>, index: usize },
+    /// Retreat away from an entity while health is low.
+    Flee(Entity),
 }
 impl Objective {
     /// Given an objective, get the next one (if there should be a next one, else None).
-    pub fn try_attacking(&self, entity: Entity) -> Option<Self> {
+    pub fn try_attacking(
+        &self,
+        entity: Entity,
+        config: &ObjectConfig,
+        rng: &mut impl Rng,
+    ) -> Option<Self> {
         match self {
-            Self::None | Self::FollowEntity(_) => Some(Self::AttackEntity {
+            Self::None
+            | Self::FollowEntity(_)
+            | Self::HoldPosition { .. }
+            | Self::Patrol { .. } => Some(Self::AttackEntity {
                 entity,
                 frame: 0,
-                cooldown: Timer::from_seconds(
-                    Self::attack_delay().as_secs_f32(),
+                cooldown: Timer::new(
+                    Self::attack_delay(config.attack_delay_ms.clone(), rng),
                     TimerMode::Repeating,
                 ),
             }),
-            Self::AttackEntity { .. } => None,
+            Self::AttackEntity { .. } | Self::Flee(_) => None,
         }
     }
 
     /// Gets a random attack delay.
-    pub fn attack_delay() -> Duration {
-        Duration::from_millis(rand::thread_rng().gen_range(0..100))
+    pub fn attack_delay(range_ms: Range<u64>, rng: &mut impl Rng) -> Duration {
+        Self::random_duration(range_ms, rng)
     }
 
     /// Gets a random attack cooldown.
-    pub fn attack_cooldown() -> Duration {
-        Duration::from_millis(rand::thread_rng().gen_range(500..1000))
+    pub fn attack_cooldown(range_ms: Range<u64>, rng: &mut impl Rng) -> Duration {
+        Self::random_duration(range_ms, rng)
+    }
+
+    /// Gets a random duration in the given millisecond range.
+    /// Empty ranges resolve to their start.
+    fn random_duration(range_ms: Range<u64>, rng: &mut impl Rng) -> Duration {
+        if range_ms.is_empty() {
+            return Duration::from_millis(range_ms.start);
+        }
+        Duration::from_millis(rng.gen_range(range_ms))
     }
 
     /// Resolves an objective.
@@ -126,7 +163,8 @@ impl Objective {
         transform: &Transform,
         query: &Query<(&Transform, Option<&Velocity>), Without<CarriedBy>>,
         time: &Time,
-        config: &ObjectiveConfig,
+        config: &ObjectConfig,
+        rng: &mut impl Rng,
     ) -> ResolvedObjective {
         match self {
             Self::None => ResolvedObjective::None,
@@ -158,10 +196,13 @@ impl Objective {
                             Vec2::ZERO
                         };
                     let delta = target_position - position;
-                    if delta.length_squared() < config.attack_radius * config.attack_radius
-                        && cooldown.finished()
+                    let attack_radius = config.objective.attack_radius;
+                    if delta.length_squared() < attack_radius * attack_radius && cooldown.finished()
                     {
-                        cooldown.set_duration(Self::attack_cooldown());
+                        cooldown.set_duration(Self::attack_cooldown(
+                            config.attack_cooldown_ms.clone(),
+                            rng,
+                        ));
                         *frame = 3;
                     }
                     if *frame > 0 {
@@ -177,6 +218,32 @@ impl Objective {
                     ResolvedObjective::None
                 }
             }
+            Self::HoldPosition { position } => ResolvedObjective::HoldPosition {
+                position: *position,
+            },
+            Self::Patrol { points, index } => {
+                if points.is_empty() {
+                    return ResolvedObjective::None;
+                }
+                *index %= points.len();
+                let position = transform.translation.xy();
+                let radius = config.objective.repell_radius;
+                if position.distance_squared(points[*index]) < radius * radius {
+                    *index = (*index + 1) % points.len();
+                }
+                ResolvedObjective::Patrol {
+                    position: points[*index],
+                }
+            }
+            Self::Flee(entity) => {
+                if let Ok((other_transform, _other_velocity)) = query.get(*entity) {
+                    ResolvedObjective::Flee {
+                        threat_position: other_transform.translation.xy(),
+                    }
+                } else {
+                    ResolvedObjective::None
+                }
+            }
         }
     }
 
@@ -185,7 +252,19 @@ impl Objective {
         match self {
             Self::AttackEntity { entity, .. } => Some(*entity),
             Self::FollowEntity(entity) => Some(*entity),
-            Self::None => None,
+            Self::None | Self::HoldPosition { .. } | Self::Patrol { .. } | Self::Flee(_) => None,
+        }
+    }
+
+    /// If this objective is navigating to a fixed position, return that position.
+    pub fn get_target_position(&self) -> Option<Vec2> {
+        match self {
+            Self::Patrol { points, index } => points.get(*index).copied(),
+            Self::None
+            | Self::FollowEntity(_)
+            | Self::AttackEntity { .. }
+            | Self::HoldPosition { .. }
+            | Self::Flee(_) => None,
         }
     }
 }
@@ -205,13 +284,17 @@ impl Objectives {
         Self(vec![Objective::None, objective])
     }
     /// Get the last objective.
+    /// Falls back to Objective::None if the stack was emptied (e.g. by a bad scene file).
     pub fn last(&self) -> &Objective {
-        unsafe { self.0.get_unchecked(self.0.len() - 1) }
+        self.0.last().unwrap_or(&Objective::None)
     }
     /// Get the last objective.
+    /// Restores the bottom Objective::None if the stack was emptied.
     pub fn last_mut(&mut self) -> &mut Objective {
-        let i = self.0.len() - 1;
-        unsafe { self.0.get_unchecked_mut(i) }
+        if self.0.is_empty() {
+            self.0.push(Objective::None);
+        }
+        self.0.last_mut().unwrap()
     }
     /// Resets the objectives.
     pub fn clear(&mut self) {
@@ -219,10 +302,49 @@ impl Objectives {
     }
     /// Push an objective on the stack.
     pub fn push(&mut self, objective: Objective) {
+        debug_assert!(
+            !self.0.is_empty(),
+            "Objectives stack is missing its bottom."
+        );
         self.0.push(objective)
     }
+    /// Queue an objective to run after all others, just above the bottom None objective.
+    pub fn queue(&mut self, objective: Objective) {
+        debug_assert!(
+            !self.0.is_empty(),
+            "Objectives stack is missing its bottom."
+        );
+        self.0.insert(self.0.len().min(1), objective)
+    }
+    /// Get the objective that runs after all others.
+    pub fn last_queued(&self) -> &Objective {
+        self.0.get(1).unwrap_or(&Objective::None)
+    }
+    /// Returns true if another objective is waiting beneath the current one.
+    pub fn has_queued(&self) -> bool {
+        self.0.len() > 2
+    }
+    /// Iterate over all objectives from the bottom of the stack.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Objective> {
+        self.0.iter()
+    }
+    /// Objectives that need navigation, in the order they will run.
+    /// That's the current objective, then any waypoints followed once it's done.
+    pub fn navigation_targets(&self) -> impl Iterator<Item = &Objective> {
+        self.0
+            .iter()
+            .rev()
+            .enumerate()
+            .filter_map(|(i, objective)| {
+                (i == 0 || matches!(objective, Objective::FollowEntity(_))).then_some(objective)
+            })
+    }
     /// Pop an objective, but only if it's not the bottom None objective.
     pub fn pop(&mut self) -> Option<Objective> {
+        debug_assert!(
+            !self.0.is_empty(),
+            "Objectives stack is missing its bottom."
+        );
         if self.0.len() > 1 {
             self.0.pop()
         } else {
@@ -231,35 +353,88 @@ impl Objectives {
     }
 
     // Start attacking
-    pub fn start_attacking(&mut self, entity: Entity) {
-        if let Some(objective) = self.last().try_attacking(entity) {
+    pub fn start_attacking(&mut self, entity: Entity, config: &ObjectConfig, rng: &mut impl Rng) {
+        if let Some(objective) = self.last().try_attacking(entity, config, rng) {
             info!("Start attacking!");
             self.push(objective);
         }
     }
 
+    /// Flee from the nearest enemy while health is below the retreat threshold.
+    /// The flee objective is popped once health recovers or no enemies remain nearby.
+    pub fn retreat(
+        mut query: Query<(&mut Self, &Object, &Health, &EnemyNeighbors)>,
+        configs: Res<Configs>,
+    ) {
+        for (mut objectives, object, health, enemies) in &mut query {
+            let Some(config) = configs.objects.get(object) else {
+                warn_once!("No config for {:?}", object);
+                continue;
+            };
+            let fraction = health.health as f32 / health.max_health.max(1) as f32;
+            let nearest_enemy = enemies.first().map(|neighbor| neighbor.entity);
+            let fleeing = matches!(objectives.last(), Objective::Flee(_));
+            match nearest_enemy {
+                Some(enemy) if fraction < config.retreat_health_fraction => {
+                    if fleeing {
+                        *objectives.last_mut() = Objective::Flee(enemy);
+                    } else {
+                        objectives.push(Objective::Flee(enemy));
+                    }
+                }
+                _ if fleeing => {
+                    objectives.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+
     /// Update acceleration from the current objective.
+    #[allow(clippy::too_many_arguments)]
     pub fn update(
-        mut query: Query<(&mut Self, &Object, &Transform, &Velocity, &mut Acceleration)>,
+        mut query: Query<ObjectivesQueryData>,
         others: Query<(&Transform, Option<&Velocity>), Without<CarriedBy>>,
         configs: Res<Configs>,
         grid_spec: Res<GridSpec>,
         navigation_grid: Res<NavigationGrid2>,
         obstacles_grid: Res<Grid2<Obstacle>>,
         time: Res<Time>,
+        mut rng: ResMut<SimRng>,
     ) {
-        for (mut objectives, object, transform, velocity, mut acceleration) in &mut query {
+        for (mut objectives, object, &team, transform, velocity, mut acceleration, stuck_timer) in
+            &mut query
+        {
             if *object == Object::Food {
                 continue;
             }
-            let config = configs.objects.get(object).unwrap();
+            let Some(config) = configs.objects.get(object) else {
+                warn_once!("No config for {:?}", object);
+                continue;
+            };
             let obstacles_acceleration = obstacles_grid
                 .obstacles_acceleration(transform.translation.xy(), *velocity)
                 * config.obstacle_acceleration;
             *acceleration += obstacles_acceleration;
-            let resolved = objectives.resolve(transform, &others, &time, &config.objective);
-            *acceleration +=
-                resolved.acceleration(transform, *velocity, config, &grid_spec, &navigation_grid);
+            let resolved = objectives.resolve(transform, &others, &time, config, &mut *rng);
+            *acceleration += resolved.acceleration(
+                transform,
+                *velocity,
+                team,
+                config,
+                &grid_spec,
+                &navigation_grid,
+            );
+            if let Some(mut stuck_timer) = stuck_timer {
+                *acceleration += stuck_timer.tick(
+                    time.delta_seconds(),
+                    transform.translation.xy(),
+                    *velocity,
+                    resolved.target_position(),
+                    config,
+                    &mut *rng,
+                );
+            }
         }
     }
 
@@ -270,14 +445,17 @@ impl Objectives {
         transform: &Transform,
         query: &Query<(&Transform, Option<&Velocity>), Without<CarriedBy>>,
         time: &Time,
-        config: &ObjectiveConfig,
+        config: &ObjectConfig,
+        rng: &mut impl Rng,
     ) -> ResolvedObjective {
         while self.last() != &Objective::None {
-            let resolved = self.last_mut().resolve(transform, query, time, config);
+            let resolved = self.last_mut().resolve(transform, query, time, config, rng);
             if resolved != ResolvedObjective::None {
                 return resolved;
             }
-            self.0.pop();
+            if self.pop().is_none() {
+                break;
+            }
         }
         ResolvedObjective::None
     }
@@ -298,13 +476,33 @@ pub enum ResolvedObjective {
         target_position: Vec2,
         frame: u16,
     },
+    /// Entity wants to stay near a position.
+    HoldPosition { position: Vec2 },
+    /// Entity is patrolling, currently towards the given position.
+    Patrol { position: Vec2 },
+    /// Entity is retreating from a threat at the given position.
+    Flee { threat_position: Vec2 },
 }
 impl ResolvedObjective {
+    /// Returns the position this objective is moving towards, if any.
+    pub fn target_position(&self) -> Option<Vec2> {
+        match self {
+            Self::None | Self::Flee { .. } => None,
+            Self::FollowEntity { position, .. }
+            | Self::HoldPosition { position }
+            | Self::Patrol { position } => Some(*position),
+            Self::AttackEntity {
+                target_position, ..
+            } => Some(*target_position),
+        }
+    }
+
     // Returns acceleration for this objective.
     pub fn acceleration(
         &self,
         transform: &Transform,
         velocity: Velocity,
+        team: Team,
         config: &ObjectConfig,
         grid_spec: &GridSpec,
         navigation_grid: &NavigationGrid2,
@@ -319,6 +517,7 @@ impl ResolvedObjective {
                 *target_position,
                 config,
                 velocity,
+                team,
                 grid_spec,
                 navigation_grid,
                 /*slow_factor=*/ 1.0,
@@ -331,43 +530,102 @@ impl ResolvedObjective {
             } => {
                 let delta = *target_position - *position;
                 if *frame > 0 {
-                    Acceleration(delta.normalize() * config.attack_velocity)
+                    Acceleration(delta.normalize_or_zero() * config.attack_velocity)
                 } else {
                     Self::accelerate_to_position(
                         *position,
                         *target_position,
                         config,
                         velocity,
+                        team,
                         grid_spec,
                         navigation_grid,
                         /*slow_factor=*/ 0.5,
-                    ) + Acceleration(delta.normalize() * 0.0)
+                    ) + Acceleration(delta.normalize_or_zero() * 0.0)
                 }
             }
-            // If no objective, slow down.
-            Self::None => {
-                let idle_slow_threshold = config.idle_speed;
-                let velocity_squared = velocity.length_squared();
-                let slow_magnitude =
-                    (velocity_squared - idle_slow_threshold).max(0.) / velocity_squared;
-                let slow_vector = -velocity.0 * slow_magnitude;
-                Acceleration(slow_vector)
+            Self::Patrol {
+                position: target_position,
+            } => Self::accelerate_to_position(
+                position,
+                *target_position,
+                config,
+                velocity,
+                team,
+                grid_spec,
+                navigation_grid,
+                /*slow_factor=*/ 1.0,
+            ),
+            Self::HoldPosition {
+                position: hold_position,
+            } => {
+                let radius = config.objective.repell_radius;
+                let delta = *hold_position - position;
+                if delta.length_squared() > radius * radius {
+                    // Steer back towards the held position.
+                    let acceleration = Acceleration(delta.normalize() * config.nav_flow_factor);
+                    acceleration
+                        + config.objective.slow_force(
+                            velocity,
+                            position,
+                            *hold_position,
+                            acceleration,
+                        )
+                } else {
+                    Self::idle_acceleration(velocity, config)
+                }
             }
+            Self::Flee { threat_position } => {
+                let away = (position - *threat_position).normalize_or_zero();
+                Acceleration(
+                    Self::clamp_to_bounds(position, away, grid_spec) * config.nav_flow_factor,
+                )
+            }
+            // If no objective, slow down.
+            Self::None => Self::idle_acceleration(velocity, config),
+        }
+    }
+
+    /// Removes components of `direction` that would push `position` out of the grid.
+    pub fn clamp_to_bounds(position: Vec2, direction: Vec2, grid_spec: &GridSpec) -> Vec2 {
+        let bounds = grid_spec.world2d_bounds_eps();
+        let mut direction = direction;
+        if (position.x <= bounds.min.x && direction.x < 0.)
+            || (position.x >= bounds.max.x && direction.x > 0.)
+        {
+            direction.x = 0.;
+        }
+        if (position.y <= bounds.min.y && direction.y < 0.)
+            || (position.y >= bounds.max.y && direction.y > 0.)
+        {
+            direction.y = 0.;
         }
+        direction
+    }
+
+    /// Slow down to idle speed.
+    pub fn idle_acceleration(velocity: Velocity, config: &ObjectConfig) -> Acceleration {
+        let idle_slow_threshold = config.idle_speed;
+        let velocity_squared = velocity.length_squared();
+        let slow_magnitude = (velocity_squared - idle_slow_threshold).max(0.) / velocity_squared;
+        let slow_vector = -velocity.0 * slow_magnitude;
+        Acceleration(slow_vector)
     }
 
     // Returns acceleration for following an entity.
+    #[allow(clippy::too_many_arguments)]
     pub fn accelerate_to_position(
         position: Vec2,
         target_position: Vec2,
         config: &ObjectConfig,
         velocity: Velocity,
+        team: Team,
         grid_spec: &GridSpec,
         navigation_grid: &NavigationGrid2,
         slow_factor: f32,
     ) -> Acceleration {
         let target_cell = grid_spec.to_rowcol(target_position);
-        if let Some(nav) = navigation_grid.get(&target_cell) {
+        if let Some(nav) = navigation_grid.get(&(team, target_cell)) {
             let target_cell_position = nav.grid.to_world_position(target_cell);
             let flow_acceleration = nav.grid.flow_acceleration5(position, config);
             flow_acceleration
@@ -403,52 +661,617 @@ impl ResolvedObjective {
     }
 }
 
+/// Accumulates seconds spent barely moving while far from the objective's target.
+#[derive(Component, Debug, Default, Clone, Copy, Deref, DerefMut)]
+pub struct StuckTimer(pub f32);
+impl StuckTimer {
+    /// Seconds an object can be stuck before it is nudged.
+    pub const LIMIT_SECS: f32 = 1.0;
+
+    /// Advance the timer, returning a random perpendicular nudge once stuck for too long.
+    pub fn tick(
+        &mut self,
+        delta_seconds: f32,
+        position: Vec2,
+        velocity: Velocity,
+        target_position: Option<Vec2>,
+        config: &ObjectConfig,
+        rng: &mut impl Rng,
+    ) -> Acceleration {
+        let arrived_radius = config.objective.repell_radius.max(config.hit_radius);
+        let direction = match target_position {
+            Some(target_position)
+                if position.distance_squared(target_position) > arrived_radius * arrived_radius
+                    && velocity.length_squared() < config.stuck_speed * config.stuck_speed =>
+            {
+                (target_position - position).normalize()
+            }
+            _ => {
+                self.0 = 0.;
+                return Acceleration::ZERO;
+            }
+        };
+        self.0 += delta_seconds;
+        if self.0 < Self::LIMIT_SECS {
+            return Acceleration::ZERO;
+        }
+        self.0 = 0.;
+        let side = if rng.gen_bool(0.5) { 1. } else { -1. };
+        Acceleration(direction.perp() * side * config.stuck_nudge)
+    }
+}
+
+/// Text child showing the objectives stack of its parent.
 #[derive(Component)]
 #[component(storage = "SparseSet")]
 pub struct ObjectiveDebugger;
 impl ObjectiveDebugger {
-    #[allow(dead_code)]
+    const FONT_SIZE: f32 = 18.0;
+    /// World scale of the text, so it stays readable next to units.
+    const SCALE: f32 = 0.5;
+    /// World offset from the parent, so the text sits above the unit.
+    const OFFSET: Vec2 = Vec2::new(0., 20.);
+
     pub fn bundle(self) -> impl Bundle {
-        info!("ObjectiveDebugger::bundle");
         (
             Text2dBundle {
                 text: Text {
                     sections: vec![TextSection::new(
                         "Objective",
                         TextStyle {
-                            font_size: 18.0,
+                            font_size: Self::FONT_SIZE,
                             ..default()
                         },
                     )],
                     justify: JustifyText::Center,
                     ..default()
                 },
-                text_2d_bounds: Text2dBounds {
-                    // Wrap text in the rectangle
-                    size: Vec2::new(1., 1.),
-                },
-                // ensure the text is drawn on top of the box
-                transform: Transform::from_translation(Vec3::Z).with_scale(Vec3::new(0.1, 0.1, 1.)),
+                // One line per objective, so don't wrap.
+                text_2d_bounds: Text2dBounds::UNBOUNDED,
+                // Placed by `update` once the parent transform is known.
+                transform: Transform::from_translation(Vec3::Z * zindex::OBJECTIVE_DEBUGGER),
                 ..default()
             },
             self,
         )
     }
 
-    #[allow(dead_code)]
+    /// Short description of an objective, omitting attack timers.
+    pub fn label(objective: &Objective) -> String {
+        match objective {
+            Objective::AttackEntity { entity, .. } => format!("AttackEntity({:?})", entity),
+            _ => format!("{:?}", objective),
+        }
+    }
+
+    pub fn color(objective: &Objective) -> Color {
+        match objective {
+            Objective::AttackEntity { .. } => Color::TOMATO,
+            Objective::FollowEntity(_) => Color::LIME_GREEN,
+            _ => Color::WHITE,
+        }
+    }
+
+    /// Local transform that cancels the parent's rotation and scale, keeping the text upright
+    /// facing the camera at a fixed size above the parent.
+    pub fn local_transform(parent: &Transform) -> Transform {
+        let rotation = parent.rotation.inverse();
+        let scale = parent.scale.recip();
+        let offset = Self::OFFSET.extend(zindex::OBJECTIVE_DEBUGGER);
+        Transform {
+            translation: (rotation * offset) * scale,
+            rotation,
+            scale: scale * Vec2::splat(Self::SCALE).extend(1.),
+        }
+    }
+
+    /// One line per objective, with the top of the stack last.
+    pub fn sections(objectives: &Objectives) -> Vec<TextSection> {
+        let count = objectives.iter().count();
+        objectives
+            .iter()
+            .enumerate()
+            .map(|(i, objective)| {
+                let newline = if i + 1 < count { "\n" } else { "" };
+                TextSection::new(
+                    Self::label(objective) + newline,
+                    TextStyle {
+                        font_size: Self::FONT_SIZE,
+                        color: Self::color(objective),
+                        ..default()
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Attach debuggers to the selected objects, or remove all debuggers if any are shown.
+    pub fn toggle(
+        debuggers: Query<Entity, With<Self>>,
+        selection: Query<(Entity, &Selected), With<Objectives>>,
+        mut control_events: EventReader<ControlEvent>,
+        mut commands: Commands,
+    ) {
+        for control in control_events.read() {
+            if !control.is_pressed(ControlAction::ToggleDebugText) {
+                continue;
+            }
+            if debuggers.is_empty() {
+                for (entity, selected) in &selection {
+                    if selected.is_selected() {
+                        let child = commands.spawn(Self.bundle()).id();
+                        commands.entity(entity).add_child(child);
+                    }
+                }
+            } else {
+                for debugger in &debuggers {
+                    commands.entity(debugger).despawn_recursive();
+                }
+            }
+        }
+    }
+
     pub fn update(
-        mut query: Query<(&mut Text, &Parent), With<Self>>,
-        objectives: Query<&Objective, Without<Self>>,
+        mut query: Query<(&mut Text, &mut Transform, &Parent), With<Self>>,
+        parents: Query<(&Objectives, &Transform), Without<Self>>,
     ) {
-        for (mut text, parent) in query.iter_mut() {
-            let objective = objectives.get(parent.get()).unwrap();
-            *text = Text::from_sections(vec![TextSection::new(
-                format!("{:?}", objective),
-                TextStyle {
-                    font_size: 18.0,
+        for (mut text, mut transform, parent) in query.iter_mut() {
+            if let Ok((objectives, parent_transform)) = parents.get(parent.get()) {
+                text.sections = Self::sections(objectives);
+                *transform = Self::local_transform(parent_transform);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{
+        ecs::system::RunSystemOnce,
+        input::ButtonState,
+        prelude::*,
+        tasks::{ComputeTaskPool, TaskPool},
+        utils::HashMap,
+    };
+
+    use crate::{
+        objects::{
+            carry::CarryEvent, neighbors::Neighbor, test_world, CarriedBy, EnemyNeighbors,
+            HealthRegen,
+        },
+        prelude::*,
+    };
+
+    use super::{Objective, ObjectiveDebugger, Objectives, ResolvedObjective, StuckTimer};
+
+    #[test]
+    fn test_navigation_targets() {
+        let [first, second] = [Entity::from_raw(1), Entity::from_raw(2)];
+        let hold = Objective::HoldPosition {
+            position: Vec2::ZERO,
+        };
+        let mut objectives = Objectives::new(Objective::FollowEntity(first));
+        objectives.push(hold.clone());
+        objectives.queue(Objective::FollowEntity(second));
+
+        // Objectives buried beneath the current one aren't navigated to.
+        objectives.push(Objective::Flee(Entity::PLACEHOLDER));
+        let targets: Vec<&Objective> = objectives.navigation_targets().collect();
+        assert_eq!(
+            targets,
+            vec![
+                &Objective::Flee(Entity::PLACEHOLDER),
+                &Objective::FollowEntity(first),
+                &Objective::FollowEntity(second),
+            ]
+        );
+
+        // The current objective is always navigated to.
+        objectives.pop();
+        assert_eq!(objectives.navigation_targets().next(), Some(&hold));
+    }
+
+    #[test]
+    fn test_objectives_stack() {
+        let mut objectives = Objectives::new(Objective::FollowEntity(Entity::PLACEHOLDER));
+        assert_eq!(
+            objectives.pop(),
+            Some(Objective::FollowEntity(Entity::PLACEHOLDER))
+        );
+        assert_eq!(objectives.pop(), None);
+        assert_eq!(objectives.last(), &Objective::None);
+
+        // An emptied stack behaves as idle instead of reading out of bounds.
+        let mut objectives = Objectives(Vec::new());
+        assert_eq!(objectives.last(), &Objective::None);
+        *objectives.last_mut() = Objective::None;
+        assert_eq!(objectives.0, vec![Objective::None]);
+    }
+
+    #[test]
+    fn test_objective_debugger() {
+        let mut world = World::new();
+        world.init_resource::<Events<ControlEvent>>();
+        let target = world.spawn_empty().id();
+        let mut objectives = Objectives::new(Objective::FollowEntity(target));
+        objectives.start_attacking(target, &ObjectConfig::default(), &mut SimRng::default());
+        let selected = Selected::Selected {
+            child_entity: Entity::PLACEHOLDER,
+        };
+        let unit_transform = Transform::from_xyz(5., 5., 1.)
+            .with_rotation(Quat::from_rotation_z(1.))
+            .with_scale(Vec2::splat(10.).extend(1.));
+        let unit = world
+            .spawn((selected, objectives.clone(), unit_transform))
+            .id();
+        world.spawn((Selected::default(), objectives, Transform::default()));
+        let toggle = |world: &mut World| {
+            world.send_event(ControlEvent {
+                action: ControlAction::ToggleDebugText,
+                state: ButtonState::Pressed,
+                position: Vec2::ZERO,
+                entity: None,
+            });
+            world.run_system_once(ObjectiveDebugger::toggle);
+            world.resource_mut::<Events<ControlEvent>>().clear();
+            let mut query = world.query_filtered::<&Parent, With<ObjectiveDebugger>>();
+            query
+                .iter(world)
+                .map(|parent| parent.get())
+                .collect::<Vec<_>>()
+        };
+
+        // Only selected objects get a debugger.
+        assert_eq!(toggle(&mut world), vec![unit]);
+        world.run_system_once(ObjectiveDebugger::update);
+        let mut query = world.query_filtered::<&Text, With<ObjectiveDebugger>>();
+        let text = query.single(&world);
+        let lines: Vec<&str> = text.sections.iter().map(|s| s.value.trim_end()).collect();
+        let attack = format!("AttackEntity({:?})", target);
+        let follow = format!("FollowEntity({:?})", target);
+        assert_eq!(lines, vec!["None", &follow, &attack]);
+        assert_eq!(text.sections[2].style.color, Color::TOMATO);
+
+        // The text stays upright above the unit regardless of the unit's rotation and scale.
+        let mut query = world.query_filtered::<(Entity, &Transform), With<ObjectiveDebugger>>();
+        let (debugger, &transform) = query.single(&world);
+        let global = unit_transform.mul_transform(transform);
+        assert!(global.rotation.abs_diff_eq(Quat::IDENTITY, 1e-5));
+        assert!(global.scale.abs_diff_eq(Vec3::new(0.5, 0.5, 1.), 1e-5));
+        let offset = Vec3::new(0., 20., zindex::OBJECTIVE_DEBUGGER);
+        assert!(global
+            .translation
+            .abs_diff_eq(unit_transform.translation + offset, 1e-4));
+
+        // Toggling again despawns them and detaches them from their parents.
+        assert_eq!(toggle(&mut world), vec![]);
+        assert!(world.get_entity(debugger).is_none());
+        assert!(world.get::<Children>(unit).is_none_or(|c| c.is_empty()));
+    }
+
+    #[test]
+    fn test_objectives_queue() {
+        let [a, b, c] = [1, 2, 3].map(|i| Objective::FollowEntity(Entity::from_raw(i)));
+        let mut objectives = Objectives::default();
+        objectives.queue(a.clone());
+        assert_eq!(objectives.last(), &a);
+        assert!(!objectives.has_queued());
+
+        // Queued objectives run after the current one, in the order they were queued.
+        objectives.queue(b.clone());
+        objectives.queue(c.clone());
+        assert_eq!(objectives.last(), &a);
+        assert_eq!(objectives.last_queued(), &c);
+        assert!(objectives.has_queued());
+        assert_eq!(objectives.pop(), Some(a));
+        assert_eq!(objectives.pop(), Some(b));
+        assert_eq!(objectives.pop(), Some(c));
+        assert_eq!(objectives.last_queued(), &Objective::None);
+    }
+
+    #[test]
+    fn test_attack_overlapping_target() {
+        let config = ObjectConfig::default();
+        let spec = GridSpec::default();
+        let navigation_grid = NavigationGrid2::default();
+        let position = Vec2::new(5., 5.);
+        for frame in [0, 1] {
+            let objective = ResolvedObjective::AttackEntity {
+                entity: Entity::PLACEHOLDER,
+                position,
+                target_position: position,
+                frame,
+            };
+            // Attacking a unit at the same position doesn't produce NaN.
+            let acceleration = objective.acceleration(
+                &Transform::from_translation(position.extend(0.)),
+                Velocity(Vec2::X),
+                Team::Blue,
+                &config,
+                &spec,
+                &navigation_grid,
+            );
+            assert!(acceleration.is_finite(), "{:?}", acceleration);
+        }
+    }
+
+    #[test]
+    fn test_stuck_timer() {
+        let config = ObjectConfig::default();
+        let mut rng = SimRng::new(0);
+        let mut timer = StuckTimer::default();
+        let target = Some(Vec2::new(100., 0.));
+        let slow = Velocity(Vec2::new(0.01, 0.));
+        let step = StuckTimer::LIMIT_SECS / 4.;
+
+        // Moving objects and objects at their target are never stuck.
+        let moving = Velocity(Vec2::new(5., 0.));
+        assert_eq!(
+            timer.tick(10., Vec2::ZERO, moving, target, &config, &mut rng),
+            Acceleration::ZERO
+        );
+        assert_eq!(
+            timer.tick(10., Vec2::new(99., 0.), slow, target, &config, &mut rng),
+            Acceleration::ZERO
+        );
+        assert_eq!(
+            timer.tick(10., Vec2::ZERO, slow, None, &config, &mut rng),
+            Acceleration::ZERO
+        );
+
+        for _ in 0..3 {
+            let nudge = timer.tick(step, Vec2::ZERO, slow, target, &config, &mut rng);
+            assert_eq!(nudge, Acceleration::ZERO);
+        }
+        let nudge = timer.tick(step, Vec2::ZERO, slow, target, &config, &mut rng);
+        assert!(nudge.x.abs() < 1e-5);
+        assert!((nudge.y.abs() - config.stuck_nudge).abs() < 1e-5);
+        assert_eq!(timer.0, 0.);
+
+        // Moving again resets accumulated time.
+        timer.tick(step, Vec2::ZERO, slow, target, &config, &mut rng);
+        timer.tick(step, Vec2::ZERO, moving, target, &config, &mut rng);
+        assert_eq!(timer.0, 0.);
+    }
+
+    #[test]
+    fn test_retreat() {
+        let mut world = World::new();
+        world.insert_resource(Configs {
+            objects: ObjectConfigs(HashMap::from_iter([(
+                Object::Worker,
+                ObjectConfig {
+                    retreat_health_fraction: 0.5,
                     ..default()
                 },
-            )]);
+            )])),
+            ..default()
+        });
+        let head = world.spawn_empty().id();
+        let enemy = world.spawn_empty().id();
+        let neighbor = |distance: f32, entity| Neighbor {
+            entity,
+            object: Object::Worker,
+            delta: Vec2::X * distance,
+            distance_squared: distance * distance,
+        };
+        let worker = world
+            .spawn((
+                Object::Worker,
+                Health {
+                    health: 1,
+                    max_health: 3,
+                    ..default()
+                },
+                Objectives::new(Objective::FollowEntity(head)),
+                EnemyNeighbors(vec![neighbor(10., enemy), neighbor(20., head)]),
+            ))
+            .id();
+        let last = |world: &World| world.get::<Objectives>(worker).unwrap().last().clone();
+
+        // Low health flees the nearest enemy, and doesn't start attacking while fleeing.
+        world.run_system_once(Objectives::retreat);
+        world.run_system_once(Objectives::retreat);
+        assert_eq!(last(&world), Objective::Flee(enemy));
+        let config = ObjectConfig::default();
+        assert_eq!(
+            last(&world).try_attacking(enemy, &config, &mut SimRng::default()),
+            None
+        );
+
+        // Regenerating health pops the flee objective and resumes the previous one.
+        let mut time = Time::<()>::default();
+        time.advance_by(Duration::from_secs(1));
+        world.insert_resource(time);
+        world
+            .entity_mut(worker)
+            .insert(HealthRegen::new(2.))
+            .get_mut::<Health>()
+            .unwrap()
+            .hit_timer
+            .tick(Duration::from_secs(1));
+        world.run_system_once(HealthRegen::update);
+        world.run_system_once(Objectives::retreat);
+        assert_eq!(last(&world), Objective::FollowEntity(head));
+
+        // Without enemies nearby there is nothing to flee from.
+        world.get_mut::<Health>(worker).unwrap().health = 1;
+        world.get_mut::<EnemyNeighbors>(worker).unwrap().clear();
+        world.run_system_once(Objectives::retreat);
+        assert_eq!(last(&world), Objective::FollowEntity(head));
+
+        // Fleeing steers away from the threat, but never out of the grid.
+        let spec = GridSpec {
+            rows: 10,
+            cols: 10,
+            width: 10.0,
+            ..default()
+        };
+        let navigation = NavigationGrid2::default();
+        let flee = ResolvedObjective::Flee {
+            threat_position: Vec2::new(10., 0.),
+        };
+        let acceleration = |position: Vec2| {
+            let transform = Transform::from_translation(position.extend(0.));
+            flee.acceleration(
+                &transform,
+                Velocity::ZERO,
+                Team::Blue,
+                &config,
+                &spec,
+                &navigation,
+            )
+        };
+        assert_eq!(acceleration(Vec2::ZERO), Acceleration(Vec2::NEG_X));
+        let edge = spec.world2d_bounds_eps().min;
+        assert_eq!(acceleration(Vec2::new(edge.x, 0.)), Acceleration::ZERO);
+    }
+
+    #[test]
+    fn test_hold_position() {
+        let config = ObjectConfig::default();
+        let hold = Vec2::new(100., 0.);
+        let resolved = ResolvedObjective::HoldPosition { position: hold };
+        let spec = GridSpec::default();
+        let navigation = NavigationGrid2::default();
+
+        // Far from the held position, steer back towards it.
+        let acceleration = resolved.acceleration(
+            &Transform::default(),
+            Velocity::ZERO,
+            Team::Blue,
+            &config,
+            &spec,
+            &navigation,
+        );
+        assert!(acceleration.x > 0.);
+        assert_eq!(acceleration.y, 0.);
+
+        // Within range, idle.
+        let velocity = Velocity(Vec2::new(0., 2.));
+        let acceleration = resolved.acceleration(
+            &Transform::from_translation(hold.extend(0.)),
+            velocity,
+            Team::Blue,
+            &config,
+            &spec,
+            &navigation,
+        );
+        assert_eq!(
+            acceleration,
+            ResolvedObjective::idle_acceleration(velocity, &config)
+        );
+
+        // Holding units still engage enemies, and resume holding afterwards.
+        let mut objectives = Objectives::new(Objective::HoldPosition { position: hold });
+        let enemy = Entity::from_raw(1);
+        objectives.start_attacking(enemy, &config, &mut SimRng::default());
+        assert_eq!(objectives.last().get_followed_entity(), Some(enemy));
+        objectives.pop();
+        assert_eq!(
+            objectives.last(),
+            &Objective::HoldPosition { position: hold }
+        );
+    }
+
+    #[test]
+    fn test_patrol() {
+        let mut world = test_world([(Object::Worker, ObjectConfig::default())]);
+        world.run_system_once(
+            |others: Query<(&Transform, Option<&Velocity>), Without<CarriedBy>>,
+             configs: Res<Configs>| {
+                let config = configs.objects.get(&Object::Worker).unwrap();
+                let time = Time::default();
+                let points = vec![Vec2::ZERO, Vec2::new(100., 0.), Vec2::new(0., 100.)];
+                let mut objective = Objective::Patrol {
+                    points: points.clone(),
+                    index: 0,
+                };
+                let mut resolve = |position: Vec2| {
+                    let transform = Transform::from_translation(position.extend(0.));
+                    objective.resolve(&transform, &others, &time, config, &mut SimRng::default())
+                };
+
+                // Far from the current point, keep heading there.
+                assert_eq!(
+                    resolve(Vec2::new(50., 50.)),
+                    ResolvedObjective::Patrol {
+                        position: points[0]
+                    }
+                );
+                // Reaching each point advances to the next, wrapping around.
+                for i in [0, 1, 2, 0] {
+                    let next = points[(i + 1) % points.len()];
+                    assert_eq!(
+                        resolve(points[i]),
+                        ResolvedObjective::Patrol { position: next }
+                    );
+                }
+            },
+        );
+    }
+
+    #[test]
+    fn test_update_objective_many() {
+        ComputeTaskPool::get_or_init(TaskPool::default);
+        let mut world = World::new();
+        world.insert_resource(Configs {
+            objects: ObjectConfigs(HashMap::from_iter([(
+                Object::Worker,
+                ObjectConfig::default(),
+            )])),
+            ..default()
+        });
+        world.init_resource::<Events<DamageEvent>>();
+        world.init_resource::<Events<CarryEvent>>();
+        world.init_resource::<SimRng>();
+
+        let enemies: Vec<Entity> = (0..100)
+            .map(|_| {
+                world
+                    .spawn((Object::Worker, Team::Red, Velocity::ZERO, Health::default()))
+                    .id()
+            })
+            .collect();
+        let mut expected = Vec::new();
+        for i in 0..1000 {
+            // Deterministic pseudo-random distances per neighbor.
+            let mut neighbors: Vec<Neighbor> = (0..10)
+                .map(|j| {
+                    let entity = enemies[(i * 7 + j * 13) % enemies.len()];
+                    Neighbor {
+                        entity,
+                        object: Object::Worker,
+                        delta: Vec2::X,
+                        distance_squared: ((i * 31 + j * 17) % 97) as f32 + 20.,
+                    }
+                })
+                .collect();
+            let nearest = neighbors
+                .iter()
+                .min_by(|a, b| a.distance_squared.total_cmp(&b.distance_squared))
+                .unwrap()
+                .entity;
+            Neighbor::sort_by_distance(&mut neighbors);
+            let worker = world
+                .spawn((
+                    Object::Worker,
+                    Team::Blue,
+                    Objectives::default(),
+                    Velocity::ZERO,
+                    Health::default(),
+                    EnemyNeighbors(neighbors),
+                ))
+                .id();
+            expected.push((worker, nearest));
+        }
+
+        world.run_system_once(Object::update_objective);
+        for (worker, nearest) in expected {
+            let objectives = world.get::<Objectives>(worker).unwrap();
+            assert_eq!(objectives.last().get_followed_entity(), Some(nearest));
         }
     }
 }
@@ -1,4 +1,7 @@
+use std::time::Duration;
+
 use bevy::prelude::*;
+use rand::Rng;
 
 use crate::{objects::ObjectSpec, prelude::*};
 
@@ -7,7 +10,15 @@ use super::ObjectCommands;
 pub struct PlanktonPlugin;
 impl Plugin for PlanktonPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(FixedUpdate, (Plankton::spawn.in_set(SystemStage::Spawn),));
+        app.register_type::<PlanktonSpawner>()
+            .init_resource::<PlanktonSpawner>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    Plankton::spawn.in_set(SystemStage::Spawn),
+                    PlanktonSpawner::update.in_set(SystemStage::Spawn),
+                ),
+            );
     }
 }
 
@@ -22,8 +33,142 @@ impl Plankton {
                     team: Team::None,
                     position: control_event.position,
                     ..default()
-                })
+                });
+            }
+        }
+    }
+}
+
+/// Periodically spawns plankton in random free cells until there are `max_count`.
+#[derive(Resource, Reflect, Clone, Debug)]
+#[reflect(Resource)]
+pub struct PlanktonSpawner {
+    pub interval: Timer,
+    pub max_count: usize,
+}
+impl Default for PlanktonSpawner {
+    fn default() -> Self {
+        Self {
+            interval: Timer::new(Duration::from_secs(1), TimerMode::Repeating),
+            max_count: 100,
+        }
+    }
+}
+impl PlanktonSpawner {
+    /// Number of random cells to try before giving up until the next interval.
+    const MAX_ATTEMPTS: usize = 16;
+
+    /// Returns a random cell that is in bounds, free of obstacles, and unoccupied.
+    pub fn find_free_cell(
+        grid: &dyn EntityGrid,
+        obstacles: &Grid2<Obstacle>,
+        rng: &mut impl Rng,
+    ) -> Option<RowCol> {
+        let spec = grid.spec();
+        if spec.rows < 3 || spec.cols < 3 {
+            return None;
+        }
+        (0..Self::MAX_ATTEMPTS)
+            .map(|_| {
+                (
+                    rng.gen_range(1..spec.rows - 1),
+                    rng.gen_range(1..spec.cols - 1),
+                )
+            })
+            .find(|&rowcol| obstacles[rowcol] == Obstacle::Empty && grid.is_cell_empty(rowcol))
+    }
+
+    /// Spawn one plankton per interval while under `max_count`.
+    pub fn update(
+        mut spawner: ResMut<Self>,
+        time: Res<Time>,
+        plankton: Query<(), With<Plankton>>,
+        grid: EntityGrids,
+        obstacles: Res<Grid2<Obstacle>>,
+        mut rng: ResMut<SimRng>,
+        mut commands: ObjectCommands,
+    ) {
+        if !spawner.interval.tick(time.delta()).just_finished() {
+            return;
+        }
+        if plankton.iter().count() >= spawner.max_count {
+            return;
+        }
+        if let Some(rowcol) = Self::find_free_cell(&*grid, &obstacles, &mut *rng) {
+            commands.spawn(ObjectSpec {
+                object: Object::Plankton,
+                team: Team::None,
+                position: grid.spec().to_world_position(rowcol),
+                ..default()
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{ecs::system::RunSystemOnce, prelude::*, utils::HashSet};
+
+    use crate::{objects::test_world, prelude::*};
+
+    use super::{Plankton, PlanktonSpawner};
+
+    #[test]
+    fn test_plankton_spawner() {
+        let spec = GridSpec {
+            rows: 6,
+            cols: 6,
+            width: 10.0,
+            visualize: false,
+            visualize_navigation: false,
+            ..default()
+        };
+        let mut world = test_world([(Object::Plankton, ObjectConfig::default())]);
+        world.init_resource::<Events<EntityGridEvent>>();
+
+        // Only cells (2, 2) and (2, 3) are free: the rest are walls or occupied.
+        let mut obstacles = Grid2::<Obstacle>::default();
+        obstacles.resize_with(spec.clone());
+        let mut grid = Grid2::<EntitySet>::default();
+        grid.resize_with(spec);
+        for row in 1..5 {
+            for col in 1..5 {
+                obstacles[(row, col)] = Obstacle::Full;
             }
         }
+        obstacles[(2, 2)] = Obstacle::Empty;
+        obstacles[(2, 3)] = Obstacle::Empty;
+        obstacles[(3, 3)] = Obstacle::Empty;
+        grid[(3, 3)].insert(Entity::PLACEHOLDER);
+        world.insert_resource(obstacles);
+        world.insert_resource(grid);
+
+        world.insert_resource(SimRng::new(7));
+        world.insert_resource(PlanktonSpawner {
+            interval: Timer::new(Duration::from_millis(100), TimerMode::Repeating),
+            max_count: 2,
+        });
+        let mut time = Time::<()>::default();
+        time.advance_by(Duration::from_millis(100));
+        world.insert_resource(time);
+
+        // Free cells are found randomly, so allow a few intervals per spawn.
+        // Spawned plankton occupy their cell once the grid is updated.
+        for _ in 0..100 {
+            world.run_system_once(PlanktonSpawner::update);
+            world.run_system_once(GridEntity::update);
+        }
+        let mut query = world.query_filtered::<&GridEntity, With<Plankton>>();
+        let cells: Vec<RowCol> = query
+            .iter(&world)
+            .map(|grid_entity| grid_entity.cell.unwrap())
+            .collect();
+        assert_eq!(cells.len(), 2);
+        assert_eq!(
+            HashSet::from_iter(cells),
+            HashSet::from_iter([(2, 2), (2, 3)])
+        );
     }
 }
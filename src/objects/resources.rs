@@ -0,0 +1,149 @@
+use bevy::prelude::*;
+
+use crate::prelude::*;
+
+/// Plugin for per-team resources spent on spawning.
+/// Workers earn biomass by delivering food to their head.
+pub struct ResourcesPlugin;
+impl Plugin for ResourcesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TeamResources>()
+            .add_event::<InsufficientBiomassEvent>()
+            .add_systems(Startup, ResourcesHud::spawn)
+            .add_systems(
+                Update,
+                (InsufficientBiomassEvent::update, ResourcesHud::update),
+            );
+    }
+}
+
+/// Biomass available to each team.
+#[derive(Resource, Debug, PartialEq)]
+pub struct TeamResources(pub [u32; Team::COUNT]);
+impl Default for TeamResources {
+    fn default() -> Self {
+        Self([Self::STARTING_BIOMASS; Team::COUNT])
+    }
+}
+impl TeamResources {
+    /// Biomass each team starts with.
+    pub const STARTING_BIOMASS: u32 = 20;
+
+    pub fn get(&self, team: Team) -> u32 {
+        self.0[team as usize]
+    }
+    pub fn add(&mut self, team: Team, amount: u32) {
+        self.0[team as usize] += amount;
+    }
+    /// Deduct `cost` from the team, or return false without spending if there isn't enough.
+    pub fn try_spend(&mut self, team: Team, cost: u32) -> bool {
+        let biomass = &mut self.0[team as usize];
+        if *biomass < cost {
+            return false;
+        }
+        *biomass -= cost;
+        true
+    }
+}
+
+/// Sent when a team can't afford a spawn.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct InsufficientBiomassEvent {
+    pub team: Team,
+    pub cost: u32,
+    pub available: u32,
+}
+impl InsufficientBiomassEvent {
+    pub fn message(&self) -> String {
+        format!(
+            "Not enough biomass: need {}, have {}",
+            self.cost, self.available
+        )
+    }
+
+    /// Show the player why their spawn was refused.
+    pub fn update(
+        mut events: EventReader<Self>,
+        mut hud: Query<&mut ResourcesHud>,
+        configs: Res<Configs>,
+    ) {
+        for event in events.read() {
+            if event.team != configs.player_team {
+                continue;
+            }
+            warn!("{}", event.message());
+            for mut hud in &mut hud {
+                hud.message = Some(event.message());
+            }
+        }
+    }
+}
+
+/// Text showing the player's biomass.
+#[derive(Component, Default, Debug)]
+pub struct ResourcesHud {
+    /// Most recent message about a refused spawn.
+    pub message: Option<String>,
+}
+impl ResourcesHud {
+    pub fn spawn(mut commands: Commands) {
+        commands.spawn((
+            Self::default(),
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 18.0,
+                    ..default()
+                },
+            )
+            .with_style(Style {
+                align_self: AlignSelf::FlexStart,
+                ..default()
+            }),
+        ));
+    }
+
+    pub fn text(&self, biomass: u32) -> String {
+        match &self.message {
+            Some(message) => format!("  Biomass: {}\n  {}", biomass, message),
+            None => format!("  Biomass: {}", biomass),
+        }
+    }
+
+    pub fn update(
+        mut query: Query<(&Self, &mut Text)>,
+        resources: Res<TeamResources>,
+        configs: Res<Configs>,
+    ) {
+        for (hud, mut text) in &mut query {
+            let value = hud.text(resources.get(configs.player_team));
+            if let Some(section) = text.sections.first_mut() {
+                if section.value != value {
+                    section.value = value;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    use super::TeamResources;
+
+    #[test]
+    fn test_try_spend() {
+        let mut resources = TeamResources([0, 3, 0]);
+        assert!(resources.try_spend(Team::Blue, 2));
+        assert_eq!(resources.get(Team::Blue), 1);
+
+        // Spawns that can't be afforded cost nothing.
+        assert!(!resources.try_spend(Team::Blue, 2));
+        assert_eq!(resources.get(Team::Blue), 1);
+        assert!(resources.try_spend(Team::Red, 0));
+
+        resources.add(Team::Blue, 4);
+        assert_eq!(resources.get(Team::Blue), 5);
+    }
+}
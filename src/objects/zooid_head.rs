@@ -1,9 +1,9 @@
-use crate::prelude::*;
+use crate::{config::StartingUnits, prelude::*};
 use bevy::prelude::*;
 use bevy::utils::{Entry, HashMap, HashSet};
 
 use super::Object;
-use super::{ObjectCommands, ObjectSpec, Team};
+use super::{InsufficientBiomassEvent, ObjectCommands, ObjectSpec, Team, TeamResources};
 
 pub struct ZooidHeadPlugin;
 impl Plugin for ZooidHeadPlugin {
@@ -12,6 +12,7 @@ impl Plugin for ZooidHeadPlugin {
             FixedUpdate,
             (
                 ZooidHead::spawn.in_set(SystemStage::Spawn),
+                ZooidHead::spawn_starting_units.in_set(SystemStage::Spawn),
                 ZooidHead::spawn_zooids.in_set(SystemStage::Spawn),
                 ZooidHead::despawn_zooids.in_set(SystemStage::Despawn),
                 NearestZooidHead::update.in_set(SystemStage::PreCompute),
@@ -42,31 +43,99 @@ impl ZooidHead {
         }
     }
 
+    /// Spawn the configured starting heads once a scene provides them.
+    /// If a new scene changes the starting units, the previous starting heads are replaced.
+    pub fn spawn_starting_units(
+        mut commands: Commands,
+        mut object_commands: ObjectCommands,
+        previous: Query<(Entity, &GridEntity), With<StartingUnit>>,
+        mut grid: EntityGridsMut,
+        configs: Res<Configs>,
+        mut spawned: Local<StartingUnits>,
+    ) {
+        if !configs.is_changed() || configs.starting_units == *spawned {
+            return;
+        }
+        for (entity, grid_entity) in &previous {
+            grid.remove(entity, grid_entity);
+            commands.entity(entity).despawn_recursive();
+        }
+        *spawned = configs.starting_units.clone();
+        for head in &spawned.heads {
+            if let Some(entity) = object_commands.spawn(ObjectSpec {
+                object: Object::Head,
+                position: head.position,
+                team: head.team,
+                ..default()
+            }) {
+                commands.entity(entity).insert(StartingUnit);
+            }
+        }
+    }
+
     /// System to spawn zooids on Z key.
+    /// While the key is held, zooids keep spawning every `spawn_interval` seconds.
+    /// Each zooid costs its team `spawn_cost` biomass, and is refused if the team can't afford it.
+    /// Refusals are reported once per team for each press.
+    #[allow(clippy::too_many_arguments)]
     pub fn spawn_zooids(
         query: Query<(&Self, Entity, &Transform, &Velocity, &Team)>,
         mut commands: ObjectCommands,
         configs: Res<Configs>,
         mut control_events: EventReader<ControlEvent>,
+        mut spawn_timer: Local<Option<Timer>>,
+        time: Res<Time>,
+        mut resources: ResMut<TeamResources>,
+        mut insufficient_events: EventWriter<InsufficientBiomassEvent>,
+        mut refused: Local<HashSet<Team>>,
     ) {
-        let config = configs.objects.get(&Object::Worker).unwrap();
+        let Some(config) = configs.objects.get(&Object::Worker) else {
+            warn_once!("No config for {:?}", Object::Worker);
+            return;
+        };
+        let mut num_spawns = 0;
         for control_event in control_events.read() {
             if control_event.is_pressed(ControlAction::SpawnZooid) {
-                for (_head, head_id, transform, velocity, team) in &query {
-                    let num_zooids = 1;
-                    for i in 1..=num_zooids {
-                        let zindex = zindex::ZOOIDS_MIN
-                            + (i as f32) * 0.00001 * (zindex::ZOOIDS_MAX - zindex::ZOOIDS_MIN);
-                        let velocity: Vec2 = Vec2::Y * config.spawn_velocity + velocity.0;
-                        commands.spawn(ObjectSpec {
-                            position: transform.translation.xy() + velocity,
-                            velocity: Some(Velocity(velocity)),
-                            team: *team,
-                            zindex,
-                            objectives: Objectives::new(Objective::FollowEntity(head_id)),
-                            ..default()
-                        });
+                num_spawns += 1;
+                *spawn_timer = Some(Timer::from_seconds(
+                    config.spawn_interval,
+                    TimerMode::Repeating,
+                ));
+                refused.clear();
+            } else if control_event.is_released(ControlAction::SpawnZooid) {
+                *spawn_timer = None;
+            }
+        }
+        if let Some(timer) = spawn_timer.as_mut() {
+            timer.tick(time.delta());
+            num_spawns += timer.times_finished_this_tick();
+        }
+
+        for _ in 0..num_spawns {
+            for (_head, head_id, transform, velocity, team) in &query {
+                let num_zooids = 1;
+                for i in 1..=num_zooids {
+                    if !resources.try_spend(*team, config.spawn_cost) {
+                        if refused.insert(*team) {
+                            insufficient_events.send(InsufficientBiomassEvent {
+                                team: *team,
+                                cost: config.spawn_cost,
+                                available: resources.get(*team),
+                            });
+                        }
+                        continue;
                     }
+                    let zindex = zindex::ZOOIDS_MIN
+                        + (i as f32) * 0.00001 * (zindex::ZOOIDS_MAX - zindex::ZOOIDS_MIN);
+                    let velocity: Vec2 = Vec2::Y * config.spawn_velocity + velocity.0;
+                    commands.spawn(ObjectSpec {
+                        position: transform.translation.xy() + velocity,
+                        velocity: Some(Velocity(velocity)),
+                        team: *team,
+                        zindex,
+                        objectives: Objectives::new(Objective::FollowEntity(head_id)),
+                        ..default()
+                    });
                 }
             }
         }
@@ -76,7 +145,7 @@ impl ZooidHead {
     pub fn despawn_zooids(
         mut objects: Query<(Entity, &GridEntity, &Object, &mut Objectives)>,
         mut commands: Commands,
-        mut grid: ResMut<Grid2<EntitySet>>,
+        mut grid: EntityGridsMut,
         keyboard_input: Res<ButtonInput<KeyCode>>,
     ) {
         if !keyboard_input.just_pressed(KeyCode::KeyD) {
@@ -93,6 +162,10 @@ impl ZooidHead {
     }
 }
 
+/// Marks heads spawned from `Configs::starting_units`.
+#[derive(Component, Debug, Default)]
+pub struct StartingUnit;
+
 #[derive(Component, Debug, Default, Reflect)]
 #[reflect(Component)]
 pub struct NearestZooidHead {
@@ -134,3 +207,141 @@ impl NearestZooidHead {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{ecs::system::RunSystemOnce, input::ButtonState, prelude::*};
+
+    use crate::{
+        config::{StartingHead, StartingUnits},
+        objects::{
+            object::ObjectBackground, test_world, InsufficientBiomassEvent, ObjectSpec,
+            TeamResources,
+        },
+        prelude::*,
+    };
+
+    use super::{StartingUnit, ZooidHead};
+
+    #[test]
+    fn test_spawn_starting_units() {
+        let mut world = test_world([(Object::Head, ObjectConfig::default())]);
+        let head = |team, x| StartingHead {
+            team,
+            position: Vec2::new(x, 0.),
+        };
+        world.resource_mut::<Configs>().starting_units = StartingUnits {
+            heads: vec![head(Team::Blue, -100.), head(Team::Red, 100.)],
+        };
+        world.init_resource::<Grid2<EntitySet>>();
+        let mut schedule = Schedule::default();
+        schedule.add_systems(ZooidHead::spawn_starting_units);
+        let heads = |world: &mut World| {
+            let mut query =
+                world.query_filtered::<(Entity, &Team, &Objectives, &Children), With<ZooidHead>>();
+            query
+                .iter(world)
+                .map(|(entity, &team, objectives, children)| {
+                    // Starting heads are set up like manually spawned ones.
+                    assert_eq!(objectives.last(), &Objective::FollowEntity(entity));
+                    assert!(children
+                        .iter()
+                        .any(|&child| world.get::<ObjectBackground>(child).is_some()));
+                    team
+                })
+                .collect::<Vec<_>>()
+        };
+
+        schedule.run(&mut world);
+        assert_eq!(heads(&mut world), vec![Team::Blue, Team::Red]);
+        assert_eq!(world.resource::<Events<CreateWaypointEvent>>().len(), 2);
+
+        // Other config changes don't spawn the starting units again.
+        schedule.run(&mut world);
+        world.resource_mut::<Configs>().sfx_volume = 0.5;
+        schedule.run(&mut world);
+        assert_eq!(heads(&mut world).len(), 2);
+
+        // A scene with different starting units replaces the previous starting heads,
+        // but keeps heads spawned during play.
+        world.run_system_once(|mut commands: ObjectCommands| {
+            commands.spawn(ObjectSpec {
+                object: Object::Head,
+                team: Team::Blue,
+                ..default()
+            });
+        });
+        world.resource_mut::<Configs>().starting_units.heads = vec![head(Team::Red, 0.)];
+        schedule.run(&mut world);
+        let mut teams = heads(&mut world);
+        teams.sort_by_key(|&team| team as usize);
+        assert_eq!(teams, vec![Team::Blue, Team::Red]);
+        let mut starting = world.query_filtered::<&Team, With<StartingUnit>>();
+        assert_eq!(starting.iter(&world).collect::<Vec<_>>(), vec![&Team::Red]);
+    }
+
+    #[test]
+    fn test_spawn_zooids_insufficient_biomass() {
+        let mut world = test_world([(
+            Object::Worker,
+            ObjectConfig {
+                spawn_cost: 5,
+                spawn_interval: 0.1,
+                ..default()
+            },
+        )]);
+        world.init_resource::<Events<ControlEvent>>();
+        world.init_resource::<Events<InsufficientBiomassEvent>>();
+        world.init_resource::<Time>();
+        let mut resources = TeamResources::default();
+        resources.0[Team::Blue as usize] = 5;
+        world.insert_resource(resources);
+        world.spawn((ZooidHead, Transform::default(), Velocity::ZERO, Team::Blue));
+        let spawn_zooids = world.register_system(ZooidHead::spawn_zooids);
+        let step = |world: &mut World, state: Option<ButtonState>| {
+            if let Some(state) = state {
+                world.send_event(ControlEvent {
+                    action: ControlAction::SpawnZooid,
+                    state,
+                    position: Vec2::ZERO,
+                    entity: None,
+                });
+            }
+            world
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_millis(100));
+            world.run_system(spawn_zooids).unwrap();
+            world.resource_mut::<Events<ControlEvent>>().clear();
+        };
+
+        // The first zooid is affordable, then holding the key is refused with one report.
+        step(&mut world, Some(ButtonState::Pressed));
+        for _ in 0..3 {
+            step(&mut world, None);
+        }
+        let mut workers = world.query::<&Object>();
+        assert_eq!(
+            workers
+                .iter(&world)
+                .filter(|&&o| o == Object::Worker)
+                .count(),
+            1
+        );
+        assert_eq!(world.resource::<TeamResources>().get(Team::Blue), 0);
+        assert_eq!(
+            world.resource::<Events<InsufficientBiomassEvent>>().len(),
+            1
+        );
+
+        // Pressing again reports again.
+        step(&mut world, Some(ButtonState::Released));
+        step(&mut world, Some(ButtonState::Pressed));
+        step(&mut world, None);
+        assert_eq!(
+            world.resource::<Events<InsufficientBiomassEvent>>().len(),
+            2
+        );
+    }
+}
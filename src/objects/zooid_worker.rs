@@ -3,8 +3,7 @@ use bevy::prelude::*;
 use crate::prelude::*;
 
 use super::ObjectSpec;
-#[allow(unused_imports)]
-use super::{objective::ObjectiveDebugger, Object, ObjectAssets, Team, TeamMaterials};
+use super::{Object, Team};
 
 pub struct ZooidWorkerPlugin;
 impl Plugin for ZooidWorkerPlugin {
@@ -1,5 +1,9 @@
 use crate::prelude::*;
-use bevy::{prelude::*, utils::HashMap};
+use bevy::{
+    prelude::*,
+    transform::TransformSystem,
+    utils::{HashMap, HashSet},
+};
 use derive_more::{Add, AddAssign, Sub, SubAssign};
 use std::ops::Mul;
 
@@ -11,7 +15,27 @@ impl Plugin for PhysicsPlugin {
             .register_type::<HashMap<PhysicsMaterialType, PhysicsMaterial>>()
             .register_type::<PhysicsMaterial>()
             .register_type::<PhysicsMaterials>()
-            .add_systems(FixedUpdate, update.in_set(SystemStage::Apply));
+            .register_type::<Drag>()
+            .register_type::<CurrentsSpec>()
+            .register_type::<Vec<(RowCol, Vec2)>>()
+            .register_type::<(RowCol, Vec2)>()
+            .init_resource::<CurrentsSpec>()
+            .init_resource::<CurrentField>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    CurrentField::update.in_set(SystemStage::PreCompute),
+                    update.in_set(SystemStage::Apply),
+                    FaceVelocity::update
+                        .in_set(SystemStage::Apply)
+                        .after(update),
+                    SpawnRamp::update.in_set(SystemStage::PostApply),
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                Upright::update.before(TransformSystem::TransformPropagate),
+            );
     }
 }
 
@@ -40,6 +64,12 @@ impl Mul<f32> for Velocity {
         Self(self.0.mul(rhs))
     }
 }
+impl Mul<Vec2> for Velocity {
+    type Output = Velocity;
+    fn mul(self, rhs: Vec2) -> Self::Output {
+        Self(self.0.mul(rhs))
+    }
+}
 
 /// Tracks new velocity per entity, which can be used for double-buffering
 /// velocity updates.
@@ -67,28 +97,210 @@ impl Mul<f32> for Acceleration {
         Self(self.0.mul(rhs))
     }
 }
+impl Mul<Vec2> for Acceleration {
+    type Output = Acceleration;
+    fn mul(self, rhs: Vec2) -> Self::Output {
+        Self(self.0.mul(rhs))
+    }
+}
+
+/// Fraction of velocity kept along each axis per step, for anisotropic friction.
+/// `Vec2::ONE` applies no drag.
+#[derive(Component, Debug, Clone, Copy, Deref, DerefMut, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct Drag(pub Vec2);
+impl Default for Drag {
+    fn default() -> Self {
+        Self(Vec2::ONE)
+    }
+}
+
+/// Ramps max velocity up from a fraction of the material's max velocity after spawning.
+/// This lets separation resolve gently when many objects spawn on top of each other.
+#[derive(Component, Debug, Deref, DerefMut)]
+pub struct SpawnRamp(pub Timer);
+impl Default for SpawnRamp {
+    fn default() -> Self {
+        Self(Timer::from_seconds(Self::DURATION_SECS, TimerMode::Once))
+    }
+}
+impl SpawnRamp {
+    pub const DURATION_SECS: f32 = 0.3;
+    pub const START_FACTOR: f32 = 0.2;
+
+    /// Returns the fraction of max velocity currently allowed.
+    pub fn factor(&self) -> f32 {
+        Self::START_FACTOR + (1. - Self::START_FACTOR) * self.fraction()
+    }
+
+    /// Tick spawn ramps and remove them once finished.
+    pub fn update(mut query: Query<(Entity, &mut Self)>, time: Res<Time>, mut commands: Commands) {
+        for (entity, mut ramp) in &mut query {
+            ramp.tick(time.delta());
+            if ramp.finished() {
+                commands.entity(entity).remove::<Self>();
+            }
+        }
+    }
+}
+
+/// Rotates an entity to face its direction of travel.
+/// Children such as background meshes inherit the rotation, unless they are `Upright`.
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct FaceVelocity;
+impl FaceVelocity {
+    /// Entities slower than this keep their current heading.
+    pub const MIN_SPEED: f32 = 0.1;
+
+    /// Turn towards the velocity direction, smoothed by the physics material.
+    pub fn update(
+        mut query: Query<(&mut Transform, &Velocity, &PhysicsMaterialType), With<Self>>,
+        materials: Res<PhysicsMaterials>,
+    ) {
+        for (mut transform, velocity, material_type) in &mut query {
+            if velocity.length_squared() < Self::MIN_SPEED * Self::MIN_SPEED {
+                continue;
+            }
+            let material = materials.get(material_type).unwrap();
+            let heading = Quat::from_rotation_z(-velocity.0.angle_between(Vec2::Y));
+            transform.rotation = heading.slerp(transform.rotation, material.rotation_smoothing);
+        }
+    }
+}
+
+/// Keeps a child of a `FaceVelocity` entity unrotated, such as a health bar or highlight.
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct Upright;
+impl Upright {
+    /// Cancel the parent's rotation, keeping the child's offset fixed in world space.
+    pub fn update(
+        mut query: Query<(&mut Transform, &Parent), With<Self>>,
+        parents: Query<&Transform, (With<FaceVelocity>, Without<Self>)>,
+    ) {
+        for (mut transform, parent) in &mut query {
+            let Ok(parent_transform) = parents.get(parent.get()) else {
+                continue;
+            };
+            let offset = transform.rotation.inverse() * transform.translation;
+            transform.rotation = parent_transform.rotation.inverse();
+            transform.translation = transform.rotation * offset;
+        }
+    }
+}
+
+/// Per-cell currents loaded from the scene, added on top of `Configs.current`.
+#[derive(Resource, Default, Deref, DerefMut, Reflect, Clone, Debug)]
+#[reflect(Resource)]
+pub struct CurrentsSpec(pub Vec<(RowCol, Vec2)>);
+
+/// World-space flow pushing every moving object, like an ocean current.
+#[derive(Resource, Default, Debug)]
+pub struct CurrentField {
+    pub uniform: Vec2,
+    pub cells: Grid2<Vec2>,
+}
+impl CurrentField {
+    /// Rebuild the field when the configured currents or the grid change.
+    pub fn update(
+        mut field: ResMut<Self>,
+        configs: Res<Configs>,
+        currents: Res<CurrentsSpec>,
+        spec: Res<GridSpec>,
+    ) {
+        if !(configs.is_changed() || currents.is_changed() || spec.is_changed()) {
+            return;
+        }
+        field.uniform = configs.current;
+        field.cells.resize_with(spec.clone());
+        field.cells.cells.fill(Vec2::ZERO);
+        for &((row, col), current) in currents.iter() {
+            if row < spec.rows && col < spec.cols {
+                field.cells[(row, col)] = current;
+            }
+        }
+    }
+
+    /// Current at a position.
+    /// Components that would push past `bounds` are dropped, so objects aren't pinned to the edges.
+    pub fn acceleration(&self, position: Vec2, bounds: &Aabb2) -> Vec2 {
+        let cell = self
+            .cells
+            .try_to_rowcol(position)
+            .and_then(|rowcol| self.cells.get(rowcol))
+            .copied()
+            .unwrap_or_default();
+        let mut current = self.uniform + cell;
+        let next = position + current;
+        if next.x < bounds.min.x || next.x > bounds.max.x {
+            current.x = 0.;
+        }
+        if next.y < bounds.min.y || next.y > bounds.max.y {
+            current.y = 0.;
+        }
+        current
+    }
+}
+
+/// Query for objects moved by physics.
+type PhysicsQueryData<'a> = (
+    Entity,
+    &'a mut Transform,
+    &'a mut Velocity,
+    &'a mut Acceleration,
+    &'a PhysicsMaterialType,
+    Option<&'a SpawnRamp>,
+    Option<&'a Drag>,
+);
 
 /// Apply velocity changes.
+/// Non-finite velocities and positions are reset so one bad object can't corrupt the grid.
 pub fn update(
-    mut query: Query<(
-        &mut Transform,
-        &mut Velocity,
-        &mut Acceleration,
-        &PhysicsMaterialType,
-    )>,
+    mut query: Query<PhysicsQueryData>,
     materials: Res<PhysicsMaterials>,
     grid_spec: Res<GridSpec>,
     obstacles: Res<Grid2<Obstacle>>,
+    current: Option<Res<CurrentField>>,
+    mut reported: Local<HashSet<Entity>>,
 ) {
-    for (mut transform, mut velocity, mut acceleration, material_type) in &mut query {
+    let bounds = grid_spec.world2d_bounds_eps();
+    for (entity, mut transform, mut velocity, mut acceleration, material_type, spawn_ramp, drag) in
+        &mut query
+    {
         let material = materials.get(material_type).unwrap();
         let prev_velocity = *velocity;
+        let prev_translation = transform.translation;
 
         velocity.0 += acceleration.0;
-        let overflow = velocity.length_squared() / (material.max_velocity.powi(2)) * 0.1;
-        velocity.0 = velocity.clamp_length_max(material.max_velocity);
-        velocity.0 *= overflow.clamp(1.0, 10.0);
+        if let Some(current) = &current {
+            velocity.0 += current.acceleration(transform.translation.xy(), &bounds);
+        }
+        // Smooth before clamping, so smoothing can't carry a launch velocity past the limits.
         velocity.0 = velocity.lerp(prev_velocity.0, material.velocity_smoothing);
+        let mut min_velocity = material.min_velocity;
+        if let Some(spawn_ramp) = spawn_ramp {
+            // Don't allow overflow while ramping up.
+            velocity.0 = velocity.clamp_length_max(material.max_velocity * spawn_ramp.factor());
+            min_velocity *= spawn_ramp.factor();
+        } else {
+            let overflow = velocity.length_squared() / (material.max_velocity.powi(2)) * 0.1;
+            velocity.0 = velocity.clamp_length_max(material.max_velocity);
+            velocity.0 *= overflow.clamp(1.0, 10.0);
+        }
+        if velocity.0 != Vec2::ZERO {
+            velocity.0 = velocity.clamp_length_min(min_velocity);
+        }
+        if let Some(drag) = drag {
+            *velocity = *velocity * drag.0;
+        }
+        if !velocity.is_finite() || !prev_translation.is_finite() {
+            if reported.insert(entity) {
+                warn!("Resetting non-finite velocity or position of {:?}", entity);
+            }
+            velocity.0 = Vec2::ZERO;
+            if !prev_translation.is_finite() {
+                transform.translation = Vec3::ZERO;
+            }
+        }
 
         let position = transform.translation.xy();
         let new_position = position + velocity.0;
@@ -99,9 +311,7 @@ pub fn update(
             transform.translation += velocity.0.extend(0.);
         }
 
-        grid_spec
-            .world2d_bounds_eps()
-            .clamp3(&mut transform.translation);
+        bounds.clamp3(&mut transform.translation);
 
         acceleration.0 = Vec2::ZERO;
     }
@@ -122,13 +332,19 @@ pub enum PhysicsMaterialType {
 #[derive(Clone, Reflect, Debug)]
 pub struct PhysicsMaterial {
     max_velocity: f32,
+    // Minimum speed of moving objects. Stationary objects stay at rest.
+    min_velocity: f32,
     velocity_smoothing: f32,
+    // Fraction of the previous heading kept each tick when facing velocity.
+    rotation_smoothing: f32,
 }
 impl Default for PhysicsMaterial {
     fn default() -> Self {
         Self {
             max_velocity: 10.0,
+            min_velocity: 0.,
             velocity_smoothing: 0.,
+            rotation_smoothing: 0.,
         }
     }
 }
@@ -139,3 +355,367 @@ pub struct PhysicsBundle {
     pub acceleration: Acceleration,
     pub material: PhysicsMaterialType,
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{ecs::system::RunSystemOnce, prelude::*, utils::HashMap};
+
+    use crate::prelude::*;
+
+    use super::{CurrentField, CurrentsSpec, Drag, PhysicsMaterials, SpawnRamp};
+
+    /// Push coincident objects apart, mimicking strong separation forces.
+    fn separate(
+        mut query: Query<(Entity, &Transform, &mut Acceleration)>,
+        others: Query<(Entity, &Transform)>,
+    ) {
+        for (entity, transform, mut acceleration) in &mut query {
+            for (other, other_transform) in &others {
+                if entity == other {
+                    continue;
+                }
+                let delta = transform.translation.xy() - other_transform.translation.xy();
+                let distance = delta.length();
+                if distance > 20. {
+                    continue;
+                }
+                let direction = if delta == Vec2::ZERO {
+                    Vec2::from_angle(entity.index() as f32)
+                } else {
+                    delta / distance
+                };
+                acceleration.0 += direction * 5. * (1. - distance / 20.);
+            }
+        }
+    }
+
+    #[test]
+    fn test_spawn_ramp() {
+        check_spawn_ramp(0.);
+        check_spawn_ramp(0.5);
+    }
+
+    fn check_spawn_ramp(velocity_smoothing: f32) {
+        let spec = GridSpec {
+            rows: 100,
+            cols: 100,
+            width: 10.0,
+            visualize: false,
+            visualize_navigation: false,
+            ..default()
+        };
+        let mut obstacles = Grid2::<Obstacle>::default();
+        obstacles.resize_with(spec.clone());
+        let material = PhysicsMaterial {
+            velocity_smoothing,
+            ..default()
+        };
+        let max_velocity = material.max_velocity;
+
+        let mut world = World::new();
+        world.insert_resource(spec);
+        world.insert_resource(obstacles);
+        world.insert_resource(PhysicsMaterials(HashMap::from_iter([(
+            PhysicsMaterialType::Default,
+            material,
+        )])));
+        world.insert_resource(Time::<()>::default());
+        for _ in 0..10 {
+            world.spawn((
+                Transform::default(),
+                PhysicsBundle {
+                    velocity: Velocity(Vec2::ONE) * max_velocity,
+                    ..default()
+                },
+                SpawnRamp::default(),
+            ));
+        }
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems((separate, super::update, SpawnRamp::update).chain());
+        let mut peak_velocity: f32 = 0.;
+        for step in 0..30 {
+            world
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_secs_f32(1. / 60.));
+            schedule.run(&mut world);
+            let mut query = world.query::<(&Velocity, Option<&SpawnRamp>)>();
+            for (velocity, spawn_ramp) in query.iter(&world) {
+                peak_velocity = peak_velocity.max(velocity.length());
+                if step < 5 {
+                    assert!(spawn_ramp.is_some());
+                    assert!(velocity.length() < max_velocity * 0.5);
+                }
+            }
+        }
+        assert!(peak_velocity <= max_velocity + 1e-3);
+
+        // Ramps are removed once finished and all objects have moved apart.
+        let mut query = world.query::<(&Transform, Option<&SpawnRamp>)>();
+        let positions: Vec<Vec2> = query
+            .iter(&world)
+            .map(|(transform, spawn_ramp)| {
+                assert!(spawn_ramp.is_none());
+                transform.translation.xy()
+            })
+            .collect();
+        for (i, a) in positions.iter().enumerate() {
+            for b in &positions[i + 1..] {
+                assert!(a.distance(*b) > 1.);
+            }
+        }
+    }
+
+    #[test]
+    fn test_min_velocity() {
+        let spec = GridSpec {
+            rows: 10,
+            cols: 10,
+            width: 10.0,
+            ..default()
+        };
+        let mut obstacles = Grid2::<Obstacle>::default();
+        obstacles.resize_with(spec.clone());
+
+        let mut world = World::new();
+        world.insert_resource(spec);
+        world.insert_resource(obstacles);
+        world.insert_resource(PhysicsMaterials(HashMap::from_iter([(
+            PhysicsMaterialType::Default,
+            PhysicsMaterial {
+                min_velocity: 3.,
+                ..default()
+            },
+        )])));
+        let slow = world
+            .spawn((
+                Transform::default(),
+                PhysicsBundle {
+                    velocity: Velocity(Vec2::new(0., 1.)),
+                    ..default()
+                },
+            ))
+            .id();
+        let stopped = world
+            .spawn((Transform::default(), PhysicsBundle::default()))
+            .id();
+        world.run_system_once(super::update);
+
+        let velocity = world.get::<Velocity>(slow).unwrap();
+        assert!(velocity.abs_diff_eq(Vec2::new(0., 3.), 1e-5));
+        assert_eq!(*world.get::<Velocity>(stopped).unwrap(), Velocity::ZERO);
+    }
+
+    #[test]
+    fn test_drag() {
+        let spec = GridSpec {
+            rows: 100,
+            cols: 100,
+            width: 10.0,
+            ..default()
+        };
+        let mut obstacles = Grid2::<Obstacle>::default();
+        obstacles.resize_with(spec.clone());
+        let mut world = World::new();
+        world.insert_resource(spec);
+        world.insert_resource(obstacles);
+        world.insert_resource(PhysicsMaterials(HashMap::from_iter([(
+            PhysicsMaterialType::Default,
+            PhysicsMaterial::default(),
+        )])));
+        let entity = world
+            .spawn((
+                Transform::default(),
+                PhysicsBundle {
+                    velocity: Velocity(Vec2::new(1., 8.)),
+                    ..default()
+                },
+                Drag(Vec2::new(1., 0.5)),
+            ))
+            .id();
+
+        // Vertical speed halves each step, while horizontal speed is untouched.
+        for expected in [4., 2., 1.] {
+            world.run_system_once(super::update);
+            let velocity = world.get::<Velocity>(entity).unwrap();
+            assert_eq!(velocity.0, Vec2::new(1., expected));
+        }
+        assert_eq!(
+            Acceleration(Vec2::new(2., 2.)) * Vec2::new(1., 0.5),
+            Acceleration(Vec2::new(2., 1.))
+        );
+    }
+
+    #[test]
+    fn test_current_field() {
+        let spec = GridSpec {
+            rows: 10,
+            cols: 10,
+            width: 10.0,
+            ..default()
+        };
+        let mut obstacles = Grid2::<Obstacle>::default();
+        obstacles.resize_with(spec.clone());
+        let mut world = World::new();
+        world.insert_resource(spec.clone());
+        world.insert_resource(obstacles);
+        world.insert_resource(PhysicsMaterials(HashMap::from_iter([(
+            PhysicsMaterialType::Default,
+            PhysicsMaterial::default(),
+        )])));
+        world.insert_resource(Configs {
+            current: Vec2::new(1., 0.),
+            ..default()
+        });
+        world.insert_resource(CurrentsSpec(vec![((5, 5), Vec2::new(0., 2.))]));
+        world.init_resource::<CurrentField>();
+        world.run_system_once(CurrentField::update);
+
+        // The uniform current applies everywhere, and per-cell currents add to it.
+        let field = world.resource::<CurrentField>();
+        let bounds = spec.world2d_bounds_eps();
+        let cell = spec.to_world_position((5, 5));
+        assert_eq!(field.acceleration(cell, &bounds), Vec2::new(1., 2.));
+        assert_eq!(
+            field.acceleration(Vec2::ZERO - 10., &bounds),
+            Vec2::new(1., 0.)
+        );
+
+        // Currents carry objects until the edge of the world, then stop pushing.
+        let entity = world
+            .spawn((Transform::from_xyz(0., -20., 0.), PhysicsBundle::default()))
+            .id();
+        for _ in 0..100 {
+            world.run_system_once(super::update);
+        }
+        let position = world.get::<Transform>(entity).unwrap().translation.xy();
+        assert_eq!(position, Vec2::new(bounds.max.x, -20.));
+    }
+
+    #[test]
+    fn test_non_finite_recovery() {
+        let spec = GridSpec {
+            rows: 10,
+            cols: 10,
+            width: 10.0,
+            ..default()
+        };
+        let mut obstacles = Grid2::<Obstacle>::default();
+        obstacles.resize_with(spec.clone());
+
+        let mut world = World::new();
+        world.insert_resource(spec);
+        world.insert_resource(obstacles);
+        world.insert_resource(PhysicsMaterials(HashMap::from_iter([(
+            PhysicsMaterialType::Default,
+            PhysicsMaterial::default(),
+        )])));
+        let position = Vec3::new(12., -8., 0.);
+        let mut spawn = |translation: Vec3, velocity: Vec2, acceleration: Vec2| {
+            world
+                .spawn((
+                    Transform::from_translation(translation),
+                    PhysicsBundle {
+                        velocity: Velocity(velocity),
+                        acceleration: Acceleration(acceleration),
+                        ..default()
+                    },
+                ))
+                .id()
+        };
+        let nan_velocity = spawn(position, Vec2::NAN, Vec2::ZERO);
+        let infinite_acceleration = spawn(position, Vec2::X, Vec2::new(f32::INFINITY, 0.));
+        let nan_position = spawn(Vec3::NAN, Vec2::X, Vec2::ZERO);
+        let mut schedule = Schedule::default();
+        schedule.add_systems(super::update);
+        schedule.run(&mut world);
+
+        for entity in [nan_velocity, infinite_acceleration] {
+            assert_eq!(*world.get::<Velocity>(entity).unwrap(), Velocity::ZERO);
+            assert_eq!(
+                world.get::<Transform>(entity).unwrap().translation,
+                position
+            );
+        }
+        assert!(world
+            .get::<Transform>(nan_position)
+            .unwrap()
+            .translation
+            .is_finite());
+
+        // Recovered objects move normally again.
+        world.get_mut::<Velocity>(nan_velocity).unwrap().0 = Vec2::X;
+        schedule.run(&mut world);
+        let translation = world.get::<Transform>(nan_velocity).unwrap().translation;
+        assert_eq!(translation, position + Vec3::X);
+    }
+
+    #[test]
+    fn test_face_velocity() {
+        let mut world = World::new();
+        world.insert_resource(PhysicsMaterials(HashMap::from_iter([
+            (PhysicsMaterialType::Default, PhysicsMaterial::default()),
+            (
+                PhysicsMaterialType::Zooid,
+                PhysicsMaterial {
+                    rotation_smoothing: 0.5,
+                    ..default()
+                },
+            ),
+        ])));
+        let mut spawn = |velocity: Vec2, material: PhysicsMaterialType| {
+            world
+                .spawn((
+                    Transform::default(),
+                    Velocity(velocity),
+                    material,
+                    FaceVelocity,
+                ))
+                .id()
+        };
+        let right = spawn(Vec2::X, PhysicsMaterialType::Default);
+        let smoothed = spawn(Vec2::X, PhysicsMaterialType::Zooid);
+        let stopped = spawn(Vec2::ZERO, PhysicsMaterialType::Default);
+        world.run_system_once(FaceVelocity::update);
+
+        let heading =
+            |world: &World, entity| world.get::<Transform>(entity).unwrap().rotation * Vec3::Y;
+        assert!(heading(&world, right).abs_diff_eq(Vec3::X, 1e-5));
+        assert_eq!(heading(&world, stopped), Vec3::Y);
+        // Smoothed objects turn part of the way each tick.
+        let partial = heading(&world, smoothed);
+        assert!(partial.x > 0.1 && partial.y > 0.1);
+        for _ in 0..20 {
+            world.run_system_once(FaceVelocity::update);
+        }
+        assert!(heading(&world, smoothed).abs_diff_eq(Vec3::X, 1e-3));
+    }
+
+    #[test]
+    fn test_upright() {
+        let mut world = World::new();
+        let offset = Vec3::new(0., 0.8, 1.);
+        let child = world
+            .spawn((Upright, Transform::from_translation(offset)))
+            .id();
+        let parent = world
+            .spawn((
+                FaceVelocity,
+                Transform::from_rotation(Quat::from_rotation_z(1.)),
+            ))
+            .add_child(child)
+            .id();
+
+        // The child stays unrotated at the same offset as the parent turns.
+        for angle in [1., -2.] {
+            world.get_mut::<Transform>(parent).unwrap().rotation = Quat::from_rotation_z(angle);
+            world.run_system_once(Upright::update);
+            let parent_rotation = world.get::<Transform>(parent).unwrap().rotation;
+            let transform = world.get::<Transform>(child).unwrap();
+            assert!((parent_rotation * transform.rotation).abs_diff_eq(Quat::IDENTITY, 1e-5));
+            assert!((parent_rotation * transform.translation).abs_diff_eq(offset, 1e-5));
+        }
+    }
+}
@@ -6,6 +6,15 @@ pub enum RaycastTarget {
     None,
     WorldGrid,
     Minimap,
+    /// An individual object, such as a unit that can be clicked to select it.
+    Object,
+}
+impl RaycastTarget {
+    /// Returns true if rays can hit this target.
+    /// Objects hidden by fog are ignored, while grids and minimaps are always hittable.
+    pub fn is_hittable(self, visibility: Option<&Visibility>) -> bool {
+        self != Self::Object || visibility != Some(&Visibility::Hidden)
+    }
 }
 
 /// System param to allow spawning effects.
@@ -19,14 +28,19 @@ pub struct RaycastCommands<'w, 's> {
             &'static RaycastTarget,
             &'static Mesh2dHandle,
             &'static GlobalTransform,
+            Option<&'static Visibility>,
         ),
     >,
     pub assets: Res<'w, Assets<Mesh>>,
 }
 impl RaycastCommands<'_, '_> {
+    /// Returns the closest hittable target along the ray.
     pub fn raycast(&self, ray: Ray3d) -> Option<RaycastEvent> {
         let mut hits = Vec::default();
-        for (entity, &target, mesh_handle, transform) in self.meshes.iter() {
+        for (entity, &target, mesh_handle, transform, visibility) in self.meshes.iter() {
+            if !target.is_hittable(visibility) {
+                continue;
+            }
             let mesh = self.assets.get(&mesh_handle.0).unwrap();
             let mesh_to_world = transform.compute_matrix();
             let world_to_mesh = mesh_to_world.inverse();
@@ -70,3 +84,21 @@ pub struct RaycastEvent {
     pub position: Vec2,
     pub target: RaycastTarget,
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::RaycastTarget;
+
+    #[test]
+    fn test_hidden_objects_not_hittable() {
+        assert!(RaycastTarget::Object.is_hittable(Some(&Visibility::Inherited)));
+        assert!(RaycastTarget::Object.is_hittable(None));
+        assert!(!RaycastTarget::Object.is_hittable(Some(&Visibility::Hidden)));
+
+        // Grid and minimap targets are always visible to clicks.
+        assert!(RaycastTarget::WorldGrid.is_hittable(Some(&Visibility::Hidden)));
+        assert!(RaycastTarget::Minimap.is_hittable(Some(&Visibility::Hidden)));
+    }
+}
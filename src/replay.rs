@@ -0,0 +1,593 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    app::AppExit,
+    input::ButtonState,
+    prelude::*,
+    reflect::serde::{ReflectSerializer, UntypedReflectDeserializer},
+    scene::ron,
+};
+use clap::ValueEnum;
+use serde::de::DeserializeSeed;
+
+use crate::prelude::*;
+
+/// Plugin to record control events to a replay file, or play them back in place of live input.
+#[derive(Default)]
+pub struct ReplayPlugin {
+    /// Record control events to this file.
+    pub record: Option<PathBuf>,
+    /// Play back control events from this file.
+    pub replay: Option<PathBuf>,
+}
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SimulationTick>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    SimulationTick::update.in_set(SystemStage::Spawn),
+                    ReplayRecorder::record
+                        .in_set(SystemStage::Spawn)
+                        .after(SimulationTick::update)
+                        .run_if(resource_exists::<ReplayRecorder>),
+                    ReplayRecorder::record_hash
+                        .after(SystemStage::Despawn)
+                        .run_if(resource_exists::<ReplayRecorder>),
+                    ReplayPlayback::apply_configs
+                        .before(SimRng::update)
+                        .run_if(resource_exists::<ReplayPlayback>),
+                    ReplayPlayback::play
+                        .in_set(SystemStage::Spawn)
+                        .after(SimulationTick::update)
+                        .run_if(resource_exists::<ReplayPlayback>),
+                    ReplayPlayback::verify_hash
+                        .after(SystemStage::Despawn)
+                        .run_if(resource_exists::<ReplayPlayback>),
+                ),
+            )
+            .add_systems(
+                Last,
+                ReplayRecorder::save.run_if(resource_exists::<ReplayRecorder>),
+            );
+
+        if let Some(path) = &self.replay {
+            match Replay::load(path) {
+                Ok(replay) => {
+                    app.insert_resource(ReplayPlayback::new(replay));
+                }
+                Err(err) => error!("Failed to load replay {:?}: {}", path, err),
+            }
+        } else if let Some(path) = &self.record {
+            app.insert_resource(ReplayRecorder::new(path.clone()));
+        }
+    }
+}
+
+/// Counts fixed simulation steps since startup.
+#[derive(Resource, Default, Deref, DerefMut, Debug, Clone, Copy)]
+pub struct SimulationTick(pub u64);
+impl SimulationTick {
+    pub fn update(mut tick: ResMut<Self>) {
+        tick.0 += 1;
+    }
+}
+
+/// A control event logged at a given simulation tick.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayEvent {
+    pub tick: u64,
+    pub action: ControlAction,
+    pub state: ButtonState,
+    pub position: Vec2,
+    /// Object targeted by the event. Entity ids match across runs that spawn in the same order.
+    pub entity: Option<Entity>,
+}
+
+/// Contents of a replay file.
+///
+/// The file is line based:
+/// ```text
+/// birdflow-replay 2
+/// seed <u64>
+/// configs <Configs as single line RON>
+/// event <tick> <action> <pressed|released> <x> <y> <entity bits|->
+/// hash <tick> <u64>
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Replay {
+    /// Seed for simulation randomness, from `Configs::seed`.
+    pub seed: u64,
+    /// Configs at the start of the recording, serialized as RON.
+    pub configs: String,
+    pub events: Vec<ReplayEvent>,
+    /// World state hashes, recorded periodically as (tick, hash).
+    pub hashes: Vec<(u64, u64)>,
+}
+impl Replay {
+    pub const HEADER: &'static str = "birdflow-replay 2";
+
+    /// Serialize the replay to the line based file format.
+    pub fn serialize(&self) -> String {
+        let mut lines = vec![
+            Self::HEADER.to_string(),
+            format!("seed {}", self.seed),
+            format!("configs {}", self.configs),
+        ];
+        for event in &self.events {
+            lines.push(format!(
+                "event {} {} {} {} {} {}",
+                event.tick,
+                event.action.to_possible_value().unwrap().get_name(),
+                match event.state {
+                    ButtonState::Pressed => "pressed",
+                    ButtonState::Released => "released",
+                },
+                event.position.x,
+                event.position.y,
+                event
+                    .entity
+                    .map_or("-".to_string(), |entity| entity.to_bits().to_string()),
+            ));
+        }
+        for (tick, hash) in &self.hashes {
+            lines.push(format!("hash {} {}", tick, hash));
+        }
+        lines.join("\n") + "\n"
+    }
+
+    /// Parse a replay from the line based file format.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines();
+        if lines.next() != Some(Self::HEADER) {
+            return Err(format!("Missing header `{}`", Self::HEADER));
+        }
+        let mut replay = Self::default();
+        for (i, line) in lines.enumerate() {
+            let err = |msg: &str| format!("Line {}: {}", i + 2, msg);
+            let (kind, rest) = line.split_once(' ').unwrap_or((line, ""));
+            match kind {
+                "seed" => replay.seed = rest.parse().map_err(|_| err("invalid seed"))?,
+                "configs" => replay.configs = rest.to_string(),
+                "event" => {
+                    let fields: Vec<&str> = rest.split(' ').collect();
+                    let [tick, action, state, x, y, entity] = fields[..] else {
+                        return Err(err("expected 6 event fields"));
+                    };
+                    replay.events.push(ReplayEvent {
+                        tick: tick.parse().map_err(|_| err("invalid tick"))?,
+                        action: ControlAction::from_str(action, false).map_err(|e| err(&e))?,
+                        state: match state {
+                            "pressed" => ButtonState::Pressed,
+                            "released" => ButtonState::Released,
+                            _ => return Err(err("invalid button state")),
+                        },
+                        position: Vec2 {
+                            x: x.parse().map_err(|_| err("invalid x"))?,
+                            y: y.parse().map_err(|_| err("invalid y"))?,
+                        },
+                        entity: match entity {
+                            "-" => None,
+                            bits => Some(
+                                bits.parse()
+                                    .ok()
+                                    .and_then(|bits| Entity::try_from_bits(bits).ok())
+                                    .ok_or_else(|| err("invalid entity"))?,
+                            ),
+                        },
+                    });
+                }
+                "hash" => {
+                    let (tick, hash) = rest.split_once(' ').ok_or_else(|| err("invalid hash"))?;
+                    replay.hashes.push((
+                        tick.parse().map_err(|_| err("invalid tick"))?,
+                        hash.parse().map_err(|_| err("invalid hash"))?,
+                    ));
+                }
+                "" => {}
+                _ => return Err(err("unknown entry")),
+            }
+        }
+        Ok(replay)
+    }
+
+    /// Load a replay file.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        Self::parse(&fs::read_to_string(path).map_err(|e| e.to_string())?)
+    }
+
+    /// Serialize configs so they can be restored on playback.
+    pub fn serialize_configs(configs: &Configs, registry: &AppTypeRegistry) -> String {
+        let registry = registry.read();
+        ron::to_string(&ReflectSerializer::new(configs.as_reflect(), &registry)).unwrap_or_default()
+    }
+
+    /// Overwrite `configs` with configs serialized by `serialize_configs`.
+    pub fn deserialize_configs(
+        text: &str,
+        configs: &mut Configs,
+        registry: &AppTypeRegistry,
+    ) -> Result<(), String> {
+        let registry = registry.read();
+        let mut deserializer = ron::de::Deserializer::from_str(text).map_err(|e| e.to_string())?;
+        let value = UntypedReflectDeserializer::new(&registry)
+            .deserialize(&mut deserializer)
+            .map_err(|e| e.to_string())?;
+        configs.apply(&*value);
+        Ok(())
+    }
+
+    /// Hash the state of all objects, independent of entity ids and query order.
+    pub fn state_hash<'a>(
+        objects: impl Iterator<Item = (&'a Object, &'a Team, &'a Transform)>,
+    ) -> u64 {
+        let mut states: Vec<(u8, u8, u32, u32)> = objects
+            .map(|(&object, &team, transform)| {
+                (
+                    object as u8,
+                    team as u8,
+                    transform.translation.x.to_bits(),
+                    transform.translation.y.to_bits(),
+                )
+            })
+            .collect();
+        states.sort_unstable();
+        let mut hasher = DefaultHasher::new();
+        states.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+/// Records control events while running.
+#[derive(Resource)]
+pub struct ReplayRecorder {
+    pub path: PathBuf,
+    pub replay: Replay,
+}
+impl ReplayRecorder {
+    /// Number of ticks between world state hashes.
+    pub const HASH_INTERVAL: u64 = 60;
+
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            replay: Replay::default(),
+        }
+    }
+
+    /// Log all control events with the current tick.
+    /// The seed and configs are recorded at startup, including changes from loading the scene
+    /// before the first input.
+    pub fn record(
+        mut recorder: ResMut<Self>,
+        mut control_events: EventReader<ControlEvent>,
+        tick: Res<SimulationTick>,
+        configs: Res<Configs>,
+        registry: Res<AppTypeRegistry>,
+    ) {
+        if recorder.replay.events.is_empty() && configs.is_changed() {
+            recorder.replay.configs = Replay::serialize_configs(&configs, &registry);
+            recorder.replay.seed = configs.seed;
+        }
+        for event in control_events.read() {
+            recorder.replay.events.push(ReplayEvent {
+                tick: tick.0,
+                action: event.action,
+                state: event.state,
+                position: event.position,
+                entity: event.entity,
+            });
+        }
+    }
+
+    /// Periodically hash the world state.
+    pub fn record_hash(
+        mut recorder: ResMut<Self>,
+        tick: Res<SimulationTick>,
+        objects: Query<(&Object, &Team, &Transform)>,
+    ) {
+        if tick.0.is_multiple_of(Self::HASH_INTERVAL) {
+            let hash = Replay::state_hash(objects.iter());
+            recorder.replay.hashes.push((tick.0, hash));
+        }
+    }
+
+    /// Write the replay file on exit.
+    pub fn save(recorder: Res<Self>, mut exits: EventReader<AppExit>) {
+        if exits.read().next().is_none() {
+            return;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        match fs::write(&recorder.path, recorder.replay.serialize()) {
+            Ok(()) => info!("Saved replay to {:?}", recorder.path),
+            Err(err) => error!("Failed to save replay to {:?}: {}", recorder.path, err),
+        }
+    }
+}
+
+/// Feeds recorded control events back into the simulation.
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    pub replay: Replay,
+    next_event: usize,
+    next_hash: usize,
+    mismatches: usize,
+}
+impl ReplayPlayback {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            next_event: 0,
+            next_hash: 0,
+            mismatches: 0,
+        }
+    }
+
+    /// Restore the recorded configs and seed at startup, and whenever a scene load replaces them.
+    pub fn apply_configs(
+        playback: Res<Self>,
+        mut configs: ResMut<Configs>,
+        mut rng: ResMut<SimRng>,
+        registry: Res<AppTypeRegistry>,
+    ) {
+        if !configs.is_changed() {
+            return;
+        }
+        if let Err(err) =
+            Replay::deserialize_configs(&playback.replay.configs, &mut configs, &registry)
+        {
+            eprintln!("DBG {}", err);
+            warn!("Failed to restore replay configs: {}", err);
+        }
+        configs.seed = playback.replay.seed;
+        *rng = SimRng::new(playback.replay.seed);
+    }
+
+    /// Send all events recorded for the current tick.
+    pub fn play(
+        mut playback: ResMut<Self>,
+        mut control_events: EventWriter<ControlEvent>,
+        tick: Res<SimulationTick>,
+    ) {
+        while let Some(event) = playback.replay.events.get(playback.next_event) {
+            if event.tick > tick.0 {
+                break;
+            }
+            control_events.send(ControlEvent {
+                action: event.action,
+                state: event.state,
+                position: event.position,
+                entity: event.entity,
+            });
+            playback.next_event += 1;
+        }
+    }
+
+    /// Compare the world state to the hashes recorded in the original run.
+    pub fn verify_hash(
+        mut playback: ResMut<Self>,
+        tick: Res<SimulationTick>,
+        objects: Query<(&Object, &Team, &Transform)>,
+    ) {
+        let Some(&(hash_tick, expected)) = playback.replay.hashes.get(playback.next_hash) else {
+            return;
+        };
+        if hash_tick != tick.0 {
+            return;
+        }
+        playback.next_hash += 1;
+        if Replay::state_hash(objects.iter()) != expected {
+            playback.mismatches += 1;
+            error!("Replay diverged at tick {}.", tick.0);
+        }
+        if playback.next_hash == playback.replay.hashes.len() {
+            info!(
+                "Replay finished with {} of {} hashes matching.",
+                playback.replay.hashes.len() - playback.mismatches,
+                playback.replay.hashes.len()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{ecs::system::RunSystemOnce, input::ButtonState, prelude::*};
+    use rand::Rng;
+
+    use crate::{config::ConfigPlugin, prelude::*, rng::RngPlugin};
+
+    use super::{
+        Replay, ReplayEvent, ReplayPlayback, ReplayPlugin, ReplayRecorder, SimulationTick,
+    };
+
+    fn replay() -> Replay {
+        Replay {
+            seed: 7,
+            configs: "{\"birdflow::config::Configs\":()}".to_string(),
+            events: vec![
+                ReplayEvent {
+                    tick: 3,
+                    action: ControlAction::SpawnZooid,
+                    state: ButtonState::Pressed,
+                    position: Vec2::new(1.5, -0.1),
+                    entity: Some(Entity::from_raw(42)),
+                },
+                ReplayEvent {
+                    tick: 5,
+                    action: ControlAction::Move,
+                    state: ButtonState::Released,
+                    position: Vec2::new(-100., 1e-7),
+                    entity: None,
+                },
+            ],
+            hashes: vec![(60, 1234567890123)],
+        }
+    }
+
+    #[test]
+    fn test_replay_format() {
+        let replay = replay();
+        assert_eq!(Replay::parse(&replay.serialize()), Ok(replay));
+        assert!(Replay::parse("event 1 move pressed 0 0").is_err());
+    }
+
+    #[test]
+    fn test_replay_playback() {
+        let mut world = World::new();
+        world.insert_resource(ReplayPlayback::new(replay()));
+        world.insert_resource(SimulationTick(4));
+        world.init_resource::<Configs>();
+        world.init_resource::<AppTypeRegistry>();
+        world.init_resource::<Events<ControlEvent>>();
+
+        world.run_system_once(ReplayPlayback::play);
+        let events = world.resource::<Events<ControlEvent>>();
+        let mut reader = events.get_reader();
+        let sent: Vec<&ControlEvent> = reader.read(events).collect();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].action, ControlAction::SpawnZooid);
+        assert_eq!(sent[0].position, Vec2::new(1.5, -0.1));
+        assert_eq!(sent[0].entity, Some(Entity::from_raw(42)));
+    }
+
+    #[test]
+    fn test_state_hash() {
+        let a = Transform::from_xyz(1., 2., 0.);
+        let b = Transform::from_xyz(3., 4., 0.);
+        let forward = [
+            (&Object::Worker, &Team::Blue, &a),
+            (&Object::Head, &Team::Red, &b),
+        ];
+        let backward = [
+            (&Object::Head, &Team::Red, &b),
+            (&Object::Worker, &Team::Blue, &a),
+        ];
+        assert_eq!(
+            Replay::state_hash(forward.into_iter()),
+            Replay::state_hash(backward.into_iter())
+        );
+        let moved = Transform::from_xyz(1., 2.5, 0.);
+        let changed = [
+            (&Object::Worker, &Team::Blue, &moved),
+            (&Object::Head, &Team::Red, &b),
+        ];
+        assert_ne!(
+            Replay::state_hash(forward.into_iter()),
+            Replay::state_hash(changed.into_iter())
+        );
+    }
+
+    fn spawn_on_press(
+        mut control_events: EventReader<ControlEvent>,
+        mut rng: ResMut<SimRng>,
+        mut commands: Commands,
+    ) {
+        for control in control_events.read() {
+            if control.is_pressed(ControlAction::SpawnZooid) {
+                commands.spawn((
+                    Object::Worker,
+                    Team::Blue,
+                    Transform::from_translation(control.position.extend(0.)),
+                    Velocity(Vec2::new(
+                        rng.gen_range(-1.0..1.0),
+                        rng.gen_range(-1.0..1.0),
+                    )),
+                ));
+            }
+        }
+    }
+
+    fn apply_velocity(mut query: Query<(&mut Transform, &Velocity)>) {
+        for (mut transform, velocity) in &mut query {
+            transform.translation += velocity.extend(0.);
+        }
+    }
+
+    fn session(seed: u64) -> App {
+        let mut app = App::new();
+        app.add_plugins((ReplayPlugin::default(), RngPlugin, ConfigPlugin))
+            .add_event::<ControlEvent>()
+            .init_resource::<GridSpec>()
+            .insert_resource(Configs { seed, ..default() })
+            .configure_sets(FixedUpdate, SystemStage::get_config())
+            .add_systems(
+                FixedUpdate,
+                (
+                    spawn_on_press.in_set(SystemStage::Compute),
+                    apply_velocity.in_set(SystemStage::Apply),
+                ),
+            );
+        app
+    }
+
+    /// Play back the replay in a session started with a different seed.
+    fn play(replay: &Replay) -> (ReplayPlayback, App) {
+        let mut app = session(2);
+        app.insert_resource(ReplayPlayback::new(replay.clone()));
+        for _ in 0..2 * ReplayRecorder::HASH_INTERVAL {
+            app.world.run_schedule(FixedUpdate);
+        }
+        (app.world.remove_resource::<ReplayPlayback>().unwrap(), app)
+    }
+
+    #[test]
+    fn test_record_without_input() {
+        let mut app = session(1);
+        app.insert_resource(ReplayRecorder::new("test.replay".into()));
+        app.world.run_schedule(FixedUpdate);
+        let recorder = app.world.resource::<ReplayRecorder>();
+        assert_eq!(recorder.replay.seed, 1);
+        assert!(!recorder.replay.configs.is_empty());
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let mut app = session(1);
+        app.world.resource_mut::<Configs>().sfx_volume = 0.25;
+        app.insert_resource(ReplayRecorder::new("test.replay".into()));
+        for tick in 1..=2 * ReplayRecorder::HASH_INTERVAL {
+            if tick == 3 || tick == 70 {
+                app.world.send_event(ControlEvent {
+                    action: ControlAction::SpawnZooid,
+                    state: ButtonState::Pressed,
+                    position: Vec2::new(tick as f32, -2.),
+                    entity: None,
+                });
+            }
+            app.world.run_schedule(FixedUpdate);
+        }
+        let recorded = app
+            .world
+            .remove_resource::<ReplayRecorder>()
+            .unwrap()
+            .replay;
+        assert_eq!(recorded.events.len(), 2);
+        assert_eq!(recorded.hashes.len(), 2);
+
+        // Playback restores the recorded seed and configs.
+        let replay = Replay::parse(&recorded.serialize()).unwrap();
+        let (playback, app) = play(&replay);
+        assert_eq!(playback.next_hash, 2);
+        assert_eq!(playback.mismatches, 0);
+        let configs = app.world.resource::<Configs>();
+        assert_eq!((configs.seed, configs.sfx_volume), (1, 0.25));
+        assert_eq!(app.world.resource::<SimRng>().seed(), 1);
+
+        // A replay with a different seed or inputs diverges from the recorded hashes.
+        let mut reseeded = replay.clone();
+        reseeded.seed = 2;
+        let (playback, _) = play(&reseeded);
+        assert_eq!(playback.mismatches, 2);
+        let mut diverged = replay;
+        diverged.events[1].position.y += 1.;
+        let (playback, _) = play(&diverged);
+        assert_eq!(playback.next_hash, 2);
+        assert_eq!(playback.mismatches, 1);
+    }
+}
@@ -0,0 +1,95 @@
+use bevy::prelude::*;
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+use crate::prelude::*;
+
+/// Plugin for seeded simulation randomness.
+pub struct RngPlugin;
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SimRng>()
+            .add_systems(FixedUpdate, SimRng::update.before(SystemStage::Spawn));
+    }
+}
+
+/// Random number generator for the simulation, seeded from `Configs::seed`.
+///
+/// Draw from this only in systems that run serially. Parallel iterators must not share it
+/// mutably, so buffer decisions that need randomness and draw when applying them.
+#[derive(Resource)]
+pub struct SimRng {
+    seed: u64,
+    rng: StdRng,
+}
+impl Default for SimRng {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+impl SimRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Returns the seed this generator was created with.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Reseed when the configured seed changes, e.g. after the scene loads.
+    pub fn update(mut rng: ResMut<Self>, configs: Res<Configs>) {
+        if configs.is_changed() && configs.seed != rng.seed {
+            *rng = Self::new(configs.seed);
+        }
+    }
+}
+impl RngCore for SimRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{ecs::system::RunSystemOnce, prelude::*};
+    use rand::Rng;
+
+    use crate::prelude::*;
+
+    use super::SimRng;
+
+    #[test]
+    fn test_reseed() {
+        let mut world = World::new();
+        world.init_resource::<SimRng>();
+        world.insert_resource(Configs {
+            seed: 42,
+            ..default()
+        });
+        world.run_system_once(SimRng::update);
+        assert_eq!(world.resource::<SimRng>().seed(), 42);
+
+        let draws: Vec<u32> = (0..4)
+            .map(|_| world.resource_mut::<SimRng>().gen())
+            .collect();
+        let mut expected = SimRng::new(42);
+        assert_eq!(draws, (0..4).map(|_| expected.gen()).collect::<Vec<u32>>());
+
+        // Unrelated config changes keep the current sequence.
+        world.resource_mut::<Configs>().sfx_volume = 1.;
+        world.run_system_once(SimRng::update);
+        assert_eq!(world.resource_mut::<SimRng>().gen::<u32>(), expected.gen());
+    }
+}
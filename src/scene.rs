@@ -3,7 +3,12 @@ use std::io::Write;
 
 use bevy::{prelude::*, tasks::IoTaskPool};
 
-use crate::prelude::*;
+use crate::{
+    grid::ObstaclesSpec,
+    inputs::InputConfig,
+    physics::{CurrentsSpec, PhysicsMaterials},
+    prelude::*,
+};
 
 /// Plugin for saving and loading scenes.
 pub struct LoadableScenePlugin;
@@ -12,9 +17,23 @@ impl Plugin for LoadableScenePlugin {
         app.register_type::<SaveEntity>()
             .register_type::<Name>()
             .register_type::<core::num::NonZeroU16>()
+            .add_event::<LoadSceneEvent>()
+            .add_event::<SaveSceneEvent>()
             .add_systems(PreStartup, load_system)
-            .add_systems(FixedUpdate, save_system)
+            .add_systems(
+                Update,
+                (
+                    LoadSceneEvent::update,
+                    SaveSceneEvent::update,
+                    save_system.after(SaveSceneEvent::update),
+                ),
+            )
             .insert_resource(SceneSpec);
+        #[cfg(target_arch = "wasm32")]
+        app.add_event::<SaveSceneWarning>().add_systems(
+            Update,
+            SaveSceneWarning::update.after(SaveSceneEvent::update),
+        );
     }
 }
 
@@ -28,39 +47,159 @@ pub struct SceneSpec;
 #[reflect(Component)]
 pub struct SaveEntity;
 
+/// Marks the root entity of a loaded scene.
+#[derive(Component, Default)]
+pub struct LoadedScene;
+
+type SceneFilter = Or<(With<LoadedScene>, With<SaveEntity>)>;
+
+/// Request to replace the current scene with the scene at `path`, relative to the assets folder.
+#[derive(Event, Clone, Debug)]
+pub struct LoadSceneEvent {
+    pub path: String,
+}
+impl LoadSceneEvent {
+    /// Returns true if the scene file can be loaded.
+    pub fn exists(&self) -> bool {
+        #[cfg(not(target_arch = "wasm32"))]
+        return asset_path(&self.path).is_file();
+        #[cfg(target_arch = "wasm32")]
+        return true;
+    }
+
+    /// Despawn the current scene and load the requested one.
+    pub fn update(
+        mut events: EventReader<Self>,
+        scenes: Query<Entity, SceneFilter>,
+        asset_server: Res<AssetServer>,
+        mut commands: Commands,
+    ) {
+        let Some(event) = events.read().last() else {
+            return;
+        };
+        if !event.exists() {
+            warn!("Scene file not found: {}", event.path);
+            return;
+        }
+        for entity in &scenes {
+            commands.entity(entity).despawn_recursive();
+        }
+        commands.spawn(scene_bundle(asset_server.load(event.path.clone())));
+    }
+}
+
+fn scene_bundle(scene: Handle<DynamicScene>) -> impl Bundle {
+    (
+        DynamicSceneBundle { scene, ..default() },
+        LoadedScene,
+        Name::new("DynamicScene"),
+    )
+}
+
+/// Request to save the current scene to a numbered slot.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SaveSceneEvent {
+    pub slot: u8,
+}
+impl SaveSceneEvent {
+    /// Keys that save to the slot at their index while Shift is held.
+    /// Number keys alone and with Ctrl already recall and assign control groups.
+    pub const SLOT_KEYS: [KeyCode; 10] = [
+        KeyCode::Digit0,
+        KeyCode::Digit1,
+        KeyCode::Digit2,
+        KeyCode::Digit3,
+        KeyCode::Digit4,
+        KeyCode::Digit5,
+        KeyCode::Digit6,
+        KeyCode::Digit7,
+        KeyCode::Digit8,
+        KeyCode::Digit9,
+    ];
+
+    /// Path of the slot's scene file, relative to the assets folder.
+    pub fn path(&self) -> String {
+        format!("save_{}.scn.ron", self.slot)
+    }
+
+    /// Send save events for `S` (slot 0) and Shift+number (slot N).
+    pub fn update(keyboard_input: Res<ButtonInput<KeyCode>>, mut events: EventWriter<Self>) {
+        if keyboard_input.just_pressed(KeyCode::KeyS) {
+            events.send(Self { slot: 0 });
+        }
+        if !keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+            return;
+        }
+        for (slot, &key) in Self::SLOT_KEYS.iter().enumerate() {
+            if keyboard_input.just_pressed(key) {
+                events.send(Self { slot: slot as u8 });
+            }
+        }
+    }
+}
+
+/// Sent when a requested save could not be written.
+#[cfg(target_arch = "wasm32")]
+#[derive(Event, Clone, Debug)]
+pub struct SaveSceneWarning {
+    pub message: String,
+}
+#[cfg(target_arch = "wasm32")]
+impl SaveSceneWarning {
+    /// Warn about every save request, since there is no filesystem on this target.
+    pub fn update(mut saves: EventReader<SaveSceneEvent>, mut warnings: EventWriter<Self>) {
+        for save in saves.read() {
+            let message = format!("Cannot save {}: no filesystem access", save.path());
+            warn!("{}", message);
+            warnings.send(Self { message });
+        }
+    }
+}
+
+/// Resolve a path relative to the assets folder the same way the `AssetServer` does,
+/// so it doesn't depend on the working directory.
+#[cfg(not(target_arch = "wasm32"))]
+fn asset_path(path: &str) -> std::path::PathBuf {
+    bevy::asset::io::file::FileAssetReader::new("assets")
+        .root_path()
+        .join(path)
+}
+
 // The initial scene file will be loaded below and not change when the scene is saved
 const SCENE_FILE_PATH: &str = "test.scn.ron";
 
-// The new, updated scene data will be saved here so that you can see the changes
-const NEW_SCENE_FILE_PATH: &str = "test-new.scn.ron";
-
 pub fn load_system(mut commands: Commands, asset_server: Res<AssetServer>) {
     // "Spawning" a scene bundle creates a new entity and spawns new instances
     // of the given scene's entities as children of that entity.
-    commands.spawn((
-        DynamicSceneBundle {
-            // Scenes are loaded just like any other asset.
-            scene: asset_server.load(SCENE_FILE_PATH),
-            ..default()
-        },
-        Name::new("DynamicScene"),
-    ));
+    // Scenes are loaded just like any other asset.
+    commands.spawn(scene_bundle(asset_server.load(SCENE_FILE_PATH)));
+}
+
+/// Build a scene from the given entities and the resources needed to restore the game state.
+pub fn build_scene(world: &World, entities: impl Iterator<Item = Entity>) -> DynamicScene {
+    DynamicSceneBuilder::from_world(world)
+        .extract_entities(entities)
+        .allow_resource::<InputConfig>()
+        .allow_resource::<GridSpec>()
+        .allow_resource::<ObstaclesSpec>()
+        .allow_resource::<PhysicsMaterials>()
+        .allow_resource::<CurrentsSpec>()
+        .allow_resource::<Configs>()
+        .allow_resource::<Grid2<EntitySet>>()
+        .extract_resources()
+        .build()
 }
 
 pub fn save_system(
     world: &World,
     query: Query<Entity, With<SaveEntity>>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut events: EventReader<SaveSceneEvent>,
 ) {
-    if !keyboard_input.just_pressed(KeyCode::KeyS) {
+    let Some(event) = events.read().last() else {
         return;
-    }
-    let scene = DynamicSceneBuilder::from_world(world)
-        .extract_entities(query.iter())
-        .allow_resource::<Configs>()
-        .allow_resource::<Grid2<EntitySet>>()
-        .extract_resources()
-        .build();
+    };
+    let path = event.path();
+    let scene = build_scene(world, query.iter());
 
     // Scenes can be serialized like this:
     let type_registry = world.resource::<AppTypeRegistry>();
@@ -75,9 +214,129 @@ pub fn save_system(
     IoTaskPool::get()
         .spawn(async move {
             // Write the scene RON data to file
-            File::create(format!("assets/{NEW_SCENE_FILE_PATH}"))
+            File::create(asset_path(&path))
                 .and_then(|mut file| file.write(serialized_scene.as_bytes()))
                 .expect("Error while writing scene to file");
         })
         .detach();
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        ecs::{entity::EntityHashMap, system::RunSystemOnce},
+        prelude::*,
+        scene::{ron, serde::SceneDeserializer, ScenePlugin},
+    };
+    use serde::de::DeserializeSeed;
+
+    use crate::{
+        grid::{GridBackend, ObstaclesSpec},
+        prelude::*,
+    };
+
+    use super::{LoadSceneEvent, LoadedScene, SaveEntity, SaveSceneEvent};
+
+    #[test]
+    fn test_save_slots() {
+        let mut world = World::new();
+        world.init_resource::<Events<SaveSceneEvent>>();
+        let mut keyboard_input = ButtonInput::<KeyCode>::default();
+        keyboard_input.press(KeyCode::KeyS);
+        keyboard_input.press(KeyCode::Digit2);
+        world.insert_resource(keyboard_input);
+        world.run_system_once(SaveSceneEvent::update);
+
+        let mut keyboard_input = ButtonInput::<KeyCode>::default();
+        keyboard_input.press(KeyCode::ShiftLeft);
+        keyboard_input.press(KeyCode::Digit3);
+        world.insert_resource(keyboard_input);
+        world.run_system_once(SaveSceneEvent::update);
+
+        let events = world.resource::<Events<SaveSceneEvent>>();
+        let slots: Vec<u8> = events
+            .get_reader()
+            .read(events)
+            .map(|event| event.slot)
+            .collect();
+        assert_eq!(slots, vec![0, 3]);
+        assert_eq!(SaveSceneEvent { slot: 3 }.path(), "save_3.scn.ron");
+    }
+
+    fn scene_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((
+            crate::config::ConfigPlugin,
+            crate::inputs::InputActionPlugin,
+            crate::physics::PhysicsPlugin,
+        ));
+        app.register_type::<GridSpec>()
+            .register_type::<GridBackend>()
+            .register_type::<ObstaclesSpec>()
+            .register_type::<Obstacle>()
+            .register_type::<Vec<(RowCol, Obstacle)>>()
+            .register_type::<(RowCol, Obstacle)>()
+            .register_type::<RowCol>();
+        app
+    }
+
+    #[test]
+    fn test_load_scene_event() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default(), ScenePlugin))
+            .add_event::<LoadSceneEvent>();
+        let saved = app.world.spawn(SaveEntity).id();
+
+        app.world.send_event(LoadSceneEvent {
+            path: "missing.scn.ron".to_string(),
+        });
+        app.world.run_system_once(LoadSceneEvent::update);
+        assert!(app.world.get_entity(saved).is_some());
+
+        app.world.send_event(LoadSceneEvent {
+            path: "test.scn.ron".to_string(),
+        });
+        app.world.run_system_once(LoadSceneEvent::update);
+        assert!(app.world.get_entity(saved).is_none());
+        let mut scenes = app.world.query_filtered::<Entity, With<LoadedScene>>();
+        assert_eq!(scenes.iter(&app.world).count(), 1);
+    }
+
+    #[test]
+    fn test_save_round_trip() {
+        let mut app = scene_app();
+        let obstacles = vec![((1, 2), Obstacle::Full), ((3, 4), Obstacle::UpLeft)];
+        app.insert_resource(GridSpec {
+            rows: 12,
+            cols: 34,
+            width: 56.,
+            ..default()
+        })
+        .insert_resource(ObstaclesSpec(obstacles.clone()))
+        .insert_resource(Configs {
+            sfx_volume: 0.25,
+            ..default()
+        });
+
+        let registry = app.world.resource::<AppTypeRegistry>().clone();
+        let text = super::build_scene(&app.world, std::iter::empty())
+            .serialize_ron(&registry)
+            .unwrap();
+
+        let mut loaded = scene_app();
+        let mut deserializer = ron::de::Deserializer::from_str(&text).unwrap();
+        let scene = SceneDeserializer {
+            type_registry: &registry.read(),
+        }
+        .deserialize(&mut deserializer)
+        .unwrap();
+        scene
+            .write_to_world(&mut loaded.world, &mut EntityHashMap::default())
+            .unwrap();
+
+        let spec = loaded.world.resource::<GridSpec>();
+        assert_eq!((spec.rows, spec.cols, spec.width), (12, 34, 56.));
+        assert_eq!(loaded.world.resource::<ObstaclesSpec>().0, obstacles);
+        assert_eq!(loaded.world.resource::<Configs>().sfx_volume, 0.25);
+    }
+}
@@ -20,65 +20,165 @@ impl Selected {
     }
 }
 
+/// Number of currently selected entities.
+#[derive(Resource, Default, Debug, Deref, DerefMut, PartialEq)]
+pub struct SelectedCount(pub usize);
+impl SelectedCount {
+    pub fn update(query: Query<&Selected>, mut count: ResMut<Self>) {
+        let selected = query
+            .iter()
+            .filter(|selected| selected.is_selected())
+            .count();
+        if **count != selected {
+            **count = selected;
+        }
+    }
+}
+
+/// Selections bound to number keys.
+#[derive(Resource, Default, Debug, Deref, DerefMut)]
+pub struct ControlGroups(pub [Vec<Entity>; 10]);
+impl ControlGroups {
+    pub fn update(
+        mut commands: Commands,
+        mut groups: ResMut<Self>,
+        mut objects: Query<(Entity, &mut Selected, &Mesh2dHandle)>,
+        assets: Res<SelectorAssets>,
+        mut events: EventReader<ControlEvent>,
+    ) {
+        // Prune dead entities so recalled groups never reference stale ids.
+        for group in groups.iter_mut() {
+            group.retain(|&entity| objects.contains(entity));
+        }
+
+        for control in events.read() {
+            if control.state != ButtonState::Pressed {
+                continue;
+            }
+            let Some((index, assign)) = control.action.control_group() else {
+                continue;
+            };
+            if assign {
+                groups[index] = objects
+                    .iter()
+                    .filter(|(_entity, selected, _mesh)| selected.is_selected())
+                    .map(|(entity, _selected, _mesh)| entity)
+                    .collect();
+                continue;
+            }
+            if groups[index].is_empty() {
+                continue;
+            }
+            for (_entity, mut selected, _mesh) in &mut objects {
+                Selector::deselect(&mut commands, &mut selected);
+            }
+            for &entity in &groups[index] {
+                let (entity, mut selected, mesh) = objects.get_mut(entity).unwrap();
+                Selector::select(&mut commands, &assets, entity, &mut selected, mesh);
+            }
+        }
+    }
+}
+
 /// Plugin for an spacial entity paritioning grid with optional debug functionality.
 pub struct SelectorPlugin;
 impl Plugin for SelectorPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<SelectorAssets>()
+            .init_resource::<SelectedCount>()
+            .init_resource::<ControlGroups>()
             .add_systems(Startup, Selector::startup)
-            .add_systems(FixedUpdate, Selector::update);
+            .add_systems(
+                FixedUpdate,
+                (
+                    Selector::update,
+                    ControlGroups::update.after(Selector::update),
+                    SelectedCount::update.after(ControlGroups::update),
+                ),
+            );
     }
 }
 
+/// Objects that can be selected by the selector.
+type SelectableObjects<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Object,
+        &'static Transform,
+        &'static Team,
+        &'static mut Selected,
+        &'static Mesh2dHandle,
+        &'static Visibility,
+    ),
+    Without<Selector>,
+>;
+
 #[derive(Component, Default)]
 pub struct Selector {
     pub active: bool,
     pub aabb: Aabb2,
+    /// Object under the cursor when the selection started.
+    pub pressed_entity: Option<Entity>,
 }
 impl Selector {
     pub fn startup(mut commands: Commands, assets: Res<SelectorAssets>) {
         commands.spawn(Self::default().bundle(&assets));
     }
 
+    /// Minimum drag distance before a press becomes a box selection.
+    pub const DRAG_THRESHOLD: f32 = 4.0;
+
+    /// Returns true if the selection has been dragged far enough to be a box selection.
+    pub fn is_dragging(&self) -> bool {
+        self.aabb.size().length() >= Self::DRAG_THRESHOLD
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn update(
         mut commands: Commands,
         mut query: Query<(&mut Self, &mut Transform, &mut Visibility)>,
-        mut objects: Query<
-            (&Object, &Transform, &Team, &mut Selected, &Mesh2dHandle),
-            Without<Self>,
-        >,
-        grid: Res<Grid2<EntitySet>>,
+        mut objects: SelectableObjects,
+        grid: EntityGrids,
         assets: Res<SelectorAssets>,
         configs: Res<Configs>,
         mut events: EventReader<ControlEvent>,
+        camera: Query<(&OrthographicProjection, &GlobalTransform), With<MainCamera>>,
+        time: Res<Time>,
+        mut last_click: Local<Option<(Entity, f32)>>,
     ) {
         for control in events.read() {
-            if control.action != ControlAction::Select {
+            // Presses on an object still start a box selection, so dragging from a unit works.
+            if !matches!(
+                control.action,
+                ControlAction::Select | ControlAction::SelectSingle
+            ) {
                 continue;
             }
-            dbg!(control);
             let (mut selector, mut transform, mut visibility) = query.single_mut();
 
             match control.state {
                 ButtonState::Pressed => {
-                    if *visibility == Visibility::Hidden {
-                        info!("First press");
+                    if !selector.active {
                         // Reset other selections.
-                        for (_object, _transform, _team, mut selected, _mesh) in &mut objects {
-                            if let Selected::Selected { child_entity } = selected.as_ref() {
-                                commands.entity(*child_entity).despawn()
-                            }
-                            *selected = Selected::Unselected;
+                        for (_object, _transform, _team, mut selected, _mesh, _visibility) in
+                            &mut objects
+                        {
+                            Self::deselect(&mut commands, &mut selected);
                         }
+                        selector.active = true;
                         selector.aabb.min = control.position;
-                        *visibility = Visibility::Visible;
+                        selector.pressed_entity = control.entity;
                         transform.scale = Vec3::ZERO;
                         transform.translation = control.position.extend(zindex::SELECTOR);
                     }
 
                     // While held
                     selector.aabb.max = control.position;
+                    if !selector.is_dragging() {
+                        continue;
+                    }
+                    *visibility = Visibility::Visible;
                     // Resize the square to match the bounding box.
                     transform.translation = selector.aabb.center().extend(zindex::SELECTOR);
                     transform.scale = selector.aabb.size().extend(0.0);
@@ -86,43 +186,177 @@ impl Selector {
                     // Correct the bounding box before we check entity collision, since it might be backwards.
                     let mut aabb = selector.aabb.clone();
                     aabb.enforce_minmax();
-                    // Check the grid for entities in this bounding box.
-                    for entity in grid.get_entities_in_aabb(&aabb) {
-                        let (_object, transform, team, mut selected, mesh) =
-                            objects.get_mut(entity).unwrap();
-                        if aabb.contains(transform.translation.xy()) {
-                            if selected.is_selected() || *team != configs.player_team {
-                                continue;
-                            }
-                            let child_entity = commands
-                                .spawn(Self::highlight_bundle(&assets, mesh.0.clone()))
-                                .id();
-                            commands.entity(entity).add_child(child_entity);
-                            *selected = Selected::Selected { child_entity };
-                        }
-                    }
+                    Self::select_in_aabb(
+                        &mut commands,
+                        &assets,
+                        &*grid,
+                        &mut objects,
+                        &aabb,
+                        |_object, team| team == configs.player_team,
+                    );
                 }
                 ButtonState::Released => {
+                    if selector.active {
+                        selector.aabb.max = control.position;
+                        // A click without dragging selects the clicked object, or the one under the cursor.
+                        if !selector.is_dragging() {
+                            let clicked = selector
+                                .pressed_entity
+                                .or_else(|| Self::entity_at(control.position, &*grid, &objects));
+                            Self::click(
+                                &mut commands,
+                                &assets,
+                                &*grid,
+                                &mut objects,
+                                &configs,
+                                &camera,
+                                time.elapsed_seconds(),
+                                &mut last_click,
+                                clicked,
+                            );
+                        }
+                    }
+                    selector.active = false;
+                    selector.pressed_entity = None;
                     *visibility = Visibility::Hidden;
                 }
             }
         }
     }
 
-    fn highlight_bundle(assets: &SelectorAssets, mesh: Handle<Mesh>) -> impl Bundle {
-        MaterialMesh2dBundle::<ColorMaterial> {
-            mesh: mesh.clone().into(),
-            transform: Transform::default()
-                .with_scale(Vec2::splat(1.).extend(1.))
-                .with_translation(Vec3 {
-                    x: 0.0,
-                    y: 0.0,
-                    z: zindex::HIGHLIGHT,
-                }),
-            material: assets.white_material.clone(),
-            visibility: Visibility::Visible,
-            ..default()
+    /// Select the clicked entity if it is a visible player unit.
+    /// Double clicking selects all units of the same type on screen.
+    #[allow(clippy::too_many_arguments)]
+    fn click(
+        commands: &mut Commands,
+        assets: &SelectorAssets,
+        grid: &dyn EntityGrid,
+        objects: &mut SelectableObjects,
+        configs: &Configs,
+        camera: &Query<(&OrthographicProjection, &GlobalTransform), With<MainCamera>>,
+        now: f32,
+        last_click: &mut Option<(Entity, f32)>,
+        clicked: Option<Entity>,
+    ) {
+        let clicked = clicked.filter(|&entity| {
+            objects.get(entity).is_ok_and(
+                |(_object, _transform, team, _selected, _mesh, visibility)| {
+                    *team == configs.player_team && visibility != Visibility::Hidden
+                },
+            )
+        });
+        let Some(entity) = clicked else {
+            *last_click = None;
+            return;
+        };
+        let double_clicked = matches!(*last_click, Some((last_entity, last_time))
+            if last_entity == entity
+                && now - last_time <= configs.double_click_seconds);
+        if double_clicked {
+            // Select all units of the same type on screen.
+            let (&clicked_object, _, &clicked_team, _, _, _) = objects.get(entity).unwrap();
+            if let Ok((projection, camera_transform)) = camera.get_single() {
+                let center = camera_transform.translation().xy();
+                let aabb = Aabb2 {
+                    min: center + projection.area.min,
+                    max: center + projection.area.max,
+                };
+                Self::select_in_aabb(commands, assets, grid, objects, &aabb, |object, team| {
+                    object == clicked_object && team == clicked_team
+                });
+            }
+            *last_click = None;
+        } else {
+            let (_, _, _, mut selected, mesh, _) = objects.get_mut(entity).unwrap();
+            Self::select(commands, assets, entity, &mut selected, mesh);
+            *last_click = Some((entity, now));
+        }
+    }
+
+    /// Select all visible objects in the bounding box that pass the filter.
+    fn select_in_aabb(
+        commands: &mut Commands,
+        assets: &SelectorAssets,
+        grid: &dyn EntityGrid,
+        objects: &mut SelectableObjects,
+        aabb: &Aabb2,
+        filter: impl Fn(Object, Team) -> bool,
+    ) {
+        // Check the grid for entities in this bounding box.
+        for entity in grid.get_entities_in_aabb(aabb) {
+            let (&object, transform, &team, mut selected, mesh, visibility) =
+                objects.get_mut(entity).unwrap();
+            if !aabb.contains(transform.translation.xy())
+                || selected.is_selected()
+                || *visibility == Visibility::Hidden
+                || !filter(object, team)
+            {
+                continue;
+            }
+            Self::select(commands, assets, entity, &mut selected, mesh);
+        }
+    }
+
+    /// Returns the topmost object whose mesh covers the given position.
+    fn entity_at(
+        position: Vec2,
+        grid: &dyn EntityGrid,
+        objects: &SelectableObjects,
+    ) -> Option<Entity> {
+        grid.get_entities_in_radius(position, grid.spec().width)
+            .into_iter()
+            .filter_map(|entity| {
+                let (_object, transform, _team, _selected, _mesh, _visibility) =
+                    objects.get(entity).ok()?;
+                // Object meshes are unit circles scaled by the transform.
+                let radius = transform.scale.x / 2.;
+                let distance_squared = transform.translation.xy().distance_squared(position);
+                (distance_squared <= radius * radius).then_some((entity, transform.translation.z))
+            })
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(entity, _)| entity)
+    }
+
+    /// Mark an entity as selected and highlight it.
+    fn select(
+        commands: &mut Commands,
+        assets: &SelectorAssets,
+        entity: Entity,
+        selected: &mut Selected,
+        mesh: &Mesh2dHandle,
+    ) {
+        let child_entity = commands
+            .spawn(Self::highlight_bundle(assets, mesh.0.clone()))
+            .id();
+        commands.entity(entity).add_child(child_entity);
+        *selected = Selected::Selected { child_entity };
+    }
+
+    /// Clear an entity's selection and remove its highlight.
+    fn deselect(commands: &mut Commands, selected: &mut Selected) {
+        if let Selected::Selected { child_entity } = selected {
+            commands.entity(*child_entity).despawn()
         }
+        *selected = Selected::Unselected;
+    }
+
+    fn highlight_bundle(assets: &SelectorAssets, mesh: Handle<Mesh>) -> impl Bundle {
+        (
+            Upright,
+            MaterialMesh2dBundle::<ColorMaterial> {
+                mesh: mesh.clone().into(),
+                transform: Transform::default()
+                    .with_scale(Vec2::splat(1.).extend(1.))
+                    .with_translation(Vec3 {
+                        x: 0.0,
+                        y: 0.0,
+                        z: zindex::HIGHLIGHT,
+                    }),
+                material: assets.white_material.clone(),
+                visibility: Visibility::Visible,
+                ..default()
+            },
+        )
     }
 
     fn bundle(self, assets: &SelectorAssets) -> impl Bundle {
@@ -161,3 +395,300 @@ impl FromWorld for SelectorAssets {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        ecs::system::{RunSystemOnce, SystemId},
+        input::ButtonState,
+        prelude::*,
+        sprite::Mesh2dHandle,
+    };
+
+    use crate::prelude::*;
+
+    use super::{ControlGroups, SelectedCount, Selector, SelectorAssets};
+
+    fn click(world: &mut World, selector: SystemId, from: Vec2, to: Vec2) {
+        // Held presses are repeated with the current cursor position.
+        for (state, position) in [
+            (ButtonState::Pressed, from),
+            (ButtonState::Pressed, to),
+            (ButtonState::Released, to),
+        ] {
+            world.send_event(ControlEvent {
+                action: ControlAction::Select,
+                state,
+                position,
+                entity: None,
+            });
+        }
+        world.run_system(selector).unwrap();
+        world.resource_mut::<Events<ControlEvent>>().clear();
+        world.run_system_once(SelectedCount::update);
+    }
+
+    #[test]
+    fn test_click_select() {
+        let spec = GridSpec {
+            rows: 10,
+            cols: 10,
+            width: 10.0,
+            visualize: false,
+            visualize_navigation: false,
+            ..default()
+        };
+        let mut world = World::new();
+        world.init_resource::<Assets<Mesh>>();
+        world.init_resource::<Assets<ColorMaterial>>();
+        world.init_resource::<SelectorAssets>();
+        world.init_resource::<SelectedCount>();
+        world.init_resource::<Events<ControlEvent>>();
+        world.init_resource::<Time>();
+        world.insert_resource(Configs {
+            player_team: Team::Blue,
+            double_click_seconds: 0.3,
+            ..default()
+        });
+        world.spawn((
+            MainCamera,
+            OrthographicProjection {
+                area: Rect::new(-50., -50., 50., 50.),
+                ..default()
+            },
+            GlobalTransform::default(),
+        ));
+        world.spawn((
+            Selector::default(),
+            Transform::default(),
+            Visibility::Hidden,
+        ));
+        let selector = world.register_system(Selector::update);
+
+        let mut grid = Grid2::<EntitySet>::default();
+        grid.resize_with(spec);
+        let mut spawn = |world: &mut World, team: Team, position: Vec2, z: f32| {
+            let entity = world
+                .spawn((
+                    Object::Worker,
+                    team,
+                    Selected::default(),
+                    Mesh2dHandle::default(),
+                    Visibility::Inherited,
+                    Transform::from_translation(position.extend(z)).with_scale(Vec3::splat(10.)),
+                ))
+                .id();
+            grid.update_entity(entity, None, position);
+            entity
+        };
+        let bottom = spawn(&mut world, Team::Blue, Vec2::new(10., 10.), 1.);
+        let top = spawn(&mut world, Team::Blue, Vec2::new(12., 10.), 2.);
+        let enemy = spawn(&mut world, Team::Red, Vec2::new(-20., -20.), 1.);
+        world.insert_resource(grid);
+        let is_selected =
+            |world: &World, entity| world.get::<Selected>(entity).unwrap().is_selected();
+
+        // A short drag is a click on the topmost entity.
+        click(
+            &mut world,
+            selector,
+            Vec2::new(11., 10.),
+            Vec2::new(11., 12.),
+        );
+        assert!(is_selected(&world, top));
+        assert!(!is_selected(&world, bottom));
+        assert_eq!(world.resource::<SelectedCount>(), &SelectedCount(1));
+
+        // Enemies can't be click-selected.
+        click(
+            &mut world,
+            selector,
+            Vec2::new(-20., -20.),
+            Vec2::new(-20., -20.),
+        );
+        assert!(!is_selected(&world, enemy));
+        assert!(!is_selected(&world, top));
+
+        // A longer drag box-selects.
+        click(&mut world, selector, Vec2::new(0., 0.), Vec2::new(20., 20.));
+        assert!(is_selected(&world, top));
+        assert!(is_selected(&world, bottom));
+        assert_eq!(world.resource::<SelectedCount>(), &SelectedCount(2));
+
+        // Hidden units can't be selected.
+        *world.get_mut::<Visibility>(bottom).unwrap() = Visibility::Hidden;
+        click(&mut world, selector, Vec2::new(0., 0.), Vec2::new(20., 20.));
+        assert!(is_selected(&world, top));
+        assert!(!is_selected(&world, bottom));
+        click(&mut world, selector, Vec2::new(6., 10.), Vec2::new(6., 10.));
+        assert_eq!(world.resource::<SelectedCount>(), &SelectedCount(0));
+
+        // Clicking empty space clears the selection.
+        click(
+            &mut world,
+            selector,
+            Vec2::new(30., -30.),
+            Vec2::new(30., -30.),
+        );
+        assert_eq!(world.resource::<SelectedCount>(), &SelectedCount(0));
+
+        // Double clicking selects all visible units of the same type on screen.
+        *world.get_mut::<Visibility>(bottom).unwrap() = Visibility::Inherited;
+        let head = world
+            .spawn((
+                Object::Head,
+                Team::Blue,
+                Selected::default(),
+                Mesh2dHandle::default(),
+                Visibility::Inherited,
+                Transform::from_translation(Vec3::new(-10., 10., 1.)).with_scale(Vec3::splat(10.)),
+            ))
+            .id();
+        let offscreen = world
+            .spawn((
+                Object::Worker,
+                Team::Blue,
+                Selected::default(),
+                Mesh2dHandle::default(),
+                Visibility::Inherited,
+                Transform::from_translation(Vec3::new(-45., 45., 1.)),
+            ))
+            .id();
+        let mut grid = world.resource_mut::<Grid2<EntitySet>>();
+        grid.update_entity(head, None, Vec2::new(-10., 10.));
+        grid.update_entity(offscreen, None, Vec2::new(-45., 45.));
+        world.get_mut::<Transform>(offscreen).unwrap().translation = Vec3::new(-60., 60., 1.);
+
+        click(
+            &mut world,
+            selector,
+            Vec2::new(12., 10.),
+            Vec2::new(12., 10.),
+        );
+        assert_eq!(world.resource::<SelectedCount>(), &SelectedCount(1));
+        click(
+            &mut world,
+            selector,
+            Vec2::new(12., 10.),
+            Vec2::new(12., 10.),
+        );
+        assert!(is_selected(&world, top));
+        assert!(is_selected(&world, bottom));
+        assert!(!is_selected(&world, head));
+        assert!(!is_selected(&world, offscreen));
+        assert!(!is_selected(&world, enemy));
+
+        // Clicks too far apart in time are two single clicks.
+        world.resource_mut::<Configs>().double_click_seconds = -1.;
+        click(
+            &mut world,
+            selector,
+            Vec2::new(12., 10.),
+            Vec2::new(12., 10.),
+        );
+        click(
+            &mut world,
+            selector,
+            Vec2::new(12., 10.),
+            Vec2::new(12., 10.),
+        );
+        assert_eq!(world.resource::<SelectedCount>(), &SelectedCount(1));
+
+        // Clicking an object directly selects the raycast hit, not the topmost unit.
+        let select_single = |world: &mut World, entity: Option<Entity>, from: Vec2, to: Vec2| {
+            for (state, position) in [
+                (ButtonState::Pressed, from),
+                (ButtonState::Pressed, to),
+                (ButtonState::Released, to),
+            ] {
+                world.send_event(ControlEvent {
+                    action: ControlAction::SelectSingle,
+                    state,
+                    position,
+                    entity,
+                });
+            }
+            world.run_system(selector).unwrap();
+            world.resource_mut::<Events<ControlEvent>>().clear();
+            world.run_system_once(SelectedCount::update);
+        };
+        let (from, still) = (Vec2::new(12., 10.), Vec2::new(12., 11.));
+        select_single(&mut world, Some(bottom), from, still);
+        assert!(is_selected(&world, bottom));
+        assert!(!is_selected(&world, top));
+        assert_eq!(world.resource::<SelectedCount>(), &SelectedCount(1));
+        select_single(&mut world, Some(enemy), from, still);
+        assert_eq!(world.resource::<SelectedCount>(), &SelectedCount(0));
+        // Without a hit entity, the object under the cursor is used.
+        select_single(&mut world, None, from, still);
+        assert!(is_selected(&world, top));
+        // Dragging from an object box-selects instead of clicking it.
+        select_single(
+            &mut world,
+            Some(bottom),
+            Vec2::new(15., 15.),
+            Vec2::new(5., 5.),
+        );
+        assert!(is_selected(&world, top));
+        assert!(is_selected(&world, bottom));
+        assert_eq!(world.resource::<SelectedCount>(), &SelectedCount(2));
+
+        // Box selection still works after selecting a single object.
+        click(&mut world, selector, Vec2::new(0., 0.), Vec2::new(20., 20.));
+        assert_eq!(world.resource::<SelectedCount>(), &SelectedCount(2));
+    }
+
+    #[test]
+    fn test_control_groups() {
+        let mut world = World::new();
+        world.init_resource::<Assets<Mesh>>();
+        world.init_resource::<Assets<ColorMaterial>>();
+        world.init_resource::<SelectorAssets>();
+        world.init_resource::<ControlGroups>();
+        world.init_resource::<Events<ControlEvent>>();
+        let units: Vec<Entity> = (0..3)
+            .map(|_| {
+                world
+                    .spawn((Selected::default(), Mesh2dHandle::default()))
+                    .id()
+            })
+            .collect();
+        let is_selected =
+            |world: &World, entity| world.get::<Selected>(entity).unwrap().is_selected();
+        let send = |world: &mut World, action: ControlAction| {
+            world.send_event(ControlEvent {
+                action,
+                state: ButtonState::Pressed,
+                position: Vec2::ZERO,
+                entity: None,
+            });
+            world.run_system_once(ControlGroups::update);
+            world.resource_mut::<Events<ControlEvent>>().clear();
+        };
+
+        // Ctrl+1 stores the current selection.
+        for &entity in &units[..2] {
+            let child_entity = world.spawn_empty().id();
+            *world.get_mut::<Selected>(entity).unwrap() = Selected::Selected { child_entity };
+        }
+        send(&mut world, ControlAction::AssignGroup1);
+        assert_eq!(world.resource::<ControlGroups>()[1], units[..2]);
+
+        // Pressing 1 re-selects the group.
+        for &entity in &units {
+            *world.get_mut::<Selected>(entity).unwrap() = Selected::Unselected;
+        }
+        let child_entity = world.spawn_empty().id();
+        *world.get_mut::<Selected>(units[2]).unwrap() = Selected::Selected { child_entity };
+        send(&mut world, ControlAction::RecallGroup1);
+        assert!(is_selected(&world, units[0]));
+        assert!(is_selected(&world, units[1]));
+        assert!(!is_selected(&world, units[2]));
+
+        // Dead entities are pruned.
+        world.despawn(units[0]);
+        send(&mut world, ControlAction::RecallGroup1);
+        assert_eq!(world.resource::<ControlGroups>()[1], units[1..2]);
+        assert!(is_selected(&world, units[1]));
+    }
+}
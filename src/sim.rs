@@ -0,0 +1,145 @@
+use bevy::{app::PluginGroupBuilder, input::InputPlugin, prelude::*, time::TimeUpdateStrategy};
+use bevy_hanabi::prelude::EffectAsset;
+
+use crate::{
+    config, effects,
+    grid::{self, ObstaclesSpec},
+    inputs, objects, physics,
+    physics::PhysicsMaterials,
+    prelude::*,
+    raycast, rng, waypoint,
+};
+
+/// Plugins for the simulation logic, without a window or rendering.
+/// Expects `Assets<Mesh>`, `Assets<ColorMaterial>`, and `Assets<EffectAsset>` to be registered.
+pub struct SimulationPlugins;
+impl PluginGroup for SimulationPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add(config::ConfigPlugin)
+            .add(inputs::InputActionPlugin)
+            .add(grid::GridPlugin)
+            .add(objects::ObjectsPlugin)
+            .add(physics::PhysicsPlugin)
+            .add(waypoint::WaypointPlugin)
+            .add(raycast::RaycastPlugin)
+            .add(effects::EffectsPlugin)
+            .add(rng::RngPlugin)
+    }
+}
+
+/// Build an app that runs the simulation without a window.
+/// Each `App::update` runs exactly one fixed step, so use `step_n` to advance it.
+/// Scene resources start at their defaults. After replacing them, step once so the grids
+/// are resized before spawning units.
+pub fn headless_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        InputPlugin,
+        TransformPlugin,
+        HierarchyPlugin,
+    ))
+    .init_asset::<Mesh>()
+    .init_asset::<ColorMaterial>()
+    .init_asset::<EffectAsset>()
+    .add_plugins(SimulationPlugins);
+
+    let timestep = app.world.resource::<Time<Fixed>>().timestep();
+    let mut materials = PhysicsMaterials::default();
+    for material in [
+        PhysicsMaterialType::Default,
+        PhysicsMaterialType::Zooid,
+        PhysicsMaterialType::SlowZooid,
+        PhysicsMaterialType::Plankton,
+    ] {
+        materials.insert(material, PhysicsMaterial::default());
+    }
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(timestep))
+        .insert_resource(materials)
+        .init_resource::<Configs>()
+        .init_resource::<GridSpec>()
+        .init_resource::<ObstaclesSpec>();
+    app.finish();
+    app.cleanup();
+    // The first update only initializes time, so no fixed step runs.
+    app.update();
+    app
+}
+
+/// Run `n` fixed simulation steps on an app from `headless_app`.
+pub fn step_n(app: &mut App, n: usize) {
+    for _ in 0..n {
+        app.update();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{ecs::system::RunSystemOnce, prelude::*, utils::HashMap};
+
+    use crate::{
+        objects::{InteractionConfig, ObjectSpec},
+        prelude::*,
+    };
+
+    use super::{headless_app, step_n};
+
+    #[test]
+    fn test_units_collide() {
+        let mut app = headless_app();
+        let mut config = ObjectConfig {
+            neighbor_radius: 50.,
+            death_speed: 1.,
+            ..default()
+        };
+        config.interactions.insert(
+            Object::Worker,
+            InteractionConfig {
+                separation_radius: 10.,
+                separation_acceleration: 1.,
+                damage_amount: 1,
+                ..default()
+            },
+        );
+        app.insert_resource(Configs {
+            objects: ObjectConfigs(HashMap::from_iter([(Object::Worker, config)])),
+            ..default()
+        })
+        .insert_resource(GridSpec {
+            rows: 16,
+            cols: 16,
+            width: 16.,
+            ..default()
+        });
+        step_n(&mut app, 1);
+
+        app.world.run_system_once(|mut commands: ObjectCommands| {
+            for (team, x) in [(Team::Blue, -20.), (Team::Red, 20.)] {
+                commands.spawn(ObjectSpec {
+                    object: Object::Worker,
+                    team,
+                    position: Vec2::new(x, 0.),
+                    velocity: Some(Velocity(Vec2::new(-x / 20., 0.))),
+                    ..default()
+                });
+            }
+        });
+        step_n(&mut app, 60);
+
+        let timestep = app.world.resource::<Time<Fixed>>().timestep();
+        assert_eq!(app.world.resource::<Time<Fixed>>().elapsed(), timestep * 61);
+        let bounds = app.world.resource::<GridSpec>().world2d_bounds();
+        let mut query = app.world.query::<(&Team, &Transform, &Health)>();
+        let units: Vec<_> = query.iter(&app.world).collect();
+        assert_eq!(units.len(), 2);
+        // The units charged each other, collided, and both took damage.
+        for (team, transform, health) in units {
+            let position = transform.translation.xy();
+            assert!(bounds.contains(position), "{:?} {:?}", team, position);
+            assert_ne!(position.x.abs(), 20., "{:?} didn't move", team);
+            assert!(health.health < health.max_health, "{:?}", team);
+        }
+    }
+}
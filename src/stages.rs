@@ -1,5 +1,7 @@
 use bevy::{ecs::schedule::SystemSetConfigs, prelude::*};
 
+use crate::prelude::*;
+
 /// Stage of computation
 #[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
 pub enum SystemStage {
@@ -15,14 +17,114 @@ pub enum SystemStage {
 impl SystemStage {
     pub fn get_config() -> SystemSetConfigs {
         (
-            Self::Spawn,
+            Self::Spawn.run_if(SimulationState::is_running),
             Self::PreCompute,
-            Self::Compute,
+            Self::Compute.run_if(SimulationState::is_running),
             Self::PostCompute,
-            Self::Apply,
+            Self::Apply.run_if(SimulationState::is_running),
             Self::PostApply,
-            Self::Despawn,
+            Self::Despawn.run_if(SimulationState::is_running),
         )
             .chain()
     }
 }
+
+/// Whether the simulation stages run each fixed step.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimulationState {
+    #[default]
+    Running,
+    Paused,
+    /// Run a single fixed step, then pause.
+    Stepping,
+}
+impl SimulationState {
+    /// Run condition for the simulation stages.
+    pub fn is_running(state: Option<Res<Self>>) -> bool {
+        state.is_none_or(|state| *state != Self::Paused)
+    }
+
+    /// Toggle pause and step frames from control inputs.
+    pub fn update(mut state: ResMut<Self>, mut control_events: EventReader<ControlEvent>) {
+        for control in control_events.read() {
+            if control.is_pressed(ControlAction::TogglePause) {
+                *state = match *state {
+                    Self::Running => Self::Paused,
+                    Self::Paused | Self::Stepping => Self::Running,
+                };
+            } else if control.is_pressed(ControlAction::StepFrame) && *state == Self::Paused {
+                *state = Self::Stepping;
+            }
+        }
+    }
+
+    /// Pause again once a stepped frame has run.
+    pub fn finish_step(mut state: ResMut<Self>) {
+        if *state == Self::Stepping {
+            *state = Self::Paused;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{input::ButtonState, prelude::*};
+
+    use crate::prelude::*;
+
+    use super::SimulationState;
+
+    #[derive(Resource, Default)]
+    struct Steps(usize);
+
+    fn count(mut steps: ResMut<Steps>) {
+        steps.0 += 1;
+    }
+
+    fn press(app: &mut App, action: ControlAction) {
+        app.world.send_event(ControlEvent {
+            action,
+            state: ButtonState::Pressed,
+            position: Vec2::ZERO,
+            entity: None,
+        });
+    }
+
+    #[test]
+    fn test_pause_and_step() {
+        let mut app = App::new();
+        app.add_event::<ControlEvent>()
+            .init_resource::<SimulationState>()
+            .init_resource::<Steps>()
+            .configure_sets(Update, SystemStage::get_config())
+            .add_systems(
+                Update,
+                (
+                    SimulationState::update.before(SystemStage::Spawn),
+                    count.in_set(SystemStage::Compute),
+                    SimulationState::finish_step.after(SystemStage::Despawn),
+                ),
+            );
+
+        app.update();
+        assert_eq!(app.world.resource::<Steps>().0, 1);
+
+        press(&mut app, ControlAction::TogglePause);
+        app.update();
+        app.update();
+        assert_eq!(app.world.resource::<Steps>().0, 1);
+
+        press(&mut app, ControlAction::StepFrame);
+        app.update();
+        assert_eq!(
+            *app.world.resource::<SimulationState>(),
+            SimulationState::Paused
+        );
+        app.update();
+        assert_eq!(app.world.resource::<Steps>().0, 2);
+
+        press(&mut app, ControlAction::TogglePause);
+        app.update();
+        assert_eq!(app.world.resource::<Steps>().0, 3);
+    }
+}
@@ -1,7 +1,15 @@
 use std::f32::consts::PI;
 
-use crate::{grid::CreateWaypointEvent, prelude::*};
-use bevy::{prelude::*, sprite::MaterialMesh2dBundle, utils::hashbrown::HashSet};
+use crate::{
+    grid::{CreateWaypointEvent, NavigationGrid2, SparseFlowGrid2},
+    prelude::*,
+};
+use bevy::{
+    prelude::*,
+    render::{mesh::PrimitiveTopology, render_asset::RenderAssetUsages},
+    sprite::MaterialMesh2dBundle,
+    utils::hashbrown::HashSet,
+};
 
 /// Plugin to add a waypoint system where the player can click to create a waypoint.
 pub struct WaypointPlugin;
@@ -11,14 +19,24 @@ impl Plugin for WaypointPlugin {
             FixedUpdate,
             (
                 Waypoint::update.in_set(SystemStage::PostApply),
-                Waypoint::cleanup
+                Waypoint::advance
                     .in_set(SystemStage::PostApply)
                     .after(Waypoint::update),
+                Waypoint::cleanup
+                    .in_set(SystemStage::PostApply)
+                    .after(Waypoint::advance),
+                PathPreview::update
+                    .in_set(SystemStage::PostApply)
+                    .after(NavigationGrid2::create_waypoints),
             ),
         );
     }
 }
 
+/// Marks an invisible waypoint for a single unit's place in a group formation.
+#[derive(Component, Default, Debug)]
+pub struct FormationSlot;
+
 #[derive(Component, Debug)]
 pub struct Waypoint {
     pub active: bool,
@@ -33,6 +51,17 @@ impl Default for Waypoint {
     }
 }
 impl Waypoint {
+    /// All entities followed by any objective, including queued ones.
+    pub fn followed_entities<'a>(
+        all_objectives: impl IntoIterator<Item = &'a Objectives>,
+    ) -> HashSet<Entity> {
+        all_objectives
+            .into_iter()
+            .flat_map(Objectives::iter)
+            .filter_map(Objective::get_followed_entity)
+            .collect()
+    }
+
     pub fn cleanup(
         all_objectives: Query<&Objectives, Without<Waypoint>>,
         waypoints: Query<Entity, With<Waypoint>>,
@@ -43,18 +72,14 @@ impl Waypoint {
             action,
             state: _,
             position: _,
+            entity: _,
         } in input_actions.read()
         {
-            if action != ControlAction::Move {
+            if action != ControlAction::Move && action != ControlAction::QueueMove {
                 continue;
             }
 
-            let mut followed_entities = HashSet::new();
-            for objectives in all_objectives.iter() {
-                if let Some(entity) = objectives.last().get_followed_entity() {
-                    followed_entities.insert(entity);
-                }
-            }
+            let followed_entities = Self::followed_entities(&all_objectives);
             for entity in waypoints.iter() {
                 if !followed_entities.contains(&entity) {
                     commands.entity(entity).despawn();
@@ -63,40 +88,185 @@ impl Waypoint {
         }
     }
 
+    /// Offsets for a loose formation, filling square rings outward from the center.
+    pub fn formation_offsets(count: usize, spacing: f32) -> Vec<Vec2> {
+        let mut offsets = Vec::with_capacity(count);
+        let mut ring = 0;
+        while offsets.len() < count {
+            let mut ring_offsets: Vec<IVec2> = (-ring..=ring)
+                .flat_map(|x| (-ring..=ring).map(move |y| IVec2::new(x, y)))
+                .filter(|offset| offset.x.abs().max(offset.y.abs()) == ring)
+                .collect();
+            ring_offsets.sort_by_key(|offset| offset.length_squared());
+            let remaining = count - offsets.len();
+            offsets.extend(
+                ring_offsets
+                    .into_iter()
+                    .take(remaining)
+                    .map(|offset| offset.as_vec2() * spacing),
+            );
+            ring += 1;
+        }
+        offsets
+    }
+
+    /// Offsets of each position from the position closest to the centroid.
+    /// That position gets a zero offset, so it takes the center slot.
+    pub fn relative_offsets(positions: &[Vec2]) -> Vec<Vec2> {
+        if positions.is_empty() {
+            return Vec::new();
+        }
+        let centroid = positions.iter().sum::<Vec2>() / positions.len() as f32;
+        let center = positions
+            .iter()
+            .copied()
+            .min_by(|a, b| {
+                a.distance_squared(centroid)
+                    .total_cmp(&b.distance_squared(centroid))
+            })
+            .unwrap();
+        positions
+            .iter()
+            .map(|&position| position - center)
+            .collect()
+    }
+
+    /// Send selected units to a new waypoint, each to its own slot in a formation.
+    /// Queued moves keep the current orders and head to the waypoint afterwards.
+    #[allow(clippy::too_many_arguments)]
     pub fn update(
         mut control_events: EventReader<ControlEvent>,
         mut selection: Query<(&Selected, &mut Objectives, &Transform), Without<Self>>,
+        waypoints: Query<&Transform, With<Self>>,
         mut event_writer: EventWriter<CreateWaypointEvent>,
         mut commands: Commands,
         assets: Res<WaypointAssets>,
+        configs: Res<Configs>,
+        spec: Res<GridSpec>,
     ) {
         for control in control_events.read() {
-            if !control.is_pressed(ControlAction::Move) {
+            let queue = control.is_pressed(ControlAction::QueueMove);
+            if !queue && !control.is_pressed(ControlAction::Move) {
                 continue;
             }
 
-            // Spawn a new waypoint.
+            // Spawn a new waypoint, dimmed if it waits behind other orders.
             let waypoint_bundle =
                 Waypoint::default().bundle(&assets, control.position.extend(zindex::WAYPOINT));
             let entity = commands.spawn(waypoint_bundle).id();
+            let waiting = queue
+                && selection.iter().any(|(selected, objectives, _)| {
+                    selected.is_selected() && objectives.last() != &Objective::None
+                });
+            if waiting {
+                commands
+                    .entity(entity)
+                    .insert(assets.queued_material.clone());
+            }
 
-            let mut sources = Vec::new();
-            for (selected, mut objectives, transform) in selection.iter_mut() {
-                if selected.is_selected() {
+            let positions: Vec<Vec2> = selection
+                .iter()
+                .filter(|(selected, _, _)| selected.is_selected())
+                .map(|(_, _, transform)| transform.translation.xy())
+                .collect();
+            let offsets = if configs.preserve_formation {
+                Self::relative_offsets(&positions)
+            } else {
+                Self::formation_offsets(positions.len(), spec.width)
+            };
+            let selected_units = selection
+                .iter_mut()
+                .filter(|(selected, _, _)| selected.is_selected());
+            for ((_, mut objectives, transform), offset) in selected_units.zip(offsets) {
+                // The center slot uses the visible waypoint.
+                let mut destination = control.position + offset;
+                spec.world2d_bounds_eps().clamp2(&mut destination);
+                let slot_entity = if offset == Vec2::ZERO {
+                    entity
+                } else {
+                    commands
+                        .spawn((
+                            Waypoint::default(),
+                            FormationSlot,
+                            TransformBundle::from_transform(Transform::from_translation(
+                                destination.extend(zindex::WAYPOINT),
+                            )),
+                            Velocity::ZERO,
+                        ))
+                        .id()
+                };
+                // Queued waypoints are reached from the end of the current orders.
+                let source = if queue {
+                    let previous = objectives
+                        .last_queued()
+                        .get_followed_entity()
+                        .and_then(|entity| waypoints.get(entity).ok());
+                    objectives.queue(Objective::FollowEntity(slot_entity));
+                    previous.unwrap_or(transform).translation.xy()
+                } else {
                     objectives.clear();
-                    objectives.push(Objective::FollowEntity(entity));
-                    sources.push(transform.translation.xy());
-                }
-            }
-            if !sources.is_empty() {
+                    objectives.push(Objective::FollowEntity(slot_entity));
+                    transform.translation.xy()
+                };
                 event_writer.send(CreateWaypointEvent {
-                    sources,
-                    destination: control.position,
+                    sources: vec![source],
+                    destination,
+                    team: configs.player_team,
                 });
             }
         }
     }
 
+    /// Pop units that reached their waypoint on to their next queued objective.
+    /// Reached waypoints that no unit follows anymore are despawned.
+    pub fn advance(
+        mut units: Query<(&mut Objectives, &Transform), Without<Self>>,
+        mut waypoints: Query<(&Transform, Option<&mut Handle<ColorMaterial>>), With<Self>>,
+        assets: Res<WaypointAssets>,
+        spec: Res<GridSpec>,
+        mut commands: Commands,
+    ) {
+        let mut reached = HashSet::new();
+        for (mut objectives, transform) in &mut units {
+            if !objectives.has_queued() {
+                continue;
+            }
+            let Some(waypoint) = objectives.last().get_followed_entity() else {
+                continue;
+            };
+            let Ok((waypoint_transform, _)) = waypoints.get(waypoint) else {
+                continue;
+            };
+            let distance = transform
+                .translation
+                .xy()
+                .distance(waypoint_transform.translation.xy());
+            if distance >= spec.width {
+                continue;
+            }
+            objectives.pop();
+            reached.insert(waypoint);
+
+            // The next waypoint is no longer waiting.
+            if let Some((_, Some(mut material))) = objectives
+                .last()
+                .get_followed_entity()
+                .and_then(|entity| waypoints.get_mut(entity).ok())
+            {
+                *material = assets.blue_material.clone();
+            }
+        }
+        if reached.is_empty() {
+            return;
+        }
+        let followed_entities = Self::followed_entities(units.iter().map(|(o, _)| o));
+        for entity in reached {
+            if !followed_entities.contains(&entity) {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+
     pub fn bundle(self, assets: &WaypointAssets, translation: Vec3) -> impl Bundle {
         (
             MaterialMesh2dBundle::<ColorMaterial> {
@@ -114,11 +284,101 @@ impl Waypoint {
     }
 }
 
+/// Line showing the route a selected unit takes to its waypoint.
+#[derive(Component, Debug)]
+pub struct PathPreview {
+    pub unit: Entity,
+    pub destination: Vec2,
+}
+impl PathPreview {
+    /// Returns points from `source` through each cell along the flow to `destination`.
+    pub fn points(source: Vec2, destination: Vec2, flow: &SparseFlowGrid2) -> Option<Vec<Vec2>> {
+        let spec = &flow.spec;
+        let path = flow.flow_path(spec.to_rowcol(source), spec.to_rowcol(destination))?;
+        let mut points = vec![source];
+        let inner = &path[1..path.len().saturating_sub(1).max(1)];
+        points.extend(inner.iter().map(|&rowcol| spec.to_world_position(rowcol)));
+        points.push(destination);
+        Some(points)
+    }
+
+    /// Build a line strip mesh through the given points.
+    pub fn mesh(points: &[Vec2]) -> Mesh {
+        let positions: Vec<Vec3> = points.iter().map(|point| point.extend(0.)).collect();
+        Mesh::new(PrimitiveTopology::LineStrip, RenderAssetUsages::default())
+            .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    }
+
+    /// Draw paths for the selected units when a move is ordered, along their team's flow.
+    /// Paths are removed on the next order or once their unit arrives.
+    #[allow(clippy::too_many_arguments)]
+    pub fn update(
+        mut control_events: EventReader<ControlEvent>,
+        selection: Query<(Entity, &Selected, &Objectives, &Transform, &Team), Without<Waypoint>>,
+        waypoints: Query<&Transform, With<Waypoint>>,
+        previews: Query<(Entity, &Self)>,
+        navigation: Res<NavigationGrid2>,
+        spec: Res<GridSpec>,
+        configs: Res<Configs>,
+        assets: Res<WaypointAssets>,
+        mut meshes: ResMut<Assets<Mesh>>,
+        mut commands: Commands,
+    ) {
+        let moved = control_events
+            .read()
+            .any(|control| control.is_pressed(ControlAction::Move));
+        for (entity, preview) in &previews {
+            let arrived = selection
+                .get(preview.unit)
+                .map_or(true, |(_, _, _, transform, _)| {
+                    transform.translation.xy().distance(preview.destination) < spec.width
+                });
+            if moved || arrived {
+                commands.entity(entity).despawn();
+            }
+        }
+        if !moved || !configs.show_paths {
+            return;
+        }
+
+        for (unit, selected, objectives, transform, &team) in &selection {
+            if !selected.is_selected() {
+                continue;
+            }
+            let Some(waypoint) = objectives
+                .last()
+                .get_followed_entity()
+                .and_then(|entity| waypoints.get(entity).ok())
+            else {
+                continue;
+            };
+            let destination = waypoint.translation.xy();
+            let source = transform.translation.xy();
+            let Some(points) = navigation
+                .get(&(team, spec.to_rowcol(destination)))
+                .and_then(|nav| Self::points(source, destination, &nav.grid))
+            else {
+                continue;
+            };
+            commands.spawn((
+                Self { unit, destination },
+                MaterialMesh2dBundle::<ColorMaterial> {
+                    mesh: meshes.add(Self::mesh(&points)).into(),
+                    transform: Transform::from_xyz(0., 0., zindex::PATH_PREVIEW),
+                    material: assets.blue_material.clone(),
+                    ..default()
+                },
+            ));
+        }
+    }
+}
+
 /// Handles to common grid assets.
 #[derive(Resource)]
 pub struct WaypointAssets {
     pub mesh: Handle<Mesh>,
     pub blue_material: Handle<ColorMaterial>,
+    pub queued_material: Handle<ColorMaterial>,
 }
 impl FromWorld for WaypointAssets {
     fn from_world(world: &mut World) -> Self {
@@ -135,6 +395,325 @@ impl FromWorld for WaypointAssets {
         Self {
             mesh,
             blue_material: materials.add(ColorMaterial::from(Color::TURQUOISE.with_a(0.5))),
+            queued_material: materials.add(ColorMaterial::from(Color::TURQUOISE.with_a(0.2))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{ecs::system::RunSystemOnce, input::ButtonState, prelude::*, utils::HashSet};
+
+    use crate::{
+        grid::{
+            CreateWaypointEvent, NavigationConfig, NavigationCostEvent, NavigationGrid2,
+            TeamInfluence,
+        },
+        prelude::*,
+    };
+
+    use super::{FormationSlot, PathPreview, Waypoint, WaypointAssets};
+
+    #[test]
+    fn test_formation_offsets() {
+        assert_eq!(Waypoint::formation_offsets(0, 10.), vec![]);
+        assert_eq!(Waypoint::formation_offsets(1, 10.), vec![Vec2::ZERO]);
+
+        // Cardinal neighbors fill in before corners.
+        let offsets = Waypoint::formation_offsets(5, 10.);
+        assert_eq!(offsets[0], Vec2::ZERO);
+        assert!(offsets[1..].iter().all(|offset| offset.length() == 10.));
+
+        // Offsets are unique and the formation grows with the number of units.
+        for count in [9, 10, 30] {
+            let offsets = Waypoint::formation_offsets(count, 10.);
+            assert_eq!(offsets.len(), count);
+            let unique: HashSet<IVec2> = offsets.iter().map(|offset| offset.as_ivec2()).collect();
+            assert_eq!(unique.len(), count);
+        }
+        let extent = |count| {
+            Waypoint::formation_offsets(count, 10.)
+                .iter()
+                .map(|offset| offset.x.abs().max(offset.y.abs()))
+                .fold(0., f32::max)
+        };
+        assert_eq!(extent(9), 10.);
+        assert_eq!(extent(10), 20.);
+    }
+
+    #[test]
+    fn test_group_move_preserves_formation() {
+        assert_eq!(Waypoint::relative_offsets(&[]), vec![]);
+
+        let mut world = World::new();
+        world.insert_resource(GridSpec {
+            rows: 10,
+            cols: 10,
+            width: 10.0,
+            ..default()
+        });
+        world.insert_resource(Configs {
+            player_team: Team::Blue,
+            preserve_formation: true,
+            ..default()
+        });
+        world.init_resource::<Assets<Mesh>>();
+        world.init_resource::<Assets<ColorMaterial>>();
+        world.init_resource::<WaypointAssets>();
+        world.init_resource::<Events<ControlEvent>>();
+        world.init_resource::<Events<CreateWaypointEvent>>();
+        let selected = Selected::Selected {
+            child_entity: Entity::PLACEHOLDER,
+        };
+        let units: Vec<Entity> = [Vec2::new(-20., 0.), Vec2::new(0., 0.), Vec2::new(20., 30.)]
+            .into_iter()
+            .map(|position| {
+                world
+                    .spawn((
+                        selected.clone(),
+                        Objectives::default(),
+                        Transform::from_translation(position.extend(0.)),
+                    ))
+                    .id()
+            })
+            .collect();
+        world.send_event(ControlEvent {
+            action: ControlAction::Move,
+            state: ButtonState::Pressed,
+            position: Vec2::new(30., 0.),
+            entity: None,
+        });
+        world.run_system_once(Waypoint::update);
+
+        // Each unit follows its own slot, offset from the destination like it was from the unit
+        // closest to the centroid. Slots outside the grid are clamped in bounds.
+        let destinations: Vec<Vec2> = units
+            .iter()
+            .map(|&unit| {
+                let Objective::FollowEntity(slot) = world.get::<Objectives>(unit).unwrap().last()
+                else {
+                    panic!("Unit isn't following a waypoint.");
+                };
+                world.get::<Transform>(*slot).unwrap().translation.xy()
+            })
+            .collect();
+        assert_eq!(
+            destinations,
+            vec![Vec2::new(10., 0.), Vec2::new(30., 0.), Vec2::new(40., 30.)]
+        );
+        let events = world.resource::<Events<CreateWaypointEvent>>();
+        let sent: Vec<Vec2> = events
+            .get_reader()
+            .read(events)
+            .map(|event| event.destination)
+            .collect();
+        assert_eq!(sent, destinations);
+
+        // The center unit follows the visible waypoint, so cleanup keeps it.
+        let followed = |world: &World, unit| {
+            world
+                .get::<Objectives>(unit)
+                .unwrap()
+                .last()
+                .get_followed_entity()
+                .unwrap()
+        };
+        let visible = followed(&world, units[1]);
+        assert!(world.get::<FormationSlot>(visible).is_none());
+        assert!(units
+            .iter()
+            .all(|&unit| unit == units[1] || followed(&world, unit) != visible));
+        world.run_system_once(Waypoint::cleanup);
+        assert!(world.get_entity(visible).is_some());
+    }
+
+    #[test]
+    fn test_queue_waypoints() {
+        let mut world = World::new();
+        world.insert_resource(GridSpec {
+            rows: 10,
+            cols: 10,
+            width: 10.0,
+            ..default()
+        });
+        world.insert_resource(Configs {
+            player_team: Team::Blue,
+            ..default()
+        });
+        world.init_resource::<Assets<Mesh>>();
+        world.init_resource::<Assets<ColorMaterial>>();
+        world.init_resource::<WaypointAssets>();
+        world.init_resource::<Events<ControlEvent>>();
+        world.init_resource::<Events<CreateWaypointEvent>>();
+        let unit = world
+            .spawn((
+                Selected::Selected {
+                    child_entity: Entity::PLACEHOLDER,
+                },
+                Objectives::default(),
+                Transform::default(),
+            ))
+            .id();
+        let mut schedule = Schedule::default();
+        schedule.add_systems((Waypoint::update, Waypoint::advance, Waypoint::cleanup).chain());
+        let order = |world: &mut World, action, position| {
+            world.resource_mut::<Events<ControlEvent>>().clear();
+            world.send_event(ControlEvent {
+                action,
+                state: ButtonState::Pressed,
+                position,
+                entity: None,
+            });
+        };
+        let followed = |world: &World| -> Vec<Entity> {
+            world
+                .get::<Objectives>(unit)
+                .unwrap()
+                .iter()
+                .filter_map(Objective::get_followed_entity)
+                .collect()
+        };
+
+        order(&mut world, ControlAction::Move, Vec2::new(20., 0.));
+        schedule.run(&mut world);
+        order(&mut world, ControlAction::QueueMove, Vec2::new(20., 20.));
+        schedule.run(&mut world);
+        let [second, first] = followed(&world)[..] else {
+            panic!("Expected two waypoints.");
+        };
+        let assets = world.resource::<WaypointAssets>();
+        let (blue, queued) = (assets.blue_material.clone(), assets.queued_material.clone());
+        assert_eq!(world.get::<Handle<ColorMaterial>>(first), Some(&blue));
+        assert_eq!(world.get::<Handle<ColorMaterial>>(second), Some(&queued));
+
+        // The queued flow starts from the first waypoint.
+        let events = world.resource::<Events<CreateWaypointEvent>>();
+        let sent: Vec<(Vec<Vec2>, Vec2)> = events
+            .get_reader()
+            .read(events)
+            .map(|event| (event.sources.clone(), event.destination))
+            .collect();
+        assert_eq!(
+            sent,
+            vec![
+                (vec![Vec2::ZERO], Vec2::new(20., 0.)),
+                (vec![Vec2::new(20., 0.)], Vec2::new(20., 20.)),
+            ]
+        );
+
+        // Reaching the first waypoint pops to the queued one and removes the reached waypoint.
+        world.resource_mut::<Events<ControlEvent>>().clear();
+        world.get_mut::<Transform>(unit).unwrap().translation = Vec3::new(18., 0., 0.);
+        schedule.run(&mut world);
+        assert_eq!(followed(&world), vec![second]);
+        assert!(world.get_entity(first).is_none());
+        assert_eq!(world.get::<Handle<ColorMaterial>>(second), Some(&blue));
+
+        // The last waypoint stays until a new order replaces it.
+        world.get_mut::<Transform>(unit).unwrap().translation = Vec3::new(20., 20., 0.);
+        schedule.run(&mut world);
+        assert_eq!(followed(&world), vec![second]);
+        order(&mut world, ControlAction::Move, Vec2::ZERO);
+        schedule.run(&mut world);
+        assert!(world.get_entity(second).is_none());
+    }
+
+    #[test]
+    fn test_path_preview() {
+        let spec = GridSpec {
+            rows: 10,
+            cols: 10,
+            width: 10.0,
+            ..default()
+        };
+        let mut obstacles = Grid2::<Obstacle>::default();
+        obstacles.resize_with(spec.clone());
+        // Wall off the direct route so the path goes around it.
+        for row in 3..=6 {
+            obstacles[(row, 5)] = Obstacle::Full;
         }
+        let source = Vec2::new(-12., 0.);
+        let destination = Vec2::new(18., 0.);
+        let mut influence = Grid2::<TeamInfluence>::default();
+        influence.resize_with(spec.clone());
+
+        let mut world = World::new();
+        world.insert_resource(spec.clone());
+        world.insert_resource(obstacles.clone());
+        world.insert_resource(influence);
+        world.insert_resource(Configs {
+            player_team: Team::Blue,
+            show_paths: true,
+            ..default()
+        });
+        world.init_resource::<Assets<Mesh>>();
+        world.init_resource::<Assets<ColorMaterial>>();
+        world.init_resource::<WaypointAssets>();
+        world.init_resource::<NavigationGrid2>();
+        world.init_resource::<NavigationConfig>();
+        world.init_resource::<Events<ControlEvent>>();
+        world.init_resource::<Events<CreateWaypointEvent>>();
+        world.init_resource::<Events<NavigationCostEvent>>();
+        let unit = world
+            .spawn((
+                Selected::Selected {
+                    child_entity: Entity::PLACEHOLDER,
+                },
+                Objectives::default(),
+                Transform::from_translation(source.extend(0.)),
+                Team::Blue,
+            ))
+            .id();
+        let mut schedule = Schedule::default();
+        schedule.add_systems(
+            (
+                Waypoint::update,
+                NavigationGrid2::create_waypoints,
+                PathPreview::update,
+            )
+                .chain(),
+        );
+        let previews = |world: &mut World| {
+            let mut query = world.query::<&PathPreview>();
+            query
+                .iter(world)
+                .map(|preview| (preview.unit, preview.destination))
+                .collect::<Vec<_>>()
+        };
+        let move_to = |world: &mut World, position: Vec2| {
+            world.send_event(ControlEvent {
+                action: ControlAction::Move,
+                state: ButtonState::Pressed,
+                position,
+                entity: None,
+            });
+        };
+
+        move_to(&mut world, destination);
+        schedule.run(&mut world);
+        assert_eq!(previews(&mut world), vec![(unit, destination)]);
+
+        // The path follows the flow units navigate with around the wall.
+        let navigation = world.resource::<NavigationGrid2>();
+        let nav = &navigation[&(Team::Blue, spec.to_rowcol(destination))];
+        let points = PathPreview::points(source, destination, &nav.grid).unwrap();
+        assert_eq!(points.first(), Some(&source));
+        assert_eq!(points.last(), Some(&destination));
+        assert!(points
+            .iter()
+            .all(|&point| obstacles[spec.to_rowcol(point)] == Obstacle::Empty));
+        assert!(points.iter().any(|point| point.y.abs() > 10.));
+
+        // A new order replaces the old path.
+        world.resource_mut::<Events<ControlEvent>>().clear();
+        move_to(&mut world, Vec2::new(18., 20.));
+        schedule.run(&mut world);
+        assert_eq!(previews(&mut world), vec![(unit, Vec2::new(18., 20.))]);
+
+        // Paths are removed once the unit arrives.
+        world.resource_mut::<Events<ControlEvent>>().clear();
+        world.get_mut::<Transform>(unit).unwrap().translation = Vec3::new(18., 20., 0.);
+        schedule.run(&mut world);
+        assert_eq!(previews(&mut world), vec![]);
     }
 }
@@ -3,7 +3,7 @@ use bevy::{
     window::{Cursor, PresentMode, PrimaryWindow, WindowMode, WindowTheme},
 };
 
-use crate::prelude::Configs;
+use crate::prelude::*;
 
 pub trait ScalableWindow {
     fn scaled_size(&self) -> Vec2;
@@ -30,12 +30,8 @@ pub fn custom_plugin() -> WindowPlugin {
             // Tells wasm not to override default event handling, like F5, Ctrl+R etc.
             prevent_default_event_handling: false,
             window_theme: Some(WindowTheme::Dark),
-            enabled_buttons: bevy::window::EnabledButtons {
-                maximize: false,
-                ..Default::default()
-            },
             visible: true,
-            resizable: false,
+            resizable: true,
             mode: WindowMode::BorderlessFullscreen,
             ..default()
         }),
@@ -43,15 +39,80 @@ pub fn custom_plugin() -> WindowPlugin {
     }
 }
 
-pub fn resize_window(mut query: Query<&mut Window, With<PrimaryWindow>>, configs: Res<Configs>) {
-    if configs.is_changed() {
-        let mut window = query.single_mut();
-        let scale_factor = window.scale_factor();
-        if configs.window_size != Vec2::ZERO {
-            window.resolution.set_physical_resolution(
-                (configs.window_size.x * scale_factor) as u32,
-                (configs.window_size.y * scale_factor) as u32,
-            );
+/// Returns the mode to switch to when toggling fullscreen.
+pub fn toggled_mode(mode: WindowMode) -> WindowMode {
+    match mode {
+        WindowMode::Windowed => WindowMode::BorderlessFullscreen,
+        _ => WindowMode::Windowed,
+    }
+}
+
+/// Toggle fullscreen from control inputs, and apply `Configs.window_size` in windowed mode.
+/// Fullscreen windows always match the monitor, so the configured size only applies when windowed.
+pub fn resize_window(
+    mut query: Query<&mut Window, With<PrimaryWindow>>,
+    configs: Res<Configs>,
+    mut control_events: EventReader<ControlEvent>,
+) {
+    let Ok(mut window) = query.get_single_mut() else {
+        return;
+    };
+    let mut mode_changed = false;
+    for control in control_events.read() {
+        if control.is_pressed(ControlAction::ToggleFullscreen) {
+            window.mode = toggled_mode(window.mode);
+            mode_changed = true;
         }
     }
+    if (mode_changed || configs.is_changed())
+        && window.mode == WindowMode::Windowed
+        && configs.window_size != Vec2::ZERO
+    {
+        window
+            .resolution
+            .set(configs.window_size.x, configs.window_size.y);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{ecs::system::RunSystemOnce, input::ButtonState, prelude::*};
+
+    use crate::prelude::*;
+
+    use super::{custom_plugin, resize_window, PrimaryWindow, WindowMode};
+
+    #[test]
+    fn test_toggle_fullscreen() {
+        let mut world = World::new();
+        world.init_resource::<Events<ControlEvent>>();
+        world.insert_resource(Configs {
+            window_size: Vec2::new(800., 600.),
+            ..default()
+        });
+        let window = world
+            .spawn((custom_plugin().primary_window.unwrap(), PrimaryWindow))
+            .id();
+        let toggle = |world: &mut World| {
+            world.send_event(ControlEvent {
+                action: ControlAction::ToggleFullscreen,
+                state: ButtonState::Pressed,
+                position: Vec2::ZERO,
+                entity: None,
+            });
+            world.run_system_once(resize_window);
+            world.resource_mut::<Events<ControlEvent>>().clear();
+            world.get::<Window>(window).unwrap().clone()
+        };
+
+        // Leaving fullscreen applies the configured size.
+        let windowed = toggle(&mut world);
+        assert_eq!(windowed.mode, WindowMode::Windowed);
+        let size = Vec2::new(windowed.resolution.width(), windowed.resolution.height());
+        assert_eq!(size, Vec2::new(800., 600.));
+        assert!(windowed.resizable);
+
+        let fullscreen = toggle(&mut world);
+        assert_eq!(fullscreen.mode, WindowMode::BorderlessFullscreen);
+    }
 }
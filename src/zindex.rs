@@ -10,11 +10,16 @@ pub const ZOOID_HEAD: f32 = 0.0;
 pub const ZOOIDS_MIN: f32 = 1.0;
 pub const ZOOIDS_MAX: f32 = 10.0;
 pub const FOOD: f32 = 10.5;
+// Relative to the parent object.
+pub const HEALTH_BAR: f32 = 0.05;
+pub const OBJECTIVE_DEBUGGER: f32 = 1.0;
 
 pub const NAVIGATION_LAYER: f32 = 11.0;
 pub const HIGHLIGHT: f32 = 15.0;
 pub const FOG_OF_WAR: f32 = 20.0;
+pub const PATH_PREVIEW: f32 = 21.0;
 pub const WAYPOINT: f32 = 22.0;
+pub const GRID_COORDS: f32 = 23.0;
 pub const MINIMAP: f32 = 25.0;
 pub const SELECTOR: f32 = 30.0;
 pub const CURSOR: f32 = 35.0;